@@ -37,10 +37,10 @@ fn main() -> Result<()> {
             let sample_rows = data.len().min(3);
             let sample_cols = if data.is_empty() { 0 } else { data[0].len().min(4) };
             
-            for i in 0..sample_rows {
+            for row in data.iter().take(sample_rows) {
                 print!("  ");
-                for j in 0..sample_cols {
-                    if let Some(val) = &data[i][j] {
+                for (j, cell) in row.iter().take(sample_cols).enumerate() {
+                    if let Some(val) = cell {
                         print!("{}", val.chars().take(12).collect::<String>());
                     } else {
                         print!("<missing>");
@@ -0,0 +1,171 @@
+use crate::{select, Result};
+use clap::{Args, ValueEnum};
+use libxtabml::{FlatOptions, HtmlOptions, Layout, RenderOptions, TextOptions, XtabML};
+use std::io::Write;
+use std::path::Path;
+
+/// Output formats of `convert`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+    Xlsx,
+    Html,
+    Md,
+    Tex,
+    Txt,
+    Parquet,
+}
+
+impl Format {
+    /// Infer the format from a file extension
+    fn from_path(path: &str) -> Option<Format> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "xlsx" => Some(Format::Xlsx),
+            "html" | "htm" => Some(Format::Html),
+            "md" | "markdown" => Some(Format::Md),
+            "tex" => Some(Format::Tex),
+            "txt" => Some(Format::Txt),
+            "parquet" => Some(Format::Parquet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LayoutArg {
+    Long,
+    Wide,
+}
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Output file, or "-" for standard output
+    #[arg(short, long, default_value = "-")]
+    output: String,
+
+    /// Output format; inferred from the output file extension by default
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+
+    /// Query selecting and slicing tables, e.g. 'title contains "Age" | rows "NET"'
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Tables to convert: a 1-based index, a table name, or a regular expression matched
+    /// against the titles (repeatable); all tables by default
+    #[arg(short, long = "table", value_name = "TABLE")]
+    tables: Vec<String>,
+
+    /// Statistic types to convert, in this order (repeatable); all by default
+    #[arg(short, long = "stat", value_name = "TYPE")]
+    statistics: Vec<String>,
+
+    /// Layout of CSV, Excel and Parquet output
+    #[arg(short, long, value_enum, default_value = "long")]
+    layout: LayoutArg,
+}
+
+pub fn convert(xtab: &XtabML, args: &ConvertArgs) -> Result<()> {
+    let format = match args.format {
+        Some(format) => format,
+        None if args.output == "-" => {
+            return Err("--format is required when writing to standard output".into())
+        }
+        None => Format::from_path(&args.output)
+            .ok_or_else(|| format!("cannot infer the format of {:?}, use --format", args.output))?,
+    };
+
+    // Table selectors refer to the tables left by the query
+    let filtered;
+    let xtab = match &args.query {
+        Some(query) => {
+            filtered = xtab.filter(&query.parse()?);
+            &filtered
+        }
+        None => xtab,
+    };
+
+    let mut tables = Vec::new();
+    for selector in &args.tables {
+        for index in select::tables(xtab, selector)? {
+            if !tables.contains(&index) {
+                tables.push(index);
+            }
+        }
+    }
+    let statistics = (!args.statistics.is_empty()).then(|| args.statistics.clone());
+    let flat = FlatOptions {
+        layout: match args.layout {
+            LayoutArg::Long => Layout::Long,
+            LayoutArg::Wide => Layout::Wide,
+        },
+        tables: (!tables.is_empty()).then(|| tables.clone()),
+        statistics: statistics.clone(),
+    };
+
+    let bytes = match format {
+        Format::Csv => xtab.to_csv(&flat).into_bytes(),
+        #[cfg(feature = "xlsx")]
+        Format::Xlsx => xtab.to_xlsx(&flat)?,
+        #[cfg(feature = "parquet")]
+        Format::Parquet => xtab.to_parquet(&flat)?,
+        #[cfg(not(feature = "xlsx"))]
+        Format::Xlsx => return Err("xtabml was built without the `xlsx` feature".into()),
+        #[cfg(not(feature = "parquet"))]
+        Format::Parquet => return Err("xtabml was built without the `parquet` feature".into()),
+        _ => {
+            let subset = subset(xtab, &flat);
+            let render = RenderOptions {
+                statistics,
+                ..RenderOptions::default()
+            };
+            match format {
+                Format::Json => subset.to_json()?.into_bytes(),
+                Format::Html => subset.to_html(&HtmlOptions::default()).into_bytes(),
+                Format::Md => subset.to_markdown(&render).into_bytes(),
+                Format::Tex => subset.to_latex(&render).into_bytes(),
+                _ => subset
+                    .to_text(&TextOptions {
+                        render,
+                        ..TextOptions::default()
+                    })
+                    .into_bytes(),
+            }
+        }
+    };
+
+    if args.output == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytes)?;
+        stdout.flush()?;
+    } else {
+        std::fs::write(&args.output, bytes).map_err(|e| format!("{}: {}", args.output, e))?;
+    }
+    Ok(())
+}
+
+/// A copy of the document with only the selected tables and statistics
+fn subset(xtab: &XtabML, options: &FlatOptions) -> XtabML {
+    let mut subset = xtab.clone();
+    subset.tables = options
+        .table_indices(xtab)
+        .into_iter()
+        .map(|index| {
+            let mut table = xtab.tables[index].clone();
+            let keep = options.statistic_indices(&table);
+            table.statistics = keep.iter().map(|&i| table.statistics[i].clone()).collect();
+            for row in &mut table.data.rows {
+                row.data_row_series = keep
+                    .iter()
+                    .filter_map(|&i| row.data_row_series.get(i).cloned())
+                    .collect();
+            }
+            table
+        })
+        .collect();
+    subset
+}
@@ -0,0 +1,94 @@
+use crate::Result;
+use libxtabml::{Query, XtabML};
+
+pub fn info(file: &str, xtab: &XtabML) -> Result<()> {
+    let missing = || "-".to_string();
+    println!("File:            {}", file);
+    println!("Version:         {}", xtab.version);
+    println!(
+        "Date:            {}",
+        xtab.date.clone().unwrap_or_else(missing)
+    );
+    println!(
+        "Time:            {}",
+        xtab.time.clone().unwrap_or_else(missing)
+    );
+    println!(
+        "User:            {}",
+        xtab.user.clone().unwrap_or_else(missing)
+    );
+    println!(
+        "Origin:          {}",
+        xtab.origin.clone().unwrap_or_else(missing)
+    );
+
+    let languages: Vec<String> = xtab
+        .languages
+        .iter()
+        .map(|l| match l.base.as_deref() {
+            Some("yes") => format!("{} ({}, base)", l.lang, l.description),
+            _ => format!("{} ({})", l.lang, l.description),
+        })
+        .collect();
+    if languages.is_empty() {
+        println!("Languages:       -");
+    } else {
+        println!("Languages:       {}", languages.join(", "));
+    }
+
+    println!("Tables:          {}", xtab.tables.len());
+    println!("Control types:   {}", xtab.control_types.len());
+    println!("Statistic types: {}", xtab.statistic_types.len());
+    Ok(())
+}
+
+pub fn list(xtab: &XtabML, query: Option<&str>) -> Result<()> {
+    let query: Query = query.map(str::parse).transpose()?.unwrap_or_default();
+    let mut rows = vec![[
+        "#".to_string(),
+        "Name".to_string(),
+        "Title".to_string(),
+        "Shape".to_string(),
+        "Statistics".to_string(),
+    ]];
+    for slice in xtab.query(&query) {
+        let table = &xtab.tables[slice.table_index];
+        let statistics: Vec<&str> = slice
+            .statistics
+            .iter()
+            .map(|&i| table.statistics[i].r#type.as_str())
+            .collect();
+        rows.push([
+            (slice.table_index + 1).to_string(),
+            table.name.clone().unwrap_or_else(|| "-".to_string()),
+            table.title.clone(),
+            format!("{}x{}", slice.rows.len(), slice.columns.len()),
+            statistics.join(", "),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..5)
+        .map(|column| {
+            rows.iter()
+                .map(|r| r[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in &rows {
+        let line = format!(
+            "{:>w0$}  {:<w1$}  {:<w2$}  {:>w3$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
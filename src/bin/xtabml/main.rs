@@ -0,0 +1,145 @@
+//! `xtabml`, a command-line tool for inspecting XtabML files
+
+mod convert;
+mod inspect;
+mod search;
+mod select;
+mod show;
+mod validate;
+
+use clap::{Parser, Subcommand};
+use libxtabml::{XtabML, XtabMLParser};
+use std::io::Read;
+use std::process::ExitCode;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "xtabml",
+    version,
+    about = "Inspect and render XtabML cross-tabulation files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the document header, its languages and counts of tables and declarations
+    Info {
+        /// XtabML file, or "-" for standard input
+        file: String,
+    },
+
+    /// List the tables with their index, name, title, shape and statistics
+    List {
+        /// XtabML file, or "-" for standard input
+        file: String,
+
+        /// Only list the tables selected by a query, with the shape of their slice
+        #[arg(short, long)]
+        query: Option<String>,
+    },
+
+    /// Convert to CSV, JSON, Excel, HTML, Markdown, LaTeX, text or Parquet
+    Convert {
+        /// XtabML file, or "-" for standard input
+        file: String,
+
+        #[command(flatten)]
+        options: convert::ConvertArgs,
+    },
+
+    /// Render tables in the terminal
+    Show {
+        /// XtabML file, or "-" for standard input
+        file: String,
+
+        /// Table to show: a 1-based index, a table name, or a regular expression matched
+        /// against the titles
+        table: String,
+
+        #[command(flatten)]
+        options: show::ShowArgs,
+    },
+
+    /// Find tables by their titles, labels, group headings and controls
+    Search {
+        /// XtabML file, or "-" for standard input
+        file: String,
+
+        /// Text to look for; a case-insensitive substring unless --regex or --fuzzy is given
+        pattern: String,
+
+        #[command(flatten)]
+        options: search::SearchArgs,
+    },
+
+    /// Check the structure of a file; exits with status 1 when diagnostics reach the
+    /// --fail-on threshold
+    Validate {
+        /// XtabML file, or "-" for standard input
+        file: String,
+
+        #[command(flatten)]
+        options: validate::ValidateArgs,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("xtabml: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Info { file } => inspect::info(&file, &load(&file)?)?,
+        Command::List { file, query } => inspect::list(&load(&file)?, query.as_deref())?,
+        Command::Convert { file, options } => convert::convert(&load(&file)?, &options)?,
+        Command::Show {
+            file,
+            table,
+            options,
+        } => show::show(&load(&file)?, &table, &options)?,
+        Command::Search {
+            file,
+            pattern,
+            options,
+        } => search::search(&load(&file)?, &pattern, &options)?,
+        Command::Validate { file, options } => {
+            return validate::validate(&file, &read(&file)?, &options)
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Read a file, or standard input for "-"
+fn read(path: &str) -> Result<Vec<u8>> {
+    if path == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        Ok(std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?)
+    }
+}
+
+/// Parse a file, or standard input for "-"
+fn load(path: &str) -> Result<XtabML> {
+    let bytes = read(path)?;
+    Ok(XtabMLParser::parse_bytes(&bytes).map_err(|e| {
+        if path == "-" {
+            e.to_string()
+        } else {
+            format!("{}: {}", path, e)
+        }
+    })?)
+}
@@ -0,0 +1,116 @@
+use crate::Result;
+use clap::{Args, ValueEnum};
+use libxtabml::{NodeKind, SearchHit, SearchPattern, XtabML};
+use regex::RegexBuilder;
+
+/// Parts of a document to search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Scope {
+    Titles,
+    Controls,
+    /// Group headings, elements and summaries of row edges
+    Rows,
+    /// Group headings, elements and summaries of column edges (banners)
+    Columns,
+}
+
+impl Scope {
+    fn contains(&self, hit: &SearchHit) -> bool {
+        match self {
+            Scope::Titles => hit.kind == NodeKind::Title,
+            Scope::Controls => hit.kind == NodeKind::Control,
+            Scope::Rows => hit.axis.as_deref() == Some("r"),
+            Scope::Columns => hit.axis.as_deref() == Some("c"),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Treat the pattern as a regular expression
+    #[arg(short = 'E', long, conflicts_with = "fuzzy")]
+    regex: bool,
+
+    /// Match the characters of the pattern in order, and rank hits by closeness
+    #[arg(long)]
+    fuzzy: bool,
+
+    /// Make regular expressions case-insensitive; substring and fuzzy matching always are
+    #[arg(short, long)]
+    ignore_case: bool,
+
+    /// Only search these parts (repeatable); everything by default
+    #[arg(long = "in", value_enum, value_name = "SCOPE")]
+    scopes: Vec<Scope>,
+
+    /// Print at most this many hits
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+}
+
+pub fn search(xtab: &XtabML, pattern: &str, args: &SearchArgs) -> Result<()> {
+    let pattern = if args.regex {
+        SearchPattern::Regex(
+            RegexBuilder::new(pattern)
+                .case_insensitive(args.ignore_case)
+                .build()?,
+        )
+    } else if args.fuzzy {
+        SearchPattern::Fuzzy(pattern.to_string())
+    } else {
+        SearchPattern::Substring(pattern.to_string())
+    };
+
+    let mut hits: Vec<SearchHit> = xtab
+        .search(&pattern)
+        .into_iter()
+        .filter(|hit| args.scopes.is_empty() || args.scopes.iter().any(|s| s.contains(hit)))
+        .collect();
+    if args.fuzzy {
+        hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    }
+    hits.truncate(args.limit.unwrap_or(usize::MAX));
+
+    let rows: Vec<[String; 4]> = hits
+        .iter()
+        .map(|hit| {
+            [
+                hit.table_index
+                    .map_or_else(|| "-".to_string(), |i| (i + 1).to_string()),
+                describe(hit),
+                hit.text.clone(),
+                hit.path.clone(),
+            ]
+        })
+        .collect();
+    let width = |column: usize| {
+        rows.iter()
+            .map(|r| r[column].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (w0, w1, w2) = (width(0), width(1), width(2));
+    for row in &rows {
+        println!(
+            "{:>w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0], row[1], row[2], row[3]
+        );
+    }
+    Ok(())
+}
+
+/// Kind of a hit with its axis, e.g. "column group"
+fn describe(hit: &SearchHit) -> String {
+    let kind = match hit.kind {
+        NodeKind::Title => "title",
+        NodeKind::Control => "control",
+        NodeKind::Group => "group",
+        NodeKind::Element => "element",
+        NodeKind::Summary => "summary",
+    };
+    match hit.axis.as_deref() {
+        Some("r") => format!("row {}", kind),
+        Some("c") => format!("column {}", kind),
+        _ => kind.to_string(),
+    }
+}
@@ -0,0 +1,37 @@
+use libxtabml::XtabML;
+use regex::Regex;
+
+/// Resolve a table selector to table indices
+///
+/// The selector is tried as a 1-based index, then as a table name, then as a regular
+/// expression matched against the titles.
+pub fn tables(xtab: &XtabML, selector: &str) -> Result<Vec<usize>, String> {
+    if let Ok(number) = selector.parse::<usize>() {
+        return if (1..=xtab.tables.len()).contains(&number) {
+            Ok(vec![number - 1])
+        } else {
+            Err(format!(
+                "table {} is out of range, the document has {} tables",
+                number,
+                xtab.tables.len()
+            ))
+        };
+    }
+
+    let named: Vec<usize> = (0..xtab.tables.len())
+        .filter(|&i| xtab.tables[i].name.as_deref() == Some(selector))
+        .collect();
+    if !named.is_empty() {
+        return Ok(named);
+    }
+
+    let pattern = Regex::new(selector)
+        .map_err(|e| format!("invalid table selector {:?}: {}", selector, e))?;
+    let matching: Vec<usize> = (0..xtab.tables.len())
+        .filter(|&i| pattern.is_match(&xtab.tables[i].title))
+        .collect();
+    if matching.is_empty() {
+        return Err(format!("no table matches {:?}", selector));
+    }
+    Ok(matching)
+}
@@ -0,0 +1,88 @@
+use crate::{select, Result};
+use clap::Args;
+use libxtabml::{BoxStyle, NumberFormat, Overflow, RenderOptions, TextOptions, XtabML};
+use std::io::IsTerminal;
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Draw borders with ASCII characters instead of box-drawing characters
+    #[arg(long)]
+    ascii: bool,
+
+    /// Maximum line width; defaults to the terminal width
+    #[arg(long)]
+    width: Option<usize>,
+
+    /// Put columns that do not fit on numbered pages instead of continuing below
+    #[arg(long)]
+    pages: bool,
+
+    /// Statistic types to show, in this order (repeatable); all by default
+    #[arg(long = "stat", value_name = "TYPE")]
+    statistics: Vec<String>,
+
+    /// Round numbers to this many decimals
+    #[arg(long)]
+    decimals: Option<usize>,
+
+    /// Do not list the table controls under the title
+    #[arg(long)]
+    no_controls: bool,
+
+    /// Swap rows and columns
+    #[arg(long)]
+    transpose: bool,
+}
+
+impl ShowArgs {
+    pub fn text_options(&self) -> TextOptions {
+        TextOptions {
+            style: if self.ascii {
+                BoxStyle::Ascii
+            } else {
+                BoxStyle::Unicode
+            },
+            width: self.width.or_else(terminal_width),
+            overflow: if self.pages {
+                Overflow::Pages
+            } else {
+                Overflow::Wrap
+            },
+            include_controls: !self.no_controls,
+            render: RenderOptions {
+                statistics: (!self.statistics.is_empty()).then(|| self.statistics.clone()),
+                number_format: NumberFormat {
+                    decimals: self.decimals,
+                    missing: String::new(),
+                },
+            },
+            ..TextOptions::default()
+        }
+    }
+}
+
+/// Width of the terminal standard output is connected to, if any
+fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+}
+
+pub fn show(xtab: &XtabML, selector: &str, args: &ShowArgs) -> Result<()> {
+    let options = args.text_options();
+    let separator = if args.pages { "\x0c" } else { "\n" };
+    let rendered: Vec<String> = select::tables(xtab, selector)?
+        .into_iter()
+        .map(|index| {
+            let table = &xtab.tables[index];
+            if args.transpose {
+                xtab.table_to_text(&table.transpose(), &options)
+            } else {
+                xtab.table_to_text(table, &options)
+            }
+        })
+        .collect();
+    print!("{}", rendered.join(separator));
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::Result;
+use clap::{Args, ValueEnum};
+use libxtabml::{Diagnostic, Rule, Severity};
+use serde_json::json;
+use std::process::ExitCode;
+
+/// Output formats of `validate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One "file:line: severity[rule]: message" line per diagnostic
+    Human,
+    Json,
+    /// SARIF 2.1.0, for code scanning tools
+    Sarif,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Threshold {
+    Warning,
+    Error,
+}
+
+impl From<Threshold> for Severity {
+    fn from(threshold: Threshold) -> Severity {
+        match threshold {
+            Threshold::Warning => Severity::Warning,
+            Threshold::Error => Severity::Error,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "human")]
+    format: Format,
+
+    /// Exit with status 1 when a diagnostic is at least this severe
+    #[arg(long, value_enum, default_value = "error")]
+    fail_on: Threshold,
+}
+
+pub fn validate(file: &str, bytes: &[u8], args: &ValidateArgs) -> Result<ExitCode> {
+    let diagnostics = libxtabml::validate_bytes(bytes);
+    let name = if file == "-" { "<stdin>" } else { file };
+    match args.format {
+        Format::Human => print_human(name, &diagnostics),
+        Format::Json => {
+            let report = json!({
+                "file": name,
+                "errors": count(&diagnostics, Severity::Error),
+                "warnings": count(&diagnostics, Severity::Warning),
+                "diagnostics": diagnostics,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Format::Sarif => println!(
+            "{}",
+            serde_json::to_string_pretty(&sarif(name, &diagnostics))?
+        ),
+    }
+
+    let threshold = Severity::from(args.fail_on);
+    if diagnostics.iter().any(|d| d.severity >= threshold) {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn count(diagnostics: &[Diagnostic], severity: Severity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == severity)
+        .count()
+}
+
+fn print_human(name: &str, diagnostics: &[Diagnostic]) {
+    for d in diagnostics {
+        println!(
+            "{}:{}: {}[{}]: {}",
+            name,
+            d.line,
+            d.severity,
+            d.rule.id(),
+            d.message
+        );
+    }
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+    if diagnostics.is_empty() {
+        println!("{}: valid", name);
+    } else {
+        println!(
+            "{}: {}, {}",
+            name,
+            plural(count(diagnostics, Severity::Error), "error"),
+            plural(count(diagnostics, Severity::Warning), "warning")
+        );
+    }
+}
+
+fn sarif(name: &str, diagnostics: &[Diagnostic]) -> serde_json::Value {
+    let rules: Vec<_> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": rule.severity().to_string() },
+            })
+        })
+        .collect();
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "ruleId": d.rule.id(),
+                "ruleIndex": Rule::ALL.iter().position(|&rule| rule == d.rule),
+                "level": d.severity.to_string(),
+                "message": { "text": d.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": name },
+                        "region": { "startLine": d.line },
+                    }
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "xtabml",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
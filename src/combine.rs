@@ -0,0 +1,254 @@
+use crate::layout::PATH_SEPARATOR;
+use crate::types::*;
+use crate::{Result, XtabMLError};
+
+impl XtabML {
+    /// Concatenate tables with identical column edges into one table
+    ///
+    /// The row edge gets a group per source table, headed by its title and holding its
+    /// row groups. Column edges must be structurally equal: the same nesting of groups,
+    /// elements and summaries with the same texts, names and summary types. Statistics are
+    /// the union of the tables' statistics, rows of tables without one getting missing
+    /// cells. Only controls shared by every table are kept.
+    pub fn stack_tables(&self, indices: &[usize]) -> Result<Table> {
+        let tables = self.tables_at(indices)?;
+        stack(&tables, indices, "column")
+    }
+
+    /// Put tables with identical row edges next to each other in one table
+    ///
+    /// The column edge gets a group per source table, headed by its title; otherwise this
+    /// mirrors [`XtabML::stack_tables`], with the row edges required to be equal.
+    pub fn side_by_side(&self, indices: &[usize]) -> Result<Table> {
+        let tables: Vec<Table> = self
+            .tables_at(indices)?
+            .iter()
+            .map(|table| table.transpose())
+            .collect();
+        let references: Vec<&Table> = tables.iter().collect();
+        Ok(stack(&references, indices, "row")?.transpose())
+    }
+
+    fn tables_at(&self, indices: &[usize]) -> Result<Vec<&Table>> {
+        if indices.is_empty() {
+            return Err(XtabMLError::MissingElement(
+                "at least one table to combine".to_string(),
+            ));
+        }
+        indices
+            .iter()
+            .map(|&i| {
+                self.tables
+                    .get(i)
+                    .ok_or_else(|| XtabMLError::MissingElement(format!("table at index {}", i)))
+            })
+            .collect()
+    }
+}
+
+/// Stack tables sharing a column edge, `shared` naming that edge in errors
+fn stack(tables: &[&Table], indices: &[usize], shared: &str) -> Result<Table> {
+    let first = tables[0];
+    for (table, index) in tables.iter().zip(indices).skip(1) {
+        if let Some(difference) =
+            edge_difference(first.column_edge.as_ref(), table.column_edge.as_ref())
+        {
+            return Err(XtabMLError::EdgeMismatch(format!(
+                "the {} edge of table {} differs from that of table {}: {}",
+                shared, index, indices[0], difference
+            )));
+        }
+    }
+
+    let mut statistics: Vec<Statistic> = Vec::new();
+    for statistic in tables.iter().flat_map(|t| &t.statistics) {
+        if !statistics.iter().any(|s| s.r#type == statistic.r#type) {
+            statistics.push(statistic.clone());
+        }
+    }
+    let columns = first.column_count();
+
+    let mut groups = Vec::new();
+    let mut rows = Vec::new();
+    for table in tables {
+        let offset = rows.len();
+        let mut group = Group {
+            name: table.name.clone(),
+            text: Some(table.title.clone()),
+            alt_texts: table.title_alt_texts.clone(),
+            elements: Vec::new(),
+            summaries: Vec::new(),
+            groups: Vec::new(),
+        };
+        match &table.row_edge {
+            Some(edge) => {
+                group.groups = edge
+                    .groups
+                    .iter()
+                    .map(|g| offset_group(g, offset))
+                    .collect()
+            }
+            // Rows without an edge get blank labels, so that positions stay aligned
+            None => {
+                group.elements = (0..table.data.rows.len())
+                    .map(|i| Element {
+                        text: String::new(),
+                        index: None,
+                        name: None,
+                        alt_texts: Vec::new(),
+                        position: Some(offset + i),
+                    })
+                    .collect()
+            }
+        }
+        groups.push(group);
+
+        for row in &table.data.rows {
+            let data_row_series = statistics
+                .iter()
+                .map(|statistic| {
+                    let series = table
+                        .statistics
+                        .iter()
+                        .position(|s| s.r#type == statistic.r#type)
+                        .and_then(|i| row.data_row_series.get(i));
+                    DataRowSeries {
+                        statistic: Some(statistic.clone()),
+                        cells: match series {
+                            Some(series) => series.cells.clone(),
+                            None => vec![
+                                DataCell {
+                                    is_missing: true,
+                                    ..DataCell::default()
+                                };
+                                columns
+                            ],
+                        },
+                    }
+                })
+                .collect();
+            rows.push(DataRow { data_row_series });
+        }
+    }
+
+    let titles: Vec<&str> = tables.iter().map(|t| t.title.as_str()).collect();
+    Ok(Table {
+        name: None,
+        title: titles.join(PATH_SEPARATOR),
+        title_alt_texts: Vec::new(),
+        controls: first
+            .controls
+            .iter()
+            .filter(|c| {
+                tables[1..].iter().all(|t| {
+                    t.controls
+                        .iter()
+                        .any(|o| o.r#type == c.r#type && o.text == c.text)
+                })
+            })
+            .cloned()
+            .collect(),
+        row_edge: Some(Edge {
+            axis: "r".to_string(),
+            name: None,
+            groups,
+        }),
+        column_edge: first.column_edge.clone(),
+        statistics,
+        data: TableData { rows },
+    })
+}
+
+fn offset_group(group: &Group, offset: usize) -> Group {
+    Group {
+        elements: group
+            .elements
+            .iter()
+            .map(|e| Element {
+                position: e.position.map(|p| p + offset),
+                ..e.clone()
+            })
+            .collect(),
+        summaries: group
+            .summaries
+            .iter()
+            .map(|s| Summary {
+                position: s.position.map(|p| p + offset),
+                ..s.clone()
+            })
+            .collect(),
+        groups: group
+            .groups
+            .iter()
+            .map(|g| offset_group(g, offset))
+            .collect(),
+        ..group.clone()
+    }
+}
+
+/// The first structural difference between two edges, if any
+fn edge_difference(a: Option<&Edge>, b: Option<&Edge>) -> Option<String> {
+    match (a, b) {
+        (None, None) => None,
+        (Some(_), None) => Some("the other table has no such edge".to_string()),
+        (None, Some(_)) => Some("the first table has no such edge".to_string()),
+        (Some(a), Some(b)) => groups_difference(&a.groups, &b.groups, "edge").or_else(|| {
+            let order = |edge: &Edge| -> Vec<String> {
+                edge.leaves().iter().map(|l| l.text().to_string()).collect()
+            };
+            (order(a) != order(b)).then(|| "the leaves are in a different order".to_string())
+        }),
+    }
+}
+
+fn groups_difference(a: &[Group], b: &[Group], path: &str) -> Option<String> {
+    if a.len() != b.len() {
+        return Some(format!(
+            "{} has {} groups instead of {}",
+            path,
+            b.len(),
+            a.len()
+        ));
+    }
+    a.iter().zip(b).enumerate().find_map(|(i, (a, b))| {
+        let path = format!("{} / group {}", path, i + 1);
+        if a.text != b.text || a.name != b.name {
+            return Some(format!(
+                "{} is {:?} instead of {:?}",
+                path,
+                b.text.as_deref().unwrap_or(""),
+                a.text.as_deref().unwrap_or("")
+            ));
+        }
+        let elements = |g: &Group| -> Vec<(String, Option<String>)> {
+            g.elements
+                .iter()
+                .map(|e| (e.text.clone(), e.name.clone()))
+                .collect()
+        };
+        let summaries = |g: &Group| -> Vec<(String, Option<String>, Option<String>)> {
+            g.summaries
+                .iter()
+                .map(|s| (s.text.clone(), s.name.clone(), s.r#type.clone()))
+                .collect()
+        };
+        let labels = |items: Vec<String>| items.join(", ");
+        if elements(a) != elements(b) {
+            return Some(format!(
+                "{} has elements [{}] instead of [{}]",
+                path,
+                labels(b.elements.iter().map(|e| e.text.clone()).collect()),
+                labels(a.elements.iter().map(|e| e.text.clone()).collect())
+            ));
+        }
+        if summaries(a) != summaries(b) {
+            return Some(format!(
+                "{} has summaries [{}] instead of [{}]",
+                path,
+                labels(b.summaries.iter().map(|s| s.text.clone()).collect()),
+                labels(a.summaries.iter().map(|s| s.text.clone()).collect())
+            ));
+        }
+        groups_difference(&a.groups, &b.groups, &path)
+    })
+}
@@ -0,0 +1,97 @@
+use crate::flat::{FlatOptions, Layout};
+use crate::types::*;
+
+/// Columns of the long layout, matching the fields of `LongRecord`
+pub(crate) const LONG_COLUMNS: [&str; 10] = [
+    "table_index",
+    "table_name",
+    "table_title",
+    "row_position",
+    "row_label",
+    "column_position",
+    "column_label",
+    "statistic",
+    "value",
+    "number",
+];
+
+impl XtabML {
+    /// Export the selected tables as CSV
+    ///
+    /// The long layout is a single CSV with one record per cell. In the wide layout each
+    /// table has a header of "Row", "Statistic" and its column labels; when several tables
+    /// are exported, each is preceded by its title and separated by an empty line.
+    pub fn to_csv(&self, options: &FlatOptions) -> String {
+        let mut out = String::new();
+        match options.layout {
+            Layout::Long => {
+                line(&mut out, LONG_COLUMNS.iter().map(|c| c.to_string()));
+                for record in self.long_records(options) {
+                    line(
+                        &mut out,
+                        [
+                            record.table_index.to_string(),
+                            record.table_name.unwrap_or_default(),
+                            record.table_title,
+                            record.row_position.to_string(),
+                            record.row_label,
+                            record.column_position.to_string(),
+                            record.column_label,
+                            record.statistic,
+                            record.value.unwrap_or_default(),
+                            record.number.map(|n| n.to_string()).unwrap_or_default(),
+                        ],
+                    );
+                }
+            }
+            Layout::Wide => {
+                let tables = self.wide_tables(options);
+                let titled = tables.len() > 1;
+                for (n, table) in tables.into_iter().enumerate() {
+                    if n > 0 {
+                        out.push_str("\r\n");
+                    }
+                    if titled {
+                        line(&mut out, [table.title]);
+                    }
+                    line(
+                        &mut out,
+                        ["Row".to_string(), "Statistic".to_string()]
+                            .into_iter()
+                            .chain(table.columns),
+                    );
+                    for row in table.rows {
+                        line(
+                            &mut out,
+                            [row.row_label, row.statistic].into_iter().chain(
+                                row.cells.into_iter().map(|c| {
+                                    c.value
+                                        .filter(|_| !c.is_missing && !c.is_hidden)
+                                        .unwrap_or_default()
+                                }),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Append a record, quoting fields as RFC 4180 requires
+fn line(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (n, field) in fields.into_iter().enumerate() {
+        if n > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
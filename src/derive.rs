@@ -0,0 +1,502 @@
+use crate::distribution::{normal_quantile, student_t_critical};
+use crate::net::{subtree_rows, Measure};
+use crate::significance::{EFFECTIVE_BASES, MEANS_BASES};
+use crate::statistic::{Basis, StandardStatistic, StatisticRegistry};
+use crate::transform::address;
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use std::collections::HashMap;
+
+/// A percentage statistic [`Table::derive_percentages`] can compute from counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Percentage {
+    /// Count over the column base
+    Column,
+    /// Count over the row base
+    Row,
+    /// Count over the base of the whole table
+    Total,
+    /// Count over the counts of all answers in the column's part of the row edge
+    ColumnResponses,
+    /// Count over the counts of all answers in the row's part of the column edge
+    RowResponses,
+    /// Count over the counts of all answers in both
+    TotalResponses,
+}
+
+impl Percentage {
+    pub const ALL: [Percentage; 6] = [
+        Percentage::Column,
+        Percentage::Row,
+        Percentage::Total,
+        Percentage::ColumnResponses,
+        Percentage::RowResponses,
+        Percentage::TotalResponses,
+    ];
+
+    /// Type of the derived statistic, as declared by common tabulation systems, e.g.
+    /// "ColumnPercent" or "PercentColumnResponses"
+    pub fn statistic_type(&self) -> &'static str {
+        match self {
+            Percentage::Column => "ColumnPercent",
+            Percentage::Row => "RowPercent",
+            Percentage::Total => "TotalPercent",
+            Percentage::ColumnResponses => "PercentColumnResponses",
+            Percentage::RowResponses => "PercentRowResponses",
+            Percentage::TotalResponses => "PercentTotalResponses",
+        }
+    }
+
+    /// What the derived statistic means
+    pub fn standard_statistic(&self) -> StandardStatistic {
+        match self {
+            Percentage::Column => StandardStatistic::ColumnPercent,
+            Percentage::Row => StandardStatistic::RowPercent,
+            Percentage::Total => StandardStatistic::TotalPercent,
+            Percentage::ColumnResponses => StandardStatistic::ColumnResponsesPercent,
+            Percentage::RowResponses => StandardStatistic::RowResponsesPercent,
+            Percentage::TotalResponses => StandardStatistic::TotalResponsesPercent,
+        }
+    }
+}
+
+/// A cell a derived value was computed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub statistic: String,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// How one derived cell was computed: `count` over the sum of `base`, times 100
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation {
+    pub statistic: String,
+    pub row: usize,
+    pub column: usize,
+    /// Unrounded value
+    pub value: f64,
+    pub count: Input,
+    /// A base statistic's cell, a base summary's count, or the counts of all answers
+    pub base: Vec<Input>,
+}
+
+/// How a confidence interval for a percentage is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalMethod {
+    /// Wilson score interval, which suits small bases and proportions near 0 or 100%
+    #[default]
+    Wilson,
+    /// Normal approximation, cut off at 0 and 100%
+    Normal,
+}
+
+/// How [`Table::derive_confidence_intervals`] computes intervals
+#[derive(Debug, Clone)]
+pub struct IntervalOptions {
+    /// Confidence level, e.g. 0.95
+    pub level: f64,
+
+    pub method: IntervalMethod,
+
+    /// Use an effective base statistic where the table has one: EffectiveColumnN or
+    /// EffectiveBaseN for column percentages, EffectiveRowN for row percentages, and for
+    /// means also EffectiveN
+    pub effective_base: bool,
+
+    /// What the table's statistic types mean
+    pub statistics: StatisticRegistry,
+}
+
+impl Default for IntervalOptions {
+    fn default() -> IntervalOptions {
+        IntervalOptions {
+            level: 0.95,
+            method: IntervalMethod::Wilson,
+            effective_base: true,
+            statistics: StatisticRegistry::standard(),
+        }
+    }
+}
+
+/// Where the percentages along one edge find their bases
+pub(crate) struct EdgeBases {
+    /// Leaf holding the base of each leaf's row or column
+    base: Vec<Option<usize>>,
+    /// Elements of the outermost group of each leaf, leaving out NETs
+    answers: Vec<Vec<usize>>,
+}
+
+impl EdgeBases {
+    pub(crate) fn new(edge: Option<&Edge>, count: usize) -> EdgeBases {
+        let Some(edge) = edge else {
+            return EdgeBases {
+                base: vec![None; count],
+                answers: vec![(0..count).collect(); count],
+            };
+        };
+        let leaves = edge.leaves();
+        let index: HashMap<*const (), usize> = leaves
+            .iter()
+            .enumerate()
+            .map(|(i, leaf)| (address(leaf.node), i))
+            .collect();
+        let base_in = |group: &Group| {
+            group
+                .summaries
+                .iter()
+                .filter_map(|s| Some((base_rank(s)?, index.get(&address(EdgeNode::Summary(s)))?)))
+                .min()
+                .map(|(_, &i)| i)
+        };
+        let anywhere = edge.groups.iter().find_map(&base_in);
+        EdgeBases {
+            base: leaves
+                .iter()
+                .map(|leaf| {
+                    leaf.groups
+                        .iter()
+                        .rev()
+                        .find_map(|g| base_in(g))
+                        .or(anywhere)
+                })
+                .collect(),
+            answers: leaves
+                .iter()
+                .map(|leaf| {
+                    let mut rows = Vec::new();
+                    subtree_rows(leaf.groups[0], &index, &mut rows);
+                    rows.into_iter()
+                        .filter(|(_, node)| !node.is_summary() && !node.is_net())
+                        .map(|(r, _)| r)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn base(&self, leaf: usize) -> Option<usize> {
+        *self.base.get(leaf)?
+    }
+
+    fn answers(&self, leaf: usize) -> &[usize] {
+        self.answers.get(leaf).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Preference of a summary as a base: type "base" or "xs:base", type "total", then untyped
+/// summaries labelled as such; `None` for other summaries
+fn base_rank(summary: &Summary) -> Option<u8> {
+    let text = summary.text.trim().to_lowercase();
+    match summary.r#type.as_deref().map(str::to_lowercase).as_deref() {
+        Some("base" | "xs:base") => Some(0),
+        Some("total") => Some(1),
+        None if text.starts_with("base") => Some(2),
+        None if text.starts_with("total") => Some(3),
+        _ => None,
+    }
+}
+
+impl Table {
+    /// A copy with percentage statistics computed from the table's counts, and how each
+    /// value was computed
+    ///
+    /// ```no_run
+    /// # use libxtabml::{Percentage, StatisticRegistry, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let registry = StatisticRegistry::standard();
+    /// let table = &xtab.tables[0];
+    /// let (table, derivations) = table.derive_percentages(&Percentage::ALL, &registry)?;
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Statistic types mean what `statistics` says they do. Counts come from the first
+    /// count statistic (Count, Frequency or n). Bases come from a base statistic
+    /// (ColumnN, RowN, TotalN and the like) where the table has one, and otherwise from
+    /// the counts of a base summary: one of type "base", "xs:base" or "total", or an
+    /// untyped one labelled "Base…" or "Total…", in the innermost enclosing group that
+    /// has one. Responses percentages take all elements of the outermost enclosing group
+    /// as the base, apart from NETs. New statistics are declared with
+    /// `datatype="percentage"` and hold values rounded to two decimals; cells without a
+    /// count or a non-zero base are missing. Percentages the table already has are left
+    /// as they are, whatever `statistics` names them, e.g. "xs:cp" for ColumnPercent.
+    pub fn derive_percentages(
+        &self,
+        percentages: &[Percentage],
+        statistics: &StatisticRegistry,
+    ) -> Result<(Table, Vec<Derivation>)> {
+        let measures = Measure::all(self, statistics);
+        let count = measures
+            .iter()
+            .position(|&m| m == Measure::Count)
+            .ok_or_else(|| {
+                XtabMLError::Derivation(
+                    "the table has no count statistic (Count, Frequency or n)".to_string(),
+                )
+            })?;
+        let base_statistic = |over: Basis| measures.iter().position(|&m| m == Measure::Base(over));
+        let rows = EdgeBases::new(self.row_edge.as_ref(), self.data.rows.len());
+        let columns = EdgeBases::new(self.column_edge.as_ref(), self.column_count());
+        let input = |statistic: usize, row: usize, column: usize| Input {
+            statistic: self.statistics[statistic].r#type.clone(),
+            row,
+            column,
+        };
+        // A base statistic's own cell, if it has a value there
+        let stated = |over: Basis, row: usize, column: usize| {
+            base_statistic(over)
+                .filter(|&b| self.number(row, b, column).is_some())
+                .map(|b| vec![input(b, row, column)])
+        };
+
+        let mut table = self.clone();
+        let mut derivations = Vec::new();
+        for &percentage in percentages {
+            let name = percentage.statistic_type();
+            let present = table.find_statistic(statistics, &[percentage.standard_statistic()]);
+            if present.is_some() || table.statistics.iter().any(|s| s.r#type == name) {
+                continue;
+            }
+            let statistic = Statistic {
+                r#type: name.to_string(),
+                datatype: Some("percentage".to_string()),
+            };
+            for (r, row) in table.data.rows.iter_mut().enumerate() {
+                let cells = (0..self.column_count())
+                    .map(|c| {
+                        let base = match percentage {
+                            Percentage::Column => stated(Basis::Column, r, c)
+                                .or_else(|| Some(vec![input(count, rows.base(r)?, c)])),
+                            Percentage::Row => stated(Basis::Row, r, c)
+                                .or_else(|| Some(vec![input(count, r, columns.base(c)?)])),
+                            Percentage::Total => stated(Basis::Total, r, c).or_else(|| {
+                                Some(vec![input(count, rows.base(r)?, columns.base(c)?)])
+                            }),
+                            Percentage::ColumnResponses => Some(
+                                rows.answers(r)
+                                    .iter()
+                                    .map(|&e| input(count, e, c))
+                                    .collect(),
+                            ),
+                            Percentage::RowResponses => Some(
+                                columns
+                                    .answers(c)
+                                    .iter()
+                                    .map(|&e| input(count, r, e))
+                                    .collect(),
+                            ),
+                            Percentage::TotalResponses => Some(
+                                rows.answers(r)
+                                    .iter()
+                                    .flat_map(|&e| {
+                                        columns.answers(c).iter().map(move |&f| input(count, e, f))
+                                    })
+                                    .collect(),
+                            ),
+                        };
+                        let derivation = base.and_then(|base| {
+                            let n = self.number(r, count, c)?;
+                            let total = base
+                                .iter()
+                                .map(|i| {
+                                    let statistic = self
+                                        .statistics
+                                        .iter()
+                                        .position(|s| s.r#type == i.statistic)?;
+                                    self.number(i.row, statistic, i.column)
+                                })
+                                .sum::<Option<f64>>()
+                                .filter(|&b| b != 0.0)?;
+                            Some(Derivation {
+                                statistic: name.to_string(),
+                                row: r,
+                                column: c,
+                                value: n / total * 100.0,
+                                count: input(count, r, c),
+                                base,
+                            })
+                        });
+                        match derivation {
+                            Some(derivation) => {
+                                let cell = DataCell {
+                                    value: Some(format!("{:.2}", derivation.value)),
+                                    ..DataCell::default()
+                                };
+                                derivations.push(derivation);
+                                cell
+                            }
+                            None => DataCell {
+                                is_missing: true,
+                                ..DataCell::default()
+                            },
+                        }
+                    })
+                    .collect();
+                row.data_row_series.push(DataRowSeries {
+                    statistic: Some(statistic.clone()),
+                    cells,
+                });
+            }
+            table.statistics.push(statistic);
+        }
+        Ok((table, derivations))
+    }
+
+    /// A copy with confidence intervals for percentages and means
+    ///
+    /// ```no_run
+    /// # use libxtabml::{IntervalOptions, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let table = xtab.tables[0].derive_confidence_intervals(IntervalOptions::default())?;
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Percentages get intervals by the Wilson or normal-approximation method, taking n
+    /// from the base statistic of their own margin: column percentages from a column base
+    /// (or EffectiveColumnN) get LowerCIPercent and UpperCIPercent, row percentages from
+    /// a row base (or EffectiveRowN) get LowerCIRowPercent and UpperCIRowPercent, and
+    /// total percentages from a total base get LowerCITotalPercent and
+    /// UpperCITotalPercent. Averages get LowerCI and UpperCI from their StandardError, or
+    /// else from StandardDeviation and n, using Student's t with n - 1 degrees of freedom
+    /// where n is known. Statistics the table already has, under any name the registry
+    /// gives them, are left as they are; cells lacking an input are missing.
+    pub fn derive_confidence_intervals(&self, options: IntervalOptions) -> Result<Table> {
+        let find = |wanted: &[StandardStatistic]| self.find_statistic(&options.statistics, wanted);
+        let measures = Measure::all(self, &options.statistics);
+        let find_measure = |measure: Measure| measures.iter().position(|&m| m == measure);
+        let effective =
+            |wanted: &[StandardStatistic]| find(wanted).filter(|_| options.effective_base);
+        let effective_column = effective(&[StandardStatistic::EffectiveColumnBase]);
+        let effective_row = effective(&[StandardStatistic::EffectiveRowBase]);
+        let percentages: Vec<(Basis, usize, usize)> = [
+            (Basis::Column, effective_column),
+            (Basis::Row, effective_row),
+            (Basis::Total, None),
+        ]
+        .into_iter()
+        .filter_map(|(basis, effective)| {
+            let percent = find_measure(Measure::Percent(basis))?;
+            let base = effective.or_else(|| find_measure(Measure::Base(basis)))?;
+            Some((basis, percent, base))
+        })
+        .collect();
+        let size = effective(&EFFECTIVE_BASES).or_else(|| find(&MEANS_BASES));
+        let error = find(&[StandardStatistic::StandardError]);
+        let deviation = find(&[StandardStatistic::StandardDeviation]);
+        let means = find(&[StandardStatistic::Mean])
+            .filter(|_| error.is_some() || (deviation.is_some() && size.is_some()));
+        if percentages.is_empty() && means.is_none() {
+            return Err(XtabMLError::Derivation(
+                "the table has neither percentages with bases nor means with standard errors"
+                    .to_string(),
+            ));
+        }
+
+        let z = normal_quantile(1.0 - (1.0 - options.level) / 2.0);
+        let mut table = self.clone();
+        let mut attach = |bounds: [(&str, StandardStatistic); 2],
+                          datatype: &str,
+                          bound: &dyn Fn(usize, usize) -> Option<(f64, f64)>,
+                          decimals: &dyn Fn(usize, usize) -> usize| {
+            for (i, (name, meaning)) in bounds.into_iter().enumerate() {
+                let present = self.find_statistic(&options.statistics, &[meaning]);
+                if present.is_some() || self.statistics.iter().any(|s| s.r#type == name) {
+                    continue;
+                }
+                let cells = (0..self.data.rows.len())
+                    .map(|r| {
+                        (0..self.column_count())
+                            .map(|c| match bound(r, c) {
+                                Some((lower, upper)) => DataCell {
+                                    value: Some(format!(
+                                        "{:.*}",
+                                        decimals(r, c),
+                                        if i == 0 { lower } else { upper }
+                                    )),
+                                    ..DataCell::default()
+                                },
+                                None => DataCell {
+                                    is_missing: true,
+                                    ..DataCell::default()
+                                },
+                            })
+                            .collect()
+                    })
+                    .collect();
+                table.set_statistic(name, datatype, cells);
+            }
+        };
+
+        for (basis, percent, base) in percentages {
+            let bound = |r: usize, c: usize| {
+                let p = self.number(r, percent, c)? / 100.0;
+                let n = self.number(r, base, c)?;
+                let (lower, upper) = proportion_interval(p, n, z, options.method)?;
+                Some((lower * 100.0, upper * 100.0))
+            };
+            let [lower, upper] = match basis {
+                Basis::Row => ["LowerCIRowPercent", "UpperCIRowPercent"],
+                Basis::Total => ["LowerCITotalPercent", "UpperCITotalPercent"],
+                _ => ["LowerCIPercent", "UpperCIPercent"],
+            };
+            let bounds = [
+                (lower, StandardStatistic::LowerCIPercent(basis)),
+                (upper, StandardStatistic::UpperCIPercent(basis)),
+            ];
+            attach(bounds, "percentage", &bound, &|_, _| 2);
+        }
+        if let Some(mean) = means {
+            let bound = |r: usize, c: usize| {
+                let m = self.number(r, mean, c)?;
+                let n = size.and_then(|s| self.number(r, s, c));
+                let se = match (error, deviation) {
+                    (Some(e), _) => self.number(r, e, c)?,
+                    (None, Some(d)) => self.number(r, d, c)? / n?.sqrt(),
+                    (None, None) => return None,
+                };
+                let critical = match n {
+                    Some(n) if n > 1.0 => student_t_critical(options.level, n - 1.0),
+                    _ => z,
+                };
+                Some((m - critical * se, m + critical * se))
+            };
+            // As precise as the means, and at least two decimals
+            let decimals = |r: usize, c: usize| {
+                let value = self.data.rows[r]
+                    .data_row_series
+                    .get(mean)
+                    .and_then(|series| series.cell(c)?.value.as_deref())
+                    .unwrap_or("");
+                value
+                    .split_once('.')
+                    .map_or(0, |(_, f)| f.trim().len())
+                    .max(2)
+            };
+            let bounds = [
+                ("LowerCI", StandardStatistic::LowerCI),
+                ("UpperCI", StandardStatistic::UpperCI),
+            ];
+            attach(bounds, "decimal", &bound, &decimals);
+        }
+        Ok(table)
+    }
+}
+
+/// Confidence interval of a proportion from a sample of size `n`
+fn proportion_interval(p: f64, n: f64, z: f64, method: IntervalMethod) -> Option<(f64, f64)> {
+    if n <= 0.0 || !(0.0..=1.0).contains(&p) {
+        return None;
+    }
+    Some(match method {
+        IntervalMethod::Wilson => {
+            let denominator = 1.0 + z * z / n;
+            let centre = (p + z * z / (2.0 * n)) / denominator;
+            let half = z / denominator * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt();
+            (centre - half, centre + half)
+        }
+        IntervalMethod::Normal => {
+            let half = z * (p * (1.0 - p) / n).sqrt();
+            ((p - half).max(0.0), (p + half).min(1.0))
+        }
+    })
+}
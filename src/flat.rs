@@ -0,0 +1,168 @@
+use crate::layout::PATH_SEPARATOR;
+use crate::types::*;
+use serde::Serialize;
+
+/// Shape of the flat, spreadsheet-like exports (CSV, Excel, Parquet)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// One record per cell with its table, row, column and statistic
+    #[default]
+    Long,
+
+    /// One line per row and statistic with a column per data column, as tables are printed
+    Wide,
+}
+
+/// What to include in a flat export
+#[derive(Debug, Clone, Default)]
+pub struct FlatOptions {
+    pub layout: Layout,
+
+    /// Indices of the tables to export, in this order; all tables when `None`
+    pub tables: Option<Vec<usize>>,
+
+    /// Statistic types to export, in this order; all statistics of each table when `None`
+    pub statistics: Option<Vec<String>>,
+}
+
+/// A cell with its coordinates, one record of the long layout
+///
+/// Labels are flattened with their group headings, e.g. "Gender / Female".
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LongRecord {
+    pub table_index: usize,
+    pub table_name: Option<String>,
+    pub table_title: String,
+    pub row_position: usize,
+    pub row_label: String,
+    pub column_position: usize,
+    pub column_label: String,
+    pub statistic: String,
+
+    /// Value as exported, `None` when missing or hidden
+    pub value: Option<String>,
+    pub number: Option<f64>,
+}
+
+/// A table in the wide layout
+#[derive(Debug, Clone)]
+pub struct WideTable {
+    pub table_index: usize,
+    pub title: String,
+
+    /// Flattened column labels
+    pub columns: Vec<String>,
+    pub rows: Vec<WideRow>,
+}
+
+/// A line of a wide table: the cells of one statistic in one row
+#[derive(Debug, Clone)]
+pub struct WideRow {
+    pub row_label: String,
+    pub statistic: String,
+
+    /// One cell per column, compressed runs expanded
+    pub cells: Vec<DataCell>,
+}
+
+impl FlatOptions {
+    /// Indices of the tables to export
+    pub fn table_indices(&self, xtab: &XtabML) -> Vec<usize> {
+        match &self.tables {
+            Some(indices) => indices
+                .iter()
+                .copied()
+                .filter(|&i| i < xtab.tables.len())
+                .collect(),
+            None => (0..xtab.tables.len()).collect(),
+        }
+    }
+
+    /// Indices into `table.statistics` of the statistics to export
+    pub fn statistic_indices(&self, table: &Table) -> Vec<usize> {
+        match &self.statistics {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| table.statistics.iter().position(|s| &s.r#type == name))
+                .collect(),
+            None => (0..table.statistics.len()).collect(),
+        }
+    }
+}
+
+impl XtabML {
+    /// Every selected cell as a record of the long layout
+    pub fn long_records(&self, options: &FlatOptions) -> Vec<LongRecord> {
+        let mut records = Vec::new();
+        for index in options.table_indices(self) {
+            let table = &self.tables[index];
+            let rows = table.row_header().flat_labels(PATH_SEPARATOR);
+            let columns = table.column_header().flat_labels(PATH_SEPARATOR);
+            for statistic in options.statistic_indices(table) {
+                for (row_position, row) in table.data.rows.iter().enumerate() {
+                    let series = row.data_row_series.get(statistic);
+                    for (column_position, column_label) in columns.iter().enumerate() {
+                        let cell = series
+                            .and_then(|s| s.cell(column_position))
+                            .filter(|c| !c.is_missing && !c.is_hidden);
+                        records.push(LongRecord {
+                            table_index: index,
+                            table_name: table.name.clone(),
+                            table_title: table.title.clone(),
+                            row_position,
+                            row_label: rows[row_position].clone(),
+                            column_position,
+                            column_label: column_label.clone(),
+                            statistic: table.statistics[statistic].r#type.clone(),
+                            value: cell.and_then(|c| c.value.clone()),
+                            number: cell.and_then(DataCell::as_f64),
+                        });
+                    }
+                }
+            }
+        }
+        records
+    }
+
+    /// The selected tables in the wide layout
+    pub fn wide_tables(&self, options: &FlatOptions) -> Vec<WideTable> {
+        options
+            .table_indices(self)
+            .into_iter()
+            .map(|index| {
+                let table = &self.tables[index];
+                let labels = table.row_header().flat_labels(PATH_SEPARATOR);
+                let columns = table.column_header().flat_labels(PATH_SEPARATOR);
+                let statistics = options.statistic_indices(table);
+
+                let mut rows = Vec::new();
+                for (position, row) in table.data.rows.iter().enumerate() {
+                    for &statistic in &statistics {
+                        let series = row.data_row_series.get(statistic);
+                        rows.push(WideRow {
+                            row_label: labels[position].clone(),
+                            statistic: table.statistics[statistic].r#type.clone(),
+                            cells: (0..columns.len())
+                                .map(|column| {
+                                    series.and_then(|s| s.cell(column)).cloned().unwrap_or(
+                                        DataCell {
+                                            is_missing: true,
+                                            ..DataCell::default()
+                                        },
+                                    )
+                                })
+                                .collect(),
+                        });
+                    }
+                }
+
+                WideTable {
+                    table_index: index,
+                    title: table.title.clone(),
+                    columns,
+                    rows,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt::Write;
+
+use crate::layout::{HeaderCell, HeaderKind};
+use crate::types::*;
+
+/// Stylesheet embedded by `HtmlOptions::inline_css`
+///
+/// Every element carries a class, so callers who switch this off can style the output
+/// themselves.
+pub const DEFAULT_CSS: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+nav.xtab-index ol { line-height: 1.6; }
+section.xtab-table { margin-bottom: 3em; }
+table.xtab { border-collapse: collapse; font-size: 0.9em; }
+table.xtab caption { font-weight: bold; text-align: left; padding: 0.5em 0; }
+table.xtab th, table.xtab td { border: 1px solid #ccc; padding: 0.25em 0.5em; }
+table.xtab thead th { background: #f2f2f2; text-align: center; }
+table.xtab th.group { background: #e6e6e6; }
+table.xtab tbody th { text-align: left; font-weight: normal; }
+table.xtab th.statistic { color: #666; font-style: italic; }
+table.xtab td { text-align: right; }
+table.xtab .summary { font-weight: bold; }
+table.xtab .net { background: #fafae6; }
+table.xtab td.missing { background: #f7f7f7; }
+table.xtab td.hidden { color: #aaa; font-style: italic; }
+dl.xtab-controls { font-size: 0.85em; color: #444; }
+dl.xtab-controls dt { font-weight: bold; float: left; clear: left; margin-right: 0.5em; }
+";
+
+/// Options for the HTML renderer
+#[derive(Debug, Clone)]
+pub struct HtmlOptions {
+    /// Embed `DEFAULT_CSS` in documents
+    pub inline_css: bool,
+
+    /// Render the controls of each table below its caption
+    pub include_controls: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            inline_css: true,
+            include_controls: true,
+        }
+    }
+}
+
+impl XtabML {
+    /// Render the document as a self-contained HTML page with an index of table titles
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let title = self
+            .controls
+            .iter()
+            .find(|c| c.r#type == "project")
+            .map_or("XtabML tables", |c| c.text.as_str());
+
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(out, "<title>{}</title>", escape(title));
+        if options.inline_css {
+            let _ = writeln!(out, "<style>\n{}</style>", DEFAULT_CSS);
+        }
+        out.push_str("</head>\n<body>\n");
+        let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+
+        out.push_str("<nav class=\"xtab-index\">\n<ol>\n");
+        for (idx, table) in self.tables.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "<li><a href=\"#{}\">{}</a></li>",
+                anchor(idx),
+                escape(&table.title)
+            );
+        }
+        out.push_str("</ol>\n</nav>\n");
+
+        for (idx, table) in self.tables.iter().enumerate() {
+            let _ = writeln!(out, "<section class=\"xtab-table\" id=\"{}\">", anchor(idx));
+            out.push_str(&table.to_html(options));
+            out.push_str("</section>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+impl Table {
+    /// Render the table as an HTML `<table>` fragment
+    ///
+    /// Nested column groups become stacked header rows using `colspan`, nested row groups
+    /// become header columns using `rowspan`, and each statistic gets its own line within
+    /// a data row.
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let rows = self.row_header();
+        let columns = self.column_header();
+        let statistics = self.statistics.len().max(1);
+        let show_statistic = self.statistics.len() > 1;
+
+        let mut out = String::new();
+        if options.include_controls && !self.controls.is_empty() {
+            out.push_str("<dl class=\"xtab-controls\">\n");
+            for control in &self.controls {
+                let _ = writeln!(
+                    out,
+                    "<dt>{}</dt><dd>{}</dd>",
+                    escape(&control.r#type),
+                    escape(&control.text)
+                );
+            }
+            out.push_str("</dl>\n");
+        }
+
+        out.push_str("<table class=\"xtab\">\n");
+        let _ = writeln!(out, "<caption>{}</caption>", escape(&self.title));
+
+        out.push_str("<thead>\n");
+        for level in 0..columns.levels {
+            out.push_str("<tr>");
+            if level == 0 {
+                let _ = write!(
+                    out,
+                    "<th class=\"corner\" colspan=\"{}\" rowspan=\"{}\"></th>",
+                    rows.levels + usize::from(show_statistic),
+                    columns.levels
+                );
+            }
+            for cell in columns.level(level) {
+                let _ = write!(
+                    out,
+                    "<th scope=\"col\" class=\"{}\"{}{}>{}</th>",
+                    header_class(cell),
+                    span_attr("colspan", cell.span),
+                    span_attr("rowspan", cell.depth),
+                    escape(&cell.text)
+                );
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</thead>\n");
+
+        out.push_str("<tbody>\n");
+        for (position, row) in self.data.rows.iter().enumerate() {
+            let row_leaf = &rows.leaves[position];
+            for index in 0..statistics {
+                let series = row.data_row_series.get(index);
+                let _ = write!(out, "<tr{}>", class_attr(&leaf_classes(row_leaf)));
+
+                if index == 0 {
+                    for cell in rows.starting_at(position) {
+                        let _ = write!(
+                            out,
+                            "<th scope=\"row\" class=\"{}\"{}{}>{}</th>",
+                            header_class(cell),
+                            span_attr("rowspan", cell.span * statistics),
+                            span_attr("colspan", cell.depth),
+                            escape(&cell.text)
+                        );
+                    }
+                }
+                if show_statistic {
+                    let label = self.statistics.get(index).map_or("", |s| s.r#type.as_str());
+                    let _ = write!(out, "<th class=\"statistic\">{}</th>", escape(label));
+                }
+
+                for column in 0..columns.leaves.len() {
+                    let cell = series.and_then(|s| s.cell(column));
+                    let mut classes = leaf_classes(&columns.leaves[column]);
+                    let text = match cell {
+                        Some(c) if c.is_missing => {
+                            classes.push("missing".to_string());
+                            ""
+                        }
+                        Some(c) => {
+                            if c.is_hidden {
+                                classes.push("hidden".to_string());
+                            }
+                            c.value.as_deref().unwrap_or("")
+                        }
+                        None => {
+                            classes.push("missing".to_string());
+                            ""
+                        }
+                    };
+                    let _ = write!(out, "<td{}>{}</td>", class_attr(&classes), escape(text));
+                }
+                out.push_str("</tr>\n");
+            }
+        }
+        out.push_str("</tbody>\n</table>\n");
+        out
+    }
+}
+
+fn anchor(idx: usize) -> String {
+    format!("table-{}", idx + 1)
+}
+
+fn span_attr(name: &str, span: usize) -> String {
+    if span > 1 {
+        format!(" {}=\"{}\"", name, span)
+    } else {
+        String::new()
+    }
+}
+
+fn class_attr(classes: &[String]) -> String {
+    if classes.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"{}\"", classes.join(" "))
+    }
+}
+
+fn header_class(cell: &HeaderCell) -> String {
+    let mut classes = match cell.kind {
+        HeaderKind::Group => vec!["group".to_string()],
+        _ => vec!["label".to_string()],
+    };
+    classes.extend(leaf_classes(cell));
+    classes.join(" ")
+}
+
+/// Classes marking summaries and NETs, shared by the header and the data cells
+fn leaf_classes(cell: &HeaderCell) -> Vec<String> {
+    let mut classes = Vec::new();
+    if let HeaderKind::Summary(summary_type) = &cell.kind {
+        classes.push("summary".to_string());
+        if let Some(t) = summary_type {
+            classes.push(format!("summary-{}", class_name(t)));
+        }
+    }
+    if cell.is_net() {
+        classes.push("net".to_string());
+    }
+    classes
+}
+
+/// Reduce a summary type such as "xs:base" to a usable class name
+fn class_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
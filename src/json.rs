@@ -0,0 +1,588 @@
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Value of the `format` member of every JSON export
+pub const JSON_FORMAT: &str = "xtabml-json";
+
+/// Version of the JSON format written by [`XtabML::to_json`]
+///
+/// Bumped on any incompatible change; the importer refuses newer versions.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) describing the JSON format
+pub const JSON_SCHEMA: &str = include_str!("../schema/xtabml-json-1.schema.json");
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    format: String,
+    format_version: u32,
+    xtabml_version: String,
+    date: Option<String>,
+    time: Option<String>,
+    origin: Option<String>,
+    user: Option<String>,
+    languages: Vec<LanguageJson>,
+    control_types: Vec<ControlTypeJson>,
+
+    /// Statistic types by name
+    statistics: Map<String, Value>,
+    controls: Vec<ControlJson>,
+    tables: Vec<TableJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LanguageJson {
+    lang: String,
+    base: bool,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ControlTypeJson {
+    name: String,
+    status: Option<String>,
+    text: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    translations: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StatisticJson {
+    text: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    translations: Map<String, Value>,
+
+    /// False for statistics used by a table without a `statistictype` declaration
+    declared: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ControlJson {
+    r#type: String,
+    text: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    translations: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TableJson {
+    name: Option<String>,
+    title: String,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    title_translations: Map<String, Value>,
+    controls: Vec<ControlJson>,
+    rows: Option<EdgeJson>,
+    columns: Option<EdgeJson>,
+
+    /// Statistic names, in the order of the first cube dimension
+    statistics: Vec<String>,
+
+    /// Declared datatypes of the statistics, in the same order; empty when none has one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    datatypes: Vec<Option<String>>,
+
+    /// Cells indexed by statistic, row leaf and column leaf
+    cube: Vec<Vec<Vec<CellJson>>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EdgeJson {
+    name: Option<String>,
+    leaf_count: usize,
+    groups: Vec<GroupJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GroupJson {
+    name: Option<String>,
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    translations: Map<String, Value>,
+    children: Vec<NodeJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum NodeJson {
+    Group(GroupJson),
+    Element {
+        leaf: usize,
+        name: Option<String>,
+        text: String,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        translations: Map<String, Value>,
+    },
+    Summary {
+        leaf: usize,
+        name: Option<String>,
+        r#type: Option<String>,
+        text: String,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        translations: Map<String, Value>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CellJson {
+    /// Value as exported, `null` for a missing value or a cell without one
+    value: Option<String>,
+
+    /// Numeric reading of the value, when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    number: Option<f64>,
+
+    /// Missing value indicator (`<x>`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    missing: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+}
+
+impl XtabML {
+    /// Export the document in the versioned JSON format described by [`JSON_SCHEMA`]
+    ///
+    /// Edges are written as trees whose elements and summaries carry their leaf index,
+    /// and each table's data as a cube indexed by statistic, row leaf and column leaf.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_json_value())?)
+    }
+
+    /// Export the document as a JSON value, see [`XtabML::to_json`]
+    pub fn to_json_value(&self) -> Value {
+        let mut statistics = Map::new();
+        for statistic_type in &self.statistic_types {
+            statistics.insert(
+                statistic_type.name.clone(),
+                statistic_json(statistic_type.text.clone(), &statistic_type.alt_texts, true),
+            );
+        }
+        for statistic in self.tables.iter().flat_map(|t| &t.statistics) {
+            if !statistics.contains_key(&statistic.r#type) {
+                statistics.insert(
+                    statistic.r#type.clone(),
+                    statistic_json(statistic.r#type.clone(), &[], false),
+                );
+            }
+        }
+
+        let document = Document {
+            format: JSON_FORMAT.to_string(),
+            format_version: JSON_FORMAT_VERSION,
+            xtabml_version: self.version.clone(),
+            date: self.date.clone(),
+            time: self.time.clone(),
+            origin: self.origin.clone(),
+            user: self.user.clone(),
+            languages: self
+                .languages
+                .iter()
+                .map(|l| LanguageJson {
+                    lang: l.lang.clone(),
+                    base: l.base.as_deref() == Some("yes"),
+                    description: l.description.clone(),
+                })
+                .collect(),
+            control_types: self
+                .control_types
+                .iter()
+                .map(|c| ControlTypeJson {
+                    name: c.name.clone(),
+                    status: c.status.clone(),
+                    text: c.text.clone(),
+                    translations: translations(&c.alt_texts),
+                })
+                .collect(),
+            statistics,
+            controls: self.controls.iter().map(control_json).collect(),
+            tables: self.tables.iter().map(table_json).collect(),
+        };
+        serde_json::to_value(document).expect("the JSON export only contains string keys")
+    }
+
+    /// Rebuild a document from the JSON format written by [`XtabML::to_json`]
+    pub fn from_json(json: &str) -> Result<XtabML> {
+        Self::from_json_value(serde_json::from_str(json)?)
+    }
+
+    /// Rebuild a document from a JSON value, see [`XtabML::from_json`]
+    pub fn from_json_value(value: Value) -> Result<XtabML> {
+        let format = value.get("format").and_then(Value::as_str);
+        if format != Some(JSON_FORMAT) {
+            return Err(XtabMLError::InvalidStructure(format!(
+                "not an {} document",
+                JSON_FORMAT
+            )));
+        }
+        match value.get("formatVersion").and_then(Value::as_u64) {
+            Some(version) if version <= JSON_FORMAT_VERSION as u64 => {}
+            Some(version) => {
+                return Err(XtabMLError::InvalidStructure(format!(
+                    "{} version {} is newer than the supported version {}",
+                    JSON_FORMAT, version, JSON_FORMAT_VERSION
+                )))
+            }
+            None => {
+                return Err(XtabMLError::MissingElement("formatVersion".to_string()));
+            }
+        }
+
+        let document: Document = serde_json::from_value(value)?;
+        let mut statistic_types = Vec::new();
+        for (name, statistic) in document.statistics {
+            let statistic: StatisticJson = serde_json::from_value(statistic)?;
+            if statistic.declared {
+                statistic_types.push(StatisticType {
+                    name,
+                    text: statistic.text,
+                    alt_texts: alt_texts(statistic.translations)?,
+                });
+            }
+        }
+
+        Ok(XtabML {
+            version: document.xtabml_version,
+            date: document.date,
+            time: document.time,
+            origin: document.origin,
+            user: document.user,
+            languages: document
+                .languages
+                .into_iter()
+                .map(|l| Language {
+                    lang: l.lang,
+                    base: l.base.then(|| "yes".to_string()),
+                    description: l.description,
+                })
+                .collect(),
+            control_types: document
+                .control_types
+                .into_iter()
+                .map(|c| {
+                    Ok(ControlType {
+                        name: c.name,
+                        status: c.status,
+                        text: c.text,
+                        alt_texts: alt_texts(c.translations)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            statistic_types,
+            controls: controls(document.controls)?,
+            tables: document
+                .tables
+                .into_iter()
+                .map(table)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+fn statistic_json(text: String, alt: &[AltText], declared: bool) -> Value {
+    serde_json::to_value(StatisticJson {
+        text,
+        translations: translations(alt),
+        declared,
+    })
+    .expect("the JSON export only contains string keys")
+}
+
+fn translations(alt: &[AltText]) -> Map<String, Value> {
+    alt.iter()
+        .map(|a| (a.lang.clone(), Value::String(a.text.clone())))
+        .collect()
+}
+
+fn alt_texts(translations: Map<String, Value>) -> Result<Vec<AltText>> {
+    translations
+        .into_iter()
+        .map(|(lang, text)| match text {
+            Value::String(text) => Ok(AltText { lang, text }),
+            _ => Err(XtabMLError::InvalidStructure(format!(
+                "translation for \"{}\" is not a string",
+                lang
+            ))),
+        })
+        .collect()
+}
+
+fn control_json(control: &Control) -> ControlJson {
+    ControlJson {
+        r#type: control.r#type.clone(),
+        text: control.text.clone(),
+        translations: translations(&control.alt_texts),
+    }
+}
+
+fn controls(controls: Vec<ControlJson>) -> Result<Vec<Control>> {
+    controls
+        .into_iter()
+        .map(|c| {
+            Ok(Control {
+                r#type: c.r#type,
+                text: c.text,
+                alt_texts: alt_texts(c.translations)?,
+            })
+        })
+        .collect()
+}
+
+fn table_json(table: &Table) -> TableJson {
+    let columns = table.column_count();
+    let cube = (0..table.statistics.len())
+        .map(|index| {
+            table
+                .data
+                .rows
+                .iter()
+                .map(|row| {
+                    let series = row.data_row_series.get(index);
+                    (0..columns)
+                        .map(|column| cell_json(series.and_then(|s| s.cell(column))))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    TableJson {
+        name: table.name.clone(),
+        title: table.title.clone(),
+        title_translations: translations(&table.title_alt_texts),
+        controls: table.controls.iter().map(control_json).collect(),
+        rows: table.row_edge.as_ref().map(edge_json),
+        columns: table.column_edge.as_ref().map(edge_json),
+        statistics: table.statistics.iter().map(|s| s.r#type.clone()).collect(),
+        datatypes: if table.statistics.iter().any(|s| s.datatype.is_some()) {
+            table
+                .statistics
+                .iter()
+                .map(|s| s.datatype.clone())
+                .collect()
+        } else {
+            Vec::new()
+        },
+        cube,
+    }
+}
+
+fn cell_json(cell: Option<&DataCell>) -> CellJson {
+    match cell.filter(|c| !c.is_missing) {
+        Some(cell) => CellJson {
+            value: cell.value.clone(),
+            number: cell.as_f64().filter(|n| n.is_finite()),
+            missing: false,
+            hidden: cell.is_hidden,
+        },
+        None => CellJson {
+            value: None,
+            number: None,
+            missing: true,
+            hidden: cell.is_some_and(|c| c.is_hidden),
+        },
+    }
+}
+
+fn edge_json(edge: &Edge) -> EdgeJson {
+    // Leaf index of each element and summary, by address
+    let leaves = edge.leaves();
+    let index: HashMap<*const (), usize> = leaves
+        .iter()
+        .enumerate()
+        .map(|(i, leaf)| {
+            let address = match leaf.node {
+                EdgeNode::Element(e) => e as *const Element as *const (),
+                EdgeNode::Summary(s) => s as *const Summary as *const (),
+            };
+            (address, i)
+        })
+        .collect();
+
+    EdgeJson {
+        name: edge.name.clone(),
+        leaf_count: leaves.len(),
+        groups: edge.groups.iter().map(|g| group_json(g, &index)).collect(),
+    }
+}
+
+/// A group with its children in document order, so the tree reads like the edge
+fn group_json(group: &Group, index: &HashMap<*const (), usize>) -> GroupJson {
+    let mut children: Vec<(usize, NodeJson)> = Vec::new();
+    for element in &group.elements {
+        let leaf = index[&(element as *const Element as *const ())];
+        children.push((
+            leaf,
+            NodeJson::Element {
+                leaf,
+                name: element.name.clone(),
+                text: element.text.clone(),
+                translations: translations(&element.alt_texts),
+            },
+        ));
+    }
+    for summary in &group.summaries {
+        let leaf = index[&(summary as *const Summary as *const ())];
+        children.push((
+            leaf,
+            NodeJson::Summary {
+                leaf,
+                name: summary.name.clone(),
+                r#type: summary.r#type.clone(),
+                text: summary.text.clone(),
+                translations: translations(&summary.alt_texts),
+            },
+        ));
+    }
+    for nested in &group.groups {
+        let child = group_json(nested, index);
+        children.push((
+            first_leaf(&child).unwrap_or(usize::MAX),
+            NodeJson::Group(child),
+        ));
+    }
+    children.sort_by_key(|(leaf, _)| *leaf);
+
+    GroupJson {
+        name: group.name.clone(),
+        text: group.text.clone(),
+        translations: translations(&group.alt_texts),
+        children: children.into_iter().map(|(_, node)| node).collect(),
+    }
+}
+
+fn first_leaf(group: &GroupJson) -> Option<usize> {
+    group
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            NodeJson::Group(g) => first_leaf(g),
+            NodeJson::Element { leaf, .. } | NodeJson::Summary { leaf, .. } => Some(*leaf),
+        })
+        .min()
+}
+
+fn table(table: TableJson) -> Result<Table> {
+    if !table.datatypes.is_empty() && table.datatypes.len() != table.statistics.len() {
+        return Err(XtabMLError::InvalidStructure(format!(
+            "table \"{}\" has {} statistics but {} datatypes",
+            table.title,
+            table.statistics.len(),
+            table.datatypes.len()
+        )));
+    }
+    let mut datatypes = table.datatypes.into_iter();
+    let statistics: Vec<Statistic> = table
+        .statistics
+        .into_iter()
+        .map(|r#type| Statistic {
+            r#type,
+            datatype: datatypes.next().flatten(),
+        })
+        .collect();
+    if table.cube.len() != statistics.len() {
+        return Err(XtabMLError::InvalidStructure(format!(
+            "table \"{}\" has {} statistics but {} cube slices",
+            table.title,
+            statistics.len(),
+            table.cube.len()
+        )));
+    }
+
+    let row_count = table.cube.first().map_or(0, Vec::len);
+    let mut rows: Vec<DataRow> = (0..row_count)
+        .map(|_| DataRow {
+            data_row_series: Vec::with_capacity(statistics.len()),
+        })
+        .collect();
+    for (statistic, slice) in statistics.iter().zip(table.cube) {
+        if slice.len() != row_count {
+            return Err(XtabMLError::InvalidStructure(format!(
+                "table \"{}\" has cube slices with different row counts",
+                table.title
+            )));
+        }
+        for (row, cells) in rows.iter_mut().zip(slice) {
+            row.data_row_series.push(DataRowSeries {
+                statistic: Some(statistic.clone()),
+                cells: cells
+                    .into_iter()
+                    .map(|c| DataCell {
+                        is_missing: c.missing,
+                        value: c.value,
+                        is_hidden: c.hidden,
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    Ok(Table {
+        name: table.name,
+        title: table.title,
+        title_alt_texts: alt_texts(table.title_translations)?,
+        controls: controls(table.controls)?,
+        row_edge: table.rows.map(|e| edge(e, "r")).transpose()?,
+        column_edge: table.columns.map(|e| edge(e, "c")).transpose()?,
+        statistics,
+        data: TableData { rows },
+    })
+}
+
+fn edge(edge: EdgeJson, axis: &str) -> Result<Edge> {
+    Ok(Edge {
+        axis: axis.to_string(),
+        name: edge.name,
+        groups: edge.groups.into_iter().map(group).collect::<Result<_>>()?,
+    })
+}
+
+fn group(group: GroupJson) -> Result<Group> {
+    let mut elements = Vec::new();
+    let mut summaries = Vec::new();
+    let mut groups = Vec::new();
+    for child in group.children {
+        match child {
+            NodeJson::Group(nested) => groups.push(self::group(nested)?),
+            NodeJson::Element {
+                leaf,
+                name,
+                text,
+                translations,
+            } => elements.push(Element {
+                text,
+                index: Some(elements.len() as i32),
+                name,
+                alt_texts: alt_texts(translations)?,
+                position: Some(leaf),
+            }),
+            NodeJson::Summary {
+                leaf,
+                name,
+                r#type,
+                text,
+                translations,
+            } => summaries.push(Summary {
+                text,
+                name,
+                r#type,
+                alt_texts: alt_texts(translations)?,
+                position: Some(leaf),
+            }),
+        }
+    }
+
+    Ok(Group {
+        name: group.name,
+        text: group.text,
+        alt_texts: alt_texts(group.translations)?,
+        elements,
+        summaries,
+        groups,
+    })
+}
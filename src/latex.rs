@@ -0,0 +1,161 @@
+use crate::layout::RenderOptions;
+use crate::types::*;
+
+impl XtabML {
+    /// Render every table as a LaTeX `table` float
+    pub fn to_latex(&self, options: &RenderOptions) -> String {
+        self.tables
+            .iter()
+            .map(|table| {
+                format!(
+                    "\\begin{{table}}\n\\centering\n\\caption{{{}}}\n{}\\end{{table}}\n",
+                    escape(&table.title),
+                    table.to_latex(options)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Table {
+    /// Render the table as a LaTeX `tabular` using booktabs rules
+    ///
+    /// Nested column groups become `\multicolumn` headings with `\cmidrule`s underneath,
+    /// and row group headings span their rows with `\multirow`. The output needs
+    /// `\usepackage{booktabs}` and `\usepackage{multirow}`.
+    pub fn to_latex(&self, options: &RenderOptions) -> String {
+        let rows = self.row_header();
+        let columns = self.column_header();
+        let statistics = options.statistic_indices(self);
+        let show_statistic = statistics.len() > 1;
+        let stub = rows.levels + usize::from(show_statistic);
+        let width = columns.leaves.len();
+
+        let mut out = format!(
+            "\\begin{{tabular}}{{{}{}}}\n\\toprule\n",
+            "l".repeat(stub),
+            "r".repeat(width)
+        );
+
+        // Group heading rows; leaves without a heading at some level only appear in the
+        // last header row
+        for level in 0..columns.levels - 1 {
+            let mut cells = vec![String::new(); stub];
+            let mut rules = Vec::new();
+            let mut position = 0;
+            while position < width {
+                match columns
+                    .groups
+                    .iter()
+                    .find(|c| c.level == level && c.start == position)
+                {
+                    Some(group) => {
+                        cells.push(format!(
+                            "\\multicolumn{{{}}}{{c}}{{{}}}",
+                            group.span,
+                            escape(&group.text)
+                        ));
+                        rules.push(format!(
+                            "\\cmidrule(lr){{{}-{}}}",
+                            stub + position + 1,
+                            stub + position + group.span
+                        ));
+                        position += group.span;
+                    }
+                    None => {
+                        cells.push(String::new());
+                        position += 1;
+                    }
+                }
+            }
+            out.push_str(&line(&cells));
+            if !rules.is_empty() {
+                out.push_str(&rules.join(" "));
+                out.push('\n');
+            }
+        }
+
+        let mut cells = vec![String::new(); stub];
+        cells.extend(columns.leaves.iter().map(|c| escape(&c.text)));
+        out.push_str(&line(&cells));
+        out.push_str("\\midrule\n");
+
+        let lines = statistics.len().max(1);
+        for (position, row) in self.data.rows.iter().enumerate() {
+            for (n, &index) in statistics.iter().enumerate() {
+                // `None` marks stub columns covered by a \multicolumn label
+                let mut stub_cells: Vec<Option<String>> = vec![Some(String::new()); stub];
+                if n == 0 {
+                    for header in rows.starting_at(position) {
+                        stub_cells[header.level] =
+                            Some(label(&header.text, header.span * lines, header.depth));
+                        for covered in 1..header.depth {
+                            stub_cells[header.level + covered] = None;
+                        }
+                    }
+                }
+                if show_statistic {
+                    stub_cells[rows.levels] = Some(escape(&self.statistics[index].r#type));
+                }
+
+                let series = row.data_row_series.get(index);
+                let cells: Vec<String> = stub_cells
+                    .into_iter()
+                    .flatten()
+                    .chain((0..width).map(|column| {
+                        escape(
+                            &options
+                                .number_format
+                                .format(series.and_then(|s| s.cell(column))),
+                        )
+                    }))
+                    .collect();
+                out.push_str(&line(&cells));
+            }
+        }
+
+        out.push_str("\\bottomrule\n\\end{tabular}\n");
+        out
+    }
+}
+
+/// A row label spanning `rows` lines and `columns` stub columns
+fn label(text: &str, rows: usize, columns: usize) -> String {
+    let text = escape(text);
+    let text = if rows > 1 {
+        format!("\\multirow{{{}}}{{*}}{{{}}}", rows, text)
+    } else {
+        text
+    };
+    if columns > 1 {
+        format!("\\multicolumn{{{}}}{{l}}{{{}}}", columns, text)
+    } else {
+        text
+    }
+}
+
+fn line(cells: &[String]) -> String {
+    format!("{} \\\\\n", cells.join(" & "))
+}
+
+/// Escape characters with a special meaning in LaTeX
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '|' => out.push_str("\\textbar{}"),
+            '<' => out.push_str("\\textless{}"),
+            '>' => out.push_str("\\textgreater{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,269 @@
+use crate::types::*;
+
+/// Separator between group headings and labels in flattened headers
+pub(crate) const PATH_SEPARATOR: &str = " / ";
+
+/// What a header cell labels
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderKind {
+    Group,
+    Element,
+    Summary(Option<String>),
+}
+
+/// A header cell spanning one or more leaves of an edge
+#[derive(Debug, Clone)]
+pub struct HeaderCell {
+    pub text: String,
+    pub kind: HeaderKind,
+
+    /// Header level, 0 being the outermost group headings
+    pub level: usize,
+
+    /// Number of levels covered; leaves under fewer headings stretch down to the last level
+    pub depth: usize,
+
+    /// First leaf position covered
+    pub start: usize,
+
+    /// Number of leaves covered
+    pub span: usize,
+
+    /// Group headings above this cell, outermost first
+    pub path: Vec<String>,
+}
+
+impl HeaderCell {
+    pub fn is_summary(&self) -> bool {
+        matches!(self.kind, HeaderKind::Summary(_))
+    }
+
+    /// Whether this is a NET, either a `type="net"` summary or an element labelled "NET"
+    pub fn is_net(&self) -> bool {
+        match &self.kind {
+            HeaderKind::Group => false,
+            HeaderKind::Element => is_net(None, &self.text),
+            HeaderKind::Summary(r#type) => is_net(r#type.as_deref(), &self.text),
+        }
+    }
+
+    /// Label with the group headings prepended, e.g. "Gender / Female"
+    pub fn flat_label(&self, separator: &str) -> String {
+        let mut parts: Vec<&str> = self.path.iter().map(String::as_str).collect();
+        parts.push(&self.text);
+        parts.join(separator)
+    }
+}
+
+/// Header of an edge flattened into levels of spanning cells
+///
+/// Only groups with a heading produce a level, so a flat edge has a single level of leaf
+/// cells. This is the common model behind the table renderers: nested column groups become
+/// stacked header rows and nested row groups become header columns.
+#[derive(Debug, Clone)]
+pub struct EdgeHeader {
+    /// Number of levels, including the leaf level
+    pub levels: usize,
+
+    /// Group heading cells, ordered by level and start
+    pub groups: Vec<HeaderCell>,
+
+    /// Leaf cells in data order
+    pub leaves: Vec<HeaderCell>,
+}
+
+impl EdgeHeader {
+    /// Lay out an edge, padding with blank leaves up to `count` data positions
+    pub fn new(edge: Option<&Edge>, count: usize) -> Self {
+        let leaves = edge.map(Edge::leaves).unwrap_or_default();
+        let chains: Vec<Vec<&Group>> = leaves
+            .iter()
+            .map(|leaf| {
+                leaf.groups
+                    .iter()
+                    .copied()
+                    .filter(|g| g.text.is_some())
+                    .collect()
+            })
+            .collect();
+        let group_levels = chains.iter().map(Vec::len).max().unwrap_or(0);
+        let levels = group_levels + 1;
+
+        let mut groups: Vec<HeaderCell> = Vec::new();
+        for level in 0..group_levels {
+            let mut previous: Option<&Group> = None;
+            for (position, chain) in chains.iter().enumerate() {
+                let Some(&group) = chain.get(level) else {
+                    previous = None;
+                    continue;
+                };
+                if previous.is_some_and(|p| std::ptr::eq(p, group)) {
+                    if let Some(cell) = groups.last_mut() {
+                        cell.span += 1;
+                    }
+                } else {
+                    groups.push(HeaderCell {
+                        text: group.text.clone().unwrap_or_default(),
+                        kind: HeaderKind::Group,
+                        level,
+                        depth: 1,
+                        start: position,
+                        span: 1,
+                        path: headings(&chain[..level]),
+                    });
+                }
+                previous = Some(group);
+            }
+        }
+
+        let mut leaf_cells: Vec<HeaderCell> = leaves
+            .iter()
+            .zip(&chains)
+            .enumerate()
+            .map(|(position, (leaf, chain))| HeaderCell {
+                text: leaf.text().to_string(),
+                kind: match leaf.node {
+                    EdgeNode::Element(_) => HeaderKind::Element,
+                    EdgeNode::Summary(s) => HeaderKind::Summary(s.r#type.clone()),
+                },
+                level: chain.len(),
+                depth: levels - chain.len(),
+                start: position,
+                span: 1,
+                path: headings(chain),
+            })
+            .collect();
+        for position in leaf_cells.len()..count {
+            leaf_cells.push(HeaderCell {
+                text: String::new(),
+                kind: HeaderKind::Element,
+                level: 0,
+                depth: levels,
+                start: position,
+                span: 1,
+                path: Vec::new(),
+            });
+        }
+
+        EdgeHeader {
+            levels,
+            groups,
+            leaves: leaf_cells,
+        }
+    }
+
+    /// All cells starting at a level, group headings and leaves alike, in data order
+    pub fn level(&self, level: usize) -> Vec<&HeaderCell> {
+        let mut cells: Vec<&HeaderCell> = self
+            .groups
+            .iter()
+            .chain(&self.leaves)
+            .filter(|c| c.level == level)
+            .collect();
+        cells.sort_by_key(|c| c.start);
+        cells
+    }
+
+    /// All cells starting at a leaf position, outermost first
+    pub fn starting_at(&self, position: usize) -> Vec<&HeaderCell> {
+        let mut cells: Vec<&HeaderCell> = self
+            .groups
+            .iter()
+            .filter(|c| c.start == position)
+            .chain(self.leaves.get(position))
+            .collect();
+        cells.sort_by_key(|c| c.level);
+        cells
+    }
+
+    /// Leaf labels with group headings prepended
+    pub fn flat_labels(&self, separator: &str) -> Vec<String> {
+        self.leaves
+            .iter()
+            .map(|c| c.flat_label(separator))
+            .collect()
+    }
+}
+
+/// Options shared by the Markdown, LaTeX and text renderers
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Statistic types to render, in this order; all statistics of the table when `None`
+    pub statistics: Option<Vec<String>>,
+
+    pub number_format: NumberFormat,
+}
+
+/// Formatting of cell values
+#[derive(Debug, Clone, Default)]
+pub struct NumberFormat {
+    /// Round numeric values to this many decimals; values are printed as exported when `None`
+    pub decimals: Option<usize>,
+
+    /// Text for missing cells
+    pub missing: String,
+}
+
+impl NumberFormat {
+    /// Format a cell, keeping a trailing "%" of the exported value
+    ///
+    /// Hidden cells (`<h>`) are blank, since their values are not for printing.
+    pub fn format(&self, cell: Option<&DataCell>) -> String {
+        let Some(cell) = cell.filter(|c| !c.is_missing) else {
+            return self.missing.clone();
+        };
+        if cell.is_hidden {
+            return String::new();
+        }
+        let value = cell.value.as_deref().unwrap_or("").trim();
+        match (self.decimals, cell.as_f64()) {
+            (Some(decimals), Some(number)) => {
+                let suffix = if value.ends_with('%') { "%" } else { "" };
+                format!("{:.*}{}", decimals, number, suffix)
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Indices into `table.statistics` of the statistics to render
+    pub fn statistic_indices(&self, table: &Table) -> Vec<usize> {
+        match &self.statistics {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| table.statistics.iter().position(|s| &s.r#type == name))
+                .collect(),
+            None => (0..table.statistics.len()).collect(),
+        }
+    }
+}
+
+fn headings(groups: &[&Group]) -> Vec<String> {
+    groups.iter().filter_map(|g| g.text.clone()).collect()
+}
+
+impl Table {
+    /// Number of data columns, the larger of the column edge and the widest row
+    pub fn column_count(&self) -> usize {
+        let edge = self.column_edge.as_ref().map_or(0, Edge::leaf_count);
+        let data = self
+            .data
+            .rows
+            .iter()
+            .flat_map(|r| r.data_row_series.iter().map(|s| s.cells.len()))
+            .max()
+            .unwrap_or(0);
+        edge.max(data)
+    }
+
+    /// Header layout of the row edge, covering every data row
+    pub fn row_header(&self) -> EdgeHeader {
+        EdgeHeader::new(self.row_edge.as_ref(), self.data.rows.len())
+    }
+
+    /// Header layout of the column edge, covering every data column
+    pub fn column_header(&self) -> EdgeHeader {
+        EdgeHeader::new(self.column_edge.as_ref(), self.column_count())
+    }
+}
@@ -1,8 +1,60 @@
+mod combine;
+mod csv;
+mod derive;
+mod distribution;
+mod flat;
+mod html;
+#[cfg(feature = "json")]
+mod json;
+mod latex;
+mod layout;
+mod lookup;
+mod markdown;
+mod metadata;
+mod net;
+#[cfg(feature = "parquet")]
+mod parquet;
 mod parser;
+mod query;
+mod search;
+mod significance;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod statistic;
+mod suppression;
+mod text;
+mod transform;
 mod types;
+mod validation;
+mod vocabulary;
+#[cfg(feature = "xlsx")]
+mod xlsx;
 
+pub use derive::{Derivation, Input, IntervalMethod, IntervalOptions, Percentage};
+pub use flat::{FlatOptions, Layout, LongRecord, WideRow, WideTable};
+pub use html::{HtmlOptions, DEFAULT_CSS};
+#[cfg(feature = "json")]
+pub use json::{JSON_FORMAT, JSON_FORMAT_VERSION, JSON_SCHEMA};
+pub use layout::{EdgeHeader, HeaderCell, HeaderKind, NumberFormat, RenderOptions};
+pub use lookup::{Axis, LookupError, Selector};
+pub use metadata::{
+    BaseSize, ClassifiedControl, ControlFacts, ControlKind, ControlPattern, MetadataRules,
+    TableMetadata,
+};
+pub use net::NetOptions;
 pub use parser::XtabMLParser;
+pub use query::{Query, TableSlice};
+pub use search::{NodeKind, SearchHit, SearchPattern};
+pub use significance::{ChiSquare, Correction, SignificanceOptions, TTest};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteExporter;
+pub use statistic::{Basis, StandardStatistic, StatisticRegistry};
+pub use suppression::{BaseSource, LowBaseAction, LowBaseCell, LowBaseRules};
+pub use text::{BoxStyle, Overflow, TextOptions};
+pub use transform::{SortOptions, SortOrder};
 pub use types::*;
+pub use validation::{validate_bytes, validate_str, Diagnostic, Rule, Severity};
+pub use vocabulary::{TranslationReport, Vocabulary};
 
 use thiserror::Error;
 
@@ -10,16 +62,62 @@ use thiserror::Error;
 pub enum XtabMLError {
     #[error("XML parsing error: {0}")]
     XmlParse(#[from] quick_xml::Error),
-    
+
     #[error("Invalid XtabML structure: {0}")]
     InvalidStructure(String),
-    
+
     #[error("Missing required element: {0}")]
     MissingElement(String),
-    
+
+    #[error("Invalid query: {0}")]
+    Query(String),
+
+    #[error("Lookup failed: {0}")]
+    Lookup(#[from] LookupError),
+
+    #[error("Edges differ: {0}")]
+    EdgeMismatch(String),
+
+    #[error("Cannot add NET: {0}")]
+    Net(String),
+
+    #[error("Summing is invalid for multi-response data: {0}")]
+    MultiResponse(String),
+
+    #[error("Cannot derive statistics: {0}")]
+    Derivation(String),
+
+    #[error("Cannot test significance: {0}")]
+    Significance(String),
+
+    #[error("Cannot translate: {0}")]
+    Translation(String),
+
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "toml")]
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[cfg(feature = "xlsx")]
+    #[error("Excel error: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] ::parquet::errors::ParquetError),
 }
 
 pub type Result<T> = std::result::Result<T, XtabMLError>;
-
@@ -0,0 +1,185 @@
+use crate::layout::PATH_SEPARATOR;
+use crate::types::*;
+use std::fmt;
+use thiserror::Error;
+
+/// The rows or the columns of a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    Rows,
+    Columns,
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Axis::Rows => "row",
+            Axis::Columns => "column",
+        })
+    }
+}
+
+/// Identifies a data row or column of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// Position among the leaves of the edge, i.e. the data row or column
+    Index(usize),
+
+    /// Text of an element or summary
+    Label(String),
+
+    /// Group headings followed by the text of an element or summary, e.g.
+    /// `["Gender", "Female"]`; leading headings may be left out
+    Path(Vec<String>),
+
+    /// `name` attribute of an element or summary
+    Name(String),
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Index(i) => write!(f, "index {}", i),
+            Selector::Label(label) => write!(f, "{:?}", label),
+            Selector::Path(path) => write!(f, "{:?}", path.join(PATH_SEPARATOR)),
+            Selector::Name(name) => write!(f, "name {:?}", name),
+        }
+    }
+}
+
+impl From<usize> for Selector {
+    fn from(index: usize) -> Selector {
+        Selector::Index(index)
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(label: &str) -> Selector {
+        Selector::Label(label.to_string())
+    }
+}
+
+impl From<String> for Selector {
+    fn from(label: String) -> Selector {
+        Selector::Label(label)
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for Selector {
+    fn from(path: [&str; N]) -> Selector {
+        Selector::Path(path.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+/// Why a labelled lookup failed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    #[error("no {axis} matches {selector}")]
+    NotFound { axis: Axis, selector: String },
+
+    /// Several rows or columns match; `matches` holds their positions
+    #[error("{selector} matches {} {axis}s: {}", .labels.len(), .labels.join(", "))]
+    Ambiguous {
+        axis: Axis,
+        selector: String,
+        matches: Vec<usize>,
+        labels: Vec<String>,
+    },
+
+    #[error("the table has no statistic {0:?}")]
+    UnknownStatistic(String),
+
+    /// The edges have the row and column but the data does not
+    #[error("no data for row {row}, column {column}")]
+    NoData { row: usize, column: usize },
+}
+
+impl Table {
+    /// Cell of a statistic at a row and column given by selectors
+    ///
+    /// ```no_run
+    /// use libxtabml::XtabMLParser;
+    ///
+    /// let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let cell = xtab.tables[0].cell("Optus", ["Gender", "Female"], "ColumnPercent")?;
+    /// println!("{:?}", cell.as_f64());
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Compressed runs are resolved; cells marked missing are returned as they are.
+    pub fn cell(
+        &self,
+        row: impl Into<Selector>,
+        column: impl Into<Selector>,
+        statistic: &str,
+    ) -> Result<&DataCell, LookupError> {
+        let row = self.locate(Axis::Rows, &row.into())?;
+        let column = self.locate(Axis::Columns, &column.into())?;
+        let series = self
+            .statistics
+            .iter()
+            .position(|s| s.r#type == statistic)
+            .ok_or_else(|| LookupError::UnknownStatistic(statistic.to_string()))?;
+        self.data
+            .rows
+            .get(row)
+            .and_then(|r| r.data_row_series.get(series))
+            .and_then(|s| s.cell(column))
+            .ok_or(LookupError::NoData { row, column })
+    }
+
+    /// Position of the single data row or column matching a selector
+    pub fn locate(&self, axis: Axis, selector: &Selector) -> Result<usize, LookupError> {
+        let (edge, count) = match axis {
+            Axis::Rows => (self.row_edge.as_ref(), self.data.rows.len()),
+            Axis::Columns => (self.column_edge.as_ref(), self.column_count()),
+        };
+        let leaves = edge.map(Edge::leaves).unwrap_or_default();
+        let matches: Vec<usize> = match selector {
+            Selector::Index(i) => (*i < count.max(leaves.len()))
+                .then_some(*i)
+                .into_iter()
+                .collect(),
+            _ => leaves
+                .iter()
+                .enumerate()
+                .filter(|(_, leaf)| selects(selector, leaf))
+                .map(|(i, _)| i)
+                .collect(),
+        };
+        match matches.as_slice() {
+            [] => Err(LookupError::NotFound {
+                axis,
+                selector: selector.to_string(),
+            }),
+            [position] => Ok(*position),
+            _ => Err(LookupError::Ambiguous {
+                axis,
+                selector: selector.to_string(),
+                labels: matches.iter().map(|&i| path(&leaves[i])).collect(),
+                matches,
+            }),
+        }
+    }
+}
+
+fn selects(selector: &Selector, leaf: &EdgeLeaf<'_>) -> bool {
+    match selector {
+        Selector::Index(_) => false,
+        Selector::Label(label) => leaf.text() == label,
+        Selector::Name(name) => leaf.node.name() == Some(name.as_str()),
+        Selector::Path(wanted) => {
+            let mut full = leaf.headings();
+            full.push(leaf.text());
+            !wanted.is_empty()
+                && full.ends_with(&wanted.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+    }
+}
+
+/// Label of a leaf with its group headings, e.g. "Gender / Female"
+fn path(leaf: &EdgeLeaf<'_>) -> String {
+    let mut parts = leaf.headings();
+    parts.push(leaf.text());
+    parts.join(PATH_SEPARATOR)
+}
@@ -0,0 +1,85 @@
+use crate::layout::{RenderOptions, PATH_SEPARATOR};
+use crate::types::*;
+
+impl XtabML {
+    /// Render every table as GitHub-flavoured Markdown, each under its title as a heading
+    pub fn to_markdown(&self, options: &RenderOptions) -> String {
+        self.tables
+            .iter()
+            .map(|table| {
+                format!(
+                    "## {}\n\n{}",
+                    escape(&table.title),
+                    table.to_markdown(options)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Table {
+    /// Render the table as a GitHub-flavoured Markdown table
+    ///
+    /// Markdown has no spanning cells, so nested headers are flattened to
+    /// "Group / Label". When more than one statistic is rendered, each gets its own line
+    /// with the statistic type in a second column.
+    pub fn to_markdown(&self, options: &RenderOptions) -> String {
+        let rows = self.row_header();
+        let columns = self.column_header();
+        let statistics = options.statistic_indices(self);
+        let show_statistic = statistics.len() > 1;
+
+        let mut header = vec![String::new()];
+        if show_statistic {
+            header.push("Statistic".to_string());
+        }
+        header.extend(
+            columns
+                .flat_labels(PATH_SEPARATOR)
+                .iter()
+                .map(|l| escape(l)),
+        );
+
+        let mut alignment = vec![":--".to_string()];
+        if show_statistic {
+            alignment.push(":--".to_string());
+        }
+        alignment.extend(columns.leaves.iter().map(|_| "--:".to_string()));
+
+        let mut lines = vec![line(&header), line(&alignment)];
+        for (position, row) in self.data.rows.iter().enumerate() {
+            for (n, &index) in statistics.iter().enumerate() {
+                let mut cells = vec![if n == 0 {
+                    escape(&rows.leaves[position].flat_label(PATH_SEPARATOR))
+                } else {
+                    String::new()
+                }];
+                if show_statistic {
+                    cells.push(escape(&self.statistics[index].r#type));
+                }
+                let series = row.data_row_series.get(index);
+                cells.extend((0..columns.leaves.len()).map(|column| {
+                    escape(
+                        &options
+                            .number_format
+                            .format(series.and_then(|s| s.cell(column))),
+                    )
+                }));
+                lines.push(line(&cells));
+            }
+        }
+
+        let mut out = lines.join("\n");
+        out.push('\n');
+        out
+    }
+}
+
+fn line(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
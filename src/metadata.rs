@@ -0,0 +1,277 @@
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What a control is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ControlKind {
+    Project,
+    Base,
+    Weight,
+    Filter,
+    Notes,
+    Other,
+}
+
+/// A control with the declaration of its type
+#[derive(Debug, Clone, Copy)]
+pub struct ClassifiedControl<'a> {
+    pub control: &'a Control,
+    pub kind: ControlKind,
+
+    /// Declared `status` of the control type, "primary" or "secondary"
+    pub status: Option<&'a str>,
+
+    /// Whether the control belongs to the whole document rather than the table
+    pub report_level: bool,
+}
+
+/// Sample sizes a base control states, e.g. "base n = from 369 to 711; total n = 713"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BaseSize {
+    /// Smallest base of the table's columns or rows; the base where there is only one
+    pub min: f64,
+    pub max: f64,
+    pub total: Option<f64>,
+    pub missing: Option<f64>,
+}
+
+/// Facts read from a table's controls
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlFacts {
+    pub project: Option<String>,
+
+    /// Description of the base, e.g. "Total sample"
+    pub base_description: Option<String>,
+    pub base_n: Option<BaseSize>,
+
+    /// Whether the table is weighted, where a control says
+    pub weighted: Option<bool>,
+
+    /// Text of the weight control of a weighted table
+    pub weight: Option<String>,
+
+    /// Filter expressions, report-level ones first
+    pub filters: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+/// Reads facts from controls of a tabulation system
+///
+/// Closures taking a control and the facts so far implement it too.
+pub trait ControlPattern {
+    fn apply(&self, control: &ClassifiedControl<'_>, facts: &mut ControlFacts);
+}
+
+impl<F: Fn(&ClassifiedControl<'_>, &mut ControlFacts)> ControlPattern for F {
+    fn apply(&self, control: &ClassifiedControl<'_>, facts: &mut ControlFacts) {
+        self(control, facts)
+    }
+}
+
+/// How [`Table::metadata`] classifies controls and reads them
+///
+/// Control types are classified by name where one is registered, and otherwise by
+/// keywords ("project", "base", "weight", "filter", "note") in the type's name and
+/// declared text, so that `<controltype name="gf"><t>Global filter</t></controltype>`
+/// declares filters.
+pub struct MetadataRules {
+    kinds: HashMap<String, ControlKind>,
+    patterns: Vec<Box<dyn ControlPattern>>,
+}
+
+impl Default for MetadataRules {
+    fn default() -> MetadataRules {
+        MetadataRules::standard()
+    }
+}
+
+impl MetadataRules {
+    /// Keyword classification and the patterns common to tabulation systems
+    pub fn standard() -> MetadataRules {
+        MetadataRules {
+            kinds: HashMap::new(),
+            patterns: vec![Box::new(standard_pattern)],
+        }
+    }
+
+    /// Classify controls of a type as given
+    pub fn register_kind(&mut self, control_type: impl Into<String>, kind: ControlKind) {
+        self.kinds.insert(control_type.into(), kind);
+    }
+
+    /// Read controls with a further pattern, after those added before
+    pub fn add_pattern(&mut self, pattern: impl ControlPattern + 'static) {
+        self.patterns.push(Box::new(pattern));
+    }
+
+    fn classify(&self, control_type: &str, declaration: Option<&ControlType>) -> ControlKind {
+        if let Some(&kind) = self.kinds.get(control_type) {
+            return kind;
+        }
+        let described = format!(
+            "{} {}",
+            control_type,
+            declaration.map_or("", |d| d.text.as_str())
+        )
+        .to_lowercase();
+        [
+            ("filter", ControlKind::Filter),
+            ("weight", ControlKind::Weight),
+            ("base", ControlKind::Base),
+            ("note", ControlKind::Notes),
+            ("project", ControlKind::Project),
+        ]
+        .into_iter()
+        .find(|(keyword, _)| described.contains(keyword))
+        .map_or(ControlKind::Other, |(_, kind)| kind)
+    }
+}
+
+/// What a table's controls say
+#[derive(Debug, Clone)]
+pub struct TableMetadata<'a> {
+    /// Report-level controls, then the table's
+    pub controls: Vec<ClassifiedControl<'a>>,
+    pub facts: ControlFacts,
+}
+
+impl TableMetadata<'_> {
+    /// Controls of a kind
+    pub fn of_kind(&self, kind: ControlKind) -> impl Iterator<Item = &ClassifiedControl<'_>> {
+        self.controls.iter().filter(move |c| c.kind == kind)
+    }
+}
+
+impl Table {
+    /// The table's controls, and those of its document, classified and read
+    ///
+    /// ```no_run
+    /// # use libxtabml::{MetadataRules, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let rules = MetadataRules::standard();
+    /// let small_weighted = xtab.tables.iter().filter(|table| {
+    ///     let facts = table.metadata(&xtab, &rules).facts;
+    ///     facts.weighted == Some(true) && facts.base_n.is_some_and(|n| n.min < 100.0)
+    /// });
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Control types take their status from the declarations of `xtab`. The standard
+    /// patterns read base controls made of parts separated by semicolons, such as "Total
+    /// sample; Unweighted; base n = 713": a part "weighted" or "unweighted", sizes
+    /// "base n = 713" or "base n = from 369 to 711", "total n = 713" and "344 missing",
+    /// and the first other part as the description. A weight control marks the table
+    /// weighted unless it says "unweighted" or "none"; each line of a filter control is a
+    /// filter expression. Later patterns and table controls override earlier facts.
+    pub fn metadata<'a>(&'a self, xtab: &'a XtabML, rules: &MetadataRules) -> TableMetadata<'a> {
+        let controls: Vec<ClassifiedControl<'a>> = xtab
+            .controls
+            .iter()
+            .map(|c| (c, true))
+            .chain(self.controls.iter().map(|c| (c, false)))
+            .map(|(control, report_level)| {
+                let declaration = xtab.control_types.iter().find(|d| d.name == control.r#type);
+                ClassifiedControl {
+                    control,
+                    kind: rules.classify(&control.r#type, declaration),
+                    status: declaration.and_then(|d| d.status.as_deref()),
+                    report_level,
+                }
+            })
+            .collect();
+        let mut facts = ControlFacts::default();
+        for control in &controls {
+            for pattern in &rules.patterns {
+                pattern.apply(control, &mut facts);
+            }
+        }
+        TableMetadata { controls, facts }
+    }
+}
+
+fn standard_pattern(control: &ClassifiedControl<'_>, facts: &mut ControlFacts) {
+    let text = control.control.text.trim();
+    match control.kind {
+        ControlKind::Project => facts.project = Some(text.to_string()),
+        ControlKind::Notes => facts.notes.push(text.to_string()),
+        ControlKind::Filter => facts.filters.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string),
+        ),
+        ControlKind::Weight => {
+            let lower = text.to_lowercase();
+            let unweighted = text.is_empty()
+                || ["unweighted", "none", "no weight"]
+                    .iter()
+                    .any(|w| lower.contains(w));
+            facts.weighted = Some(!unweighted);
+            facts.weight = (!unweighted).then(|| text.to_string());
+        }
+        ControlKind::Base => read_base(text, facts),
+        ControlKind::Other => {}
+    }
+}
+
+fn read_base(text: &str, facts: &mut ControlFacts) {
+    let mut description = None;
+    let mut size: Option<BaseSize> = None;
+    let (mut total, mut missing) = (None, None);
+    for part in text
+        .split([';', '\n'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    {
+        let lower = part.to_lowercase();
+        if lower == "unweighted" || lower == "not weighted" {
+            facts.weighted = Some(false);
+        } else if lower == "weighted" || lower.starts_with("weighted by") {
+            facts.weighted = Some(true);
+        } else if let Some(value) = assignment(&lower, "total n") {
+            total = number(value);
+        } else if let Some(value) = assignment(&lower, "base n").or(assignment(&lower, "n")) {
+            size = sizes(value).or(size);
+        } else if let Some(count) = lower.strip_suffix("missing").and_then(number) {
+            missing = Some(count);
+        } else if description.is_none() {
+            description = Some(part.to_string());
+        }
+    }
+    if description.is_some() {
+        facts.base_description = description;
+    }
+    if let Some(mut size) = size {
+        size.total = total;
+        size.missing = missing;
+        facts.base_n = Some(size);
+    }
+}
+
+/// The value of "`name` = value"
+fn assignment<'a>(part: &'a str, name: &str) -> Option<&'a str> {
+    let (left, right) = part.split_once('=')?;
+    (left.trim() == name).then_some(right.trim())
+}
+
+/// "713" or "from 369 to 711"
+fn sizes(value: &str) -> Option<BaseSize> {
+    let (min, max) = match value.strip_prefix("from") {
+        Some(range) => {
+            let (low, high) = range.split_once("to")?;
+            (number(low)?, number(high)?)
+        }
+        None => (number(value)?, number(value)?),
+    };
+    Some(BaseSize {
+        min,
+        max,
+        total: None,
+        missing: None,
+    })
+}
+
+fn number(text: &str) -> Option<f64> {
+    text.trim().replace(',', "").parse().ok()
+}
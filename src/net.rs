@@ -0,0 +1,415 @@
+use crate::layout::PATH_SEPARATOR;
+use crate::lookup::{Axis, Selector};
+use crate::statistic::{Basis, StandardStatistic, StatisticRegistry};
+use crate::transform::{address, reorder_group};
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use std::collections::{HashMap, HashSet};
+
+/// How [`Table::add_net`] builds the new summary
+#[derive(Debug, Clone, Default)]
+pub struct NetOptions {
+    /// `name` attribute of the new summary
+    pub name: Option<String>,
+
+    /// Sum counts even where the group looks multi-response, i.e. its elements add up to
+    /// more than the base so that respondents may be counted in several of them
+    pub allow_multi_response: bool,
+
+    /// What the table's statistic types mean
+    pub statistics: StatisticRegistry,
+}
+
+/// What a statistic measures, as far as combining rows goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Measure {
+    Count,
+    Base(Basis),
+    Percent(Basis),
+    Other,
+}
+
+impl Measure {
+    pub(crate) fn of(statistic: Option<StandardStatistic>) -> Measure {
+        let Some(statistic) = statistic.filter(|s| !s.is_effective() && !s.is_volume()) else {
+            return Measure::Other;
+        };
+        if statistic.is_count() {
+            return Measure::Count;
+        }
+        match (statistic.base_of(), statistic.percentaged_on()) {
+            (Some(basis), _) => Measure::Base(basis),
+            (_, Some(basis @ (Basis::Column | Basis::Row | Basis::Total))) => {
+                Measure::Percent(basis)
+            }
+            _ => Measure::Other,
+        }
+    }
+
+    /// What each of a table's statistics measures
+    pub(crate) fn all(table: &Table, registry: &StatisticRegistry) -> Vec<Measure> {
+        table
+            .standard_statistics(registry)
+            .into_iter()
+            .map(Measure::of)
+            .collect()
+    }
+}
+
+/// A group of the row edge with its position in the tree and its headings
+struct Candidate<'a> {
+    path: Vec<usize>,
+    headings: Vec<&'a str>,
+    group: &'a Group,
+}
+
+impl Table {
+    /// A copy with a new NET summary combining some elements of a row group
+    ///
+    /// ```no_run
+    /// # use libxtabml::{NetOptions, Selector, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let top_two: Vec<Selector> = vec!["Very satisfied".into(), "Satisfied".into()];
+    /// let options = NetOptions::default();
+    /// let table = xtab.tables[0].add_net(&["Satisfaction"], &top_two, "Top 2", options)?;
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// `group` gives the headings of the group, trailing ones sufficing as for
+    /// [`Selector::Path`]; when empty, the innermost group holding all the leaves is used.
+    /// The summary (`type="net"`) goes after the group's last row. Counts and row bases are
+    /// summed, column and total bases carried over where the leaves agree, and percentages
+    /// recomputed from a count and the matching base; all other cells become missing
+    /// (`<x/>`). Groups whose elements add up to more than their base, as counts against
+    /// the column base or else as column percentages over 100%, are taken to be
+    /// multi-response and refused unless [`NetOptions::allow_multi_response`] is set. NETs
+    /// already in the group, including elements labelled "NET" as Q exports them, are left
+    /// out of that sum.
+    pub fn add_net(
+        &self,
+        group: &[&str],
+        leaves: &[Selector],
+        label: &str,
+        options: NetOptions,
+    ) -> Result<Table> {
+        let Some(edge) = &self.row_edge else {
+            return Err(XtabMLError::Net("the table has no row edge".to_string()));
+        };
+        let all = edge.leaves();
+        let mut members = leaves
+            .iter()
+            .map(|s| self.locate(Axis::Rows, s))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        members.sort_unstable();
+        members.dedup();
+        if members.is_empty() {
+            return Err(XtabMLError::Net("no rows to combine".to_string()));
+        }
+        if let Some(&summary) = members.iter().find(|&&m| all[m].node.is_summary()) {
+            return Err(XtabMLError::Net(format!(
+                "row {:?} is a summary; only elements can be combined",
+                all[summary].text()
+            )));
+        }
+
+        let mut candidates = Vec::new();
+        collect_groups(
+            &edge.groups,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut candidates,
+        );
+        let index: HashMap<*const (), usize> = all
+            .iter()
+            .enumerate()
+            .map(|(i, leaf)| (address(leaf.node), i))
+            .collect();
+        let rows_of = |group| {
+            let mut rows = Vec::new();
+            subtree_rows(group, &index, &mut rows);
+            rows
+        };
+        let holds_all = |group| {
+            let rows: HashSet<usize> = rows_of(group).into_iter().map(|(r, _)| r).collect();
+            members.iter().all(|m| rows.contains(m))
+        };
+        let target = if group.is_empty() {
+            candidates
+                .iter()
+                .filter(|c| holds_all(c.group))
+                .max_by_key(|c| c.path.len())
+                .ok_or_else(|| XtabMLError::Net("the rows are in different groups".to_string()))?
+        } else {
+            let matching: Vec<&Candidate> = candidates
+                .iter()
+                .filter(|c| c.group.text.is_some() && c.headings.ends_with(group))
+                .collect();
+            match matching.as_slice() {
+                [] => {
+                    return Err(XtabMLError::Net(format!(
+                        "no row group {:?}",
+                        group.join(PATH_SEPARATOR)
+                    )))
+                }
+                [target] => *target,
+                _ => {
+                    return Err(XtabMLError::Net(format!(
+                        "{} row groups match {:?}",
+                        matching.len(),
+                        group.join(PATH_SEPARATOR)
+                    )))
+                }
+            }
+        };
+        let heading = target.headings.join(PATH_SEPARATOR);
+        let group_rows = rows_of(target.group);
+        let inside: HashSet<usize> = group_rows.iter().map(|&(r, _)| r).collect();
+        if let Some(&outside) = members.iter().find(|m| !inside.contains(m)) {
+            return Err(XtabMLError::Net(format!(
+                "row {:?} is not in group {:?}",
+                all[outside].text(),
+                heading
+            )));
+        }
+
+        let measures = Measure::all(self, &options.statistics);
+        if !options.allow_multi_response {
+            // NETs already in the group, Q's included, would count their members twice
+            let elements: Vec<usize> = group_rows
+                .iter()
+                .filter(|(_, node)| !node.is_summary() && !node.is_net())
+                .map(|&(r, _)| r)
+                .collect();
+            if let Some(reason) = self.overlap(&elements, &measures) {
+                return Err(XtabMLError::MultiResponse(format!(
+                    "the elements of group {:?} {}; set allow_multi_response to sum them anyway",
+                    heading, reason
+                )));
+            }
+        }
+
+        let insert = group_rows.iter().map(|&(r, _)| r + 1).max().unwrap_or(0);
+        let position = |node: EdgeNode<'_>, old: Option<usize>| {
+            index
+                .get(&address(node))
+                .map_or(old, |&row| Some(if row >= insert { row + 1 } else { row }))
+        };
+        let mut groups: Vec<Group> = edge
+            .groups
+            .iter()
+            .map(|g| reorder_group(g, &position))
+            .collect();
+        let (first, rest) = target.path.split_first().expect("groups have a path");
+        let mut group = &mut groups[*first];
+        for &i in rest {
+            group = &mut group.groups[i];
+        }
+        group.summaries.push(Summary {
+            text: label.to_string(),
+            name: options.name,
+            r#type: Some("net".to_string()),
+            alt_texts: Vec::new(),
+            position: Some(insert),
+        });
+
+        let mut table = self.clone();
+        table.row_edge = Some(Edge {
+            axis: edge.axis.clone(),
+            name: edge.name.clone(),
+            groups,
+        });
+        let row = self.net_row(&members, &measures);
+        table
+            .data
+            .rows
+            .insert(insert.min(table.data.rows.len()), row);
+        Ok(table)
+    }
+
+    /// Numeric value of a statistic's cell, `None` for rows without data
+    pub(crate) fn number(&self, row: usize, statistic: usize, column: usize) -> Option<f64> {
+        self.data
+            .rows
+            .get(row)?
+            .data_row_series
+            .get(statistic)?
+            .cell(column)?
+            .as_f64()
+    }
+
+    /// How the elements of a group show they overlap, if they do
+    ///
+    /// Counts are held against the column base, or else column percentages against 100%;
+    /// a table with neither cannot show overlap.
+    fn overlap(&self, elements: &[usize], measures: &[Measure]) -> Option<String> {
+        let find = |measure: Measure| measures.iter().position(|&m| m == measure);
+        let labels = self.column_labels();
+        let column_label = |c: usize| labels.get(c).cloned().unwrap_or_else(|| c.to_string());
+        let sum = |statistic: usize, column: usize| -> f64 {
+            elements
+                .iter()
+                .filter_map(|&r| self.number(r, statistic, column))
+                .sum()
+        };
+        for column in 0..self.column_count() {
+            if let (Some(count), Some(base)) =
+                (find(Measure::Count), find(Measure::Base(Basis::Column)))
+            {
+                let base_value = elements.iter().find_map(|&r| self.number(r, base, column));
+                let total = sum(count, column);
+                if base_value.is_some_and(|b| total > b + 1e-6) {
+                    return Some(format!(
+                        "count {} against a base of {} in column {:?}",
+                        total,
+                        base_value.unwrap_or_default(),
+                        column_label(column)
+                    ));
+                }
+            } else if let Some(percent) = find(Measure::Percent(Basis::Column)) {
+                // Allow for each percentage having been rounded
+                let total = sum(percent, column);
+                if total > 100.0 + 0.5 * elements.len() as f64 {
+                    return Some(format!(
+                        "add up to {:.1}% in column {:?}",
+                        total,
+                        column_label(column)
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// The data row of a NET over the given rows
+    fn net_row(&self, members: &[usize], measures: &[Measure]) -> DataRow {
+        let columns = self.column_count();
+        let combined = |statistic: usize, measure: Measure| -> Vec<Option<f64>> {
+            (0..columns)
+                .map(|column| {
+                    let mut values = members.iter().map(|&r| self.number(r, statistic, column));
+                    match measure {
+                        Measure::Count | Measure::Base(Basis::Row) => values.sum(),
+                        Measure::Base(_) => {
+                            let first = values.next()??;
+                            values
+                                .all(|v| v.is_some_and(|v| (v - first).abs() < 1e-9))
+                                .then_some(first)
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        };
+        let mut nets: Vec<Vec<Option<f64>>> = measures
+            .iter()
+            .enumerate()
+            .map(|(s, &m)| combined(s, m))
+            .collect();
+        for (s, &measure) in measures.iter().enumerate() {
+            let Measure::Percent(over) = measure else {
+                continue;
+            };
+            let count = measures.iter().position(|&m| m == Measure::Count);
+            let base = measures.iter().position(|&m| m == Measure::Base(over));
+            if let (Some(count), Some(base)) = (count, base) {
+                nets[s] = (0..columns)
+                    .map(|c| match (nets[count][c], nets[base][c]) {
+                        (Some(n), Some(b)) if b != 0.0 => Some(n / b * 100.0),
+                        _ => None,
+                    })
+                    .collect();
+            }
+        }
+
+        DataRow {
+            data_row_series: nets
+                .into_iter()
+                .enumerate()
+                .map(|(s, values)| {
+                    let (decimals, suffix) = self.style(members, s);
+                    DataRowSeries {
+                        statistic: Some(self.statistics[s].clone()),
+                        cells: values
+                            .into_iter()
+                            .map(|value| match value {
+                                Some(v) => DataCell {
+                                    value: Some(format!("{:.*}{}", decimals, v, suffix)),
+                                    ..DataCell::default()
+                                },
+                                None => DataCell {
+                                    is_missing: true,
+                                    ..DataCell::default()
+                                },
+                            })
+                            .collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Decimal places and suffix of a statistic's values in the given rows
+    fn style(&self, rows: &[usize], statistic: usize) -> (usize, &'static str) {
+        let mut decimals = 0;
+        let mut suffix = "";
+        let cells = rows
+            .iter()
+            .filter_map(|&r| self.data.rows.get(r)?.data_row_series.get(statistic))
+            .flat_map(|series| &series.cells)
+            .filter(|c| !c.is_missing);
+        for value in cells.filter_map(|c| c.value.as_deref()) {
+            let value = value.trim();
+            if value.ends_with('%') {
+                suffix = "%";
+            }
+            let value = value.trim_end_matches('%').trim_end();
+            if let Some((_, fraction)) = value.split_once('.') {
+                decimals = decimals.max(fraction.len());
+            }
+        }
+        (decimals, suffix)
+    }
+}
+
+fn collect_groups<'a>(
+    groups: &'a [Group],
+    path: &mut Vec<usize>,
+    headings: &mut Vec<&'a str>,
+    candidates: &mut Vec<Candidate<'a>>,
+) {
+    for (i, group) in groups.iter().enumerate() {
+        path.push(i);
+        let titled = group.text.as_deref().inspect(|&text| headings.push(text));
+        candidates.push(Candidate {
+            path: path.clone(),
+            headings: headings.clone(),
+            group,
+        });
+        collect_groups(&group.groups, path, headings, candidates);
+        if titled.is_some() {
+            headings.pop();
+        }
+        path.pop();
+    }
+}
+
+/// Data rows of the leaves of a group and its nested groups, with the leaves
+pub(crate) fn subtree_rows<'a>(
+    group: &'a Group,
+    index: &HashMap<*const (), usize>,
+    rows: &mut Vec<(usize, EdgeNode<'a>)>,
+) {
+    let nodes = group
+        .elements
+        .iter()
+        .map(EdgeNode::Element)
+        .chain(group.summaries.iter().map(EdgeNode::Summary));
+    for node in nodes {
+        if let Some(&row) = index.get(&address(node)) {
+            rows.push((row, node));
+        }
+    }
+    for child in &group.groups {
+        subtree_rows(child, index, rows);
+    }
+}
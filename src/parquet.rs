@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+
+use crate::csv::LONG_COLUMNS;
+use crate::flat::{FlatOptions, Layout};
+use crate::{types::*, Result, XtabMLError};
+
+/// Values of one Parquet column
+enum Values {
+    Int(Vec<i64>),
+    Text(Vec<Option<String>>),
+    Double(Vec<Option<f64>>),
+}
+
+struct Column {
+    name: String,
+    values: Values,
+}
+
+impl XtabML {
+    /// Export the selected tables as a Parquet file
+    ///
+    /// The long layout has the same columns as the long CSV. A wide export must select a
+    /// single table, since every table has its own columns; values are written as text.
+    pub fn to_parquet(&self, options: &FlatOptions) -> Result<Vec<u8>> {
+        let columns = match options.layout {
+            Layout::Long => long_columns(self, options),
+            Layout::Wide => wide_columns(self, options)?,
+        };
+        write(columns)
+    }
+}
+
+fn long_columns(xtab: &XtabML, options: &FlatOptions) -> Vec<Column> {
+    let records = xtab.long_records(options);
+    let int = |f: fn(&crate::LongRecord) -> usize| {
+        Values::Int(records.iter().map(|r| f(r) as i64).collect())
+    };
+    let text =
+        |f: fn(&crate::LongRecord) -> Option<String>| Values::Text(records.iter().map(f).collect());
+    let values = [
+        int(|r| r.table_index),
+        text(|r| r.table_name.clone()),
+        text(|r| Some(r.table_title.clone())),
+        int(|r| r.row_position),
+        text(|r| Some(r.row_label.clone())),
+        int(|r| r.column_position),
+        text(|r| Some(r.column_label.clone())),
+        text(|r| Some(r.statistic.clone())),
+        text(|r| r.value.clone()),
+        Values::Double(records.iter().map(|r| r.number).collect()),
+    ];
+    LONG_COLUMNS
+        .iter()
+        .zip(values)
+        .map(|(name, values)| Column {
+            name: name.to_string(),
+            values,
+        })
+        .collect()
+}
+
+fn wide_columns(xtab: &XtabML, options: &FlatOptions) -> Result<Vec<Column>> {
+    let mut tables = xtab.wide_tables(options);
+    if tables.len() != 1 {
+        return Err(XtabMLError::Unsupported(format!(
+            "a wide Parquet export needs exactly one table, got {}",
+            tables.len()
+        )));
+    }
+    let table = tables.remove(0);
+
+    let mut columns = vec![
+        Column {
+            name: "Row".to_string(),
+            values: Values::Text(
+                table
+                    .rows
+                    .iter()
+                    .map(|r| Some(r.row_label.clone()))
+                    .collect(),
+            ),
+        },
+        Column {
+            name: "Statistic".to_string(),
+            values: Values::Text(
+                table
+                    .rows
+                    .iter()
+                    .map(|r| Some(r.statistic.clone()))
+                    .collect(),
+            ),
+        },
+    ];
+    for (position, label) in table.columns.iter().enumerate() {
+        // Column names must be unique
+        let mut name = label.clone();
+        let mut suffix = 1;
+        while columns.iter().any(|c| c.name == name) {
+            suffix += 1;
+            name = format!("{} ({})", label, suffix);
+        }
+        columns.push(Column {
+            name,
+            values: Values::Text(
+                table
+                    .rows
+                    .iter()
+                    .map(|r| {
+                        r.cells
+                            .get(position)
+                            .filter(|c| !c.is_missing && !c.is_hidden)
+                            .and_then(|c| c.value.clone())
+                    })
+                    .collect(),
+            ),
+        });
+    }
+    Ok(columns)
+}
+
+fn write(columns: Vec<Column>) -> Result<Vec<u8>> {
+    let fields = columns
+        .iter()
+        .map(|column| {
+            let (physical, repetition, logical) = match column.values {
+                Values::Int(_) => (PhysicalType::INT64, Repetition::REQUIRED, None),
+                Values::Text(_) => (
+                    PhysicalType::BYTE_ARRAY,
+                    Repetition::OPTIONAL,
+                    Some(LogicalType::String),
+                ),
+                Values::Double(_) => (PhysicalType::DOUBLE, Repetition::OPTIONAL, None),
+            };
+            Ok(Arc::new(
+                Type::primitive_type_builder(&column.name, physical)
+                    .with_repetition(repetition)
+                    .with_logical_type(logical)
+                    .build()?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let schema = Arc::new(
+        Type::group_type_builder("xtabml")
+            .with_fields(fields)
+            .build()?,
+    );
+
+    let mut buffer = Vec::new();
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(&mut buffer, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+    let mut values = columns.into_iter().map(|c| c.values);
+    while let Some(mut column) = row_group.next_column()? {
+        match values.next() {
+            Some(Values::Int(v)) => {
+                column.typed::<Int64Type>().write_batch(&v, None, None)?;
+            }
+            Some(Values::Text(v)) => {
+                let levels = definition_levels(&v);
+                let present: Vec<ByteArray> = v
+                    .into_iter()
+                    .flatten()
+                    .map(|s| ByteArray::from(s.into_bytes()))
+                    .collect();
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&present, Some(&levels), None)?;
+            }
+            Some(Values::Double(v)) => {
+                let levels = definition_levels(&v);
+                let present: Vec<f64> = v.into_iter().flatten().collect();
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&present, Some(&levels), None)?;
+            }
+            None => unreachable!("one value vector per schema column"),
+        }
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// Definition levels of an optional column: 1 for a value, 0 for null
+fn definition_levels<T>(values: &[Option<T>]) -> Vec<i16> {
+    values.iter().map(|v| i16::from(v.is_some())).collect()
+}
@@ -1,7 +1,5 @@
-use std::thread::current;
-
 use crate::{types::*, Result, XtabMLError};
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
 /// Parser for XtabML documents
@@ -47,13 +45,22 @@ impl XtabMLParser {
         // Table parsing state
         let mut current_table: Option<Table> = None;
         let mut current_edge: Option<Edge> = None;
-        let mut current_group: Option<Group> = None;
+        let mut group_stack: Vec<Group> = Vec::new();
+        let mut current_summary: Option<Summary> = None;
+        let mut current_leaf_position: usize = 0;
         let mut current_data_row: Option<DataRow> = None;
         let mut current_data_row_series_index: usize = 0;
         let mut current_data_cell: Option<DataCell> = None;
         let mut current_element: Option<Element> = None;
         let mut current_element_index: i32 = 0;
         let mut current_statistic_type: Option<StatisticType> = None;
+        let mut current_language: Option<Language> = None;
+        let mut current_control_type: Option<ControlType> = None;
+
+        // Alternative texts of the current <t>, and the language and start in the text
+        // buffer of the open <a>
+        let mut alt_texts: Vec<AltText> = Vec::new();
+        let mut current_alt: Option<(String, usize)> = None;
 
         loop {
             let event = reader.read_event_into(&mut buf);
@@ -94,6 +101,7 @@ impl XtabMLParser {
                             current_table = Some(Table {
                                 name,
                                 title: String::new(),
+                                title_alt_texts: Vec::new(),
                                 controls: Vec::new(),
                                 row_edge: None,
                                 column_edge: None,
@@ -115,17 +123,35 @@ impl XtabMLParser {
                                 }
                             }
                             text_buffer.clear();
+                            let mut control_alt_texts = Vec::new();
 
                             // Read until end of control
                             let mut depth = 1;
                             loop {
                                 match reader.read_event_into(&mut buf) {
-                                    Ok(Event::Start(_)) => depth += 1,
-                                    Ok(Event::End(_)) => {
+                                    Ok(Event::Start(e)) => {
+                                        depth += 1;
+                                        if e.name().as_ref() == b"a" {
+                                            current_alt = Some((
+                                                attribute(&e, b"lang")?.unwrap_or_default(),
+                                                text_buffer.len(),
+                                            ));
+                                        }
+                                    }
+                                    Ok(Event::End(e)) => {
                                         depth -= 1;
                                         if depth == 0 {
                                             break;
                                         }
+                                        if e.name().as_ref() == b"a" {
+                                            if let Some((lang, start)) = current_alt.take() {
+                                                control_alt_texts.push(AltText {
+                                                    lang,
+                                                    text: text_buffer
+                                                        .split_off(start.min(text_buffer.len())),
+                                                });
+                                            }
+                                        }
                                     }
                                     Ok(Event::Text(e)) => match e.unescape() {
                                         Ok(text) => text_buffer.push_str(&text),
@@ -145,6 +171,7 @@ impl XtabMLParser {
                             let control = Control {
                                 r#type: control_type.clone(),
                                 text: text_buffer.clone(),
+                                alt_texts: control_alt_texts,
                             };
 
                             if let Some(ref mut table) = current_table {
@@ -171,13 +198,19 @@ impl XtabMLParser {
                             }
                             current_edge = Some(Edge {
                                 axis,
+                                name: attribute(&e, b"name")?,
                                 groups: Vec::new(),
                             });
+                            current_leaf_position = 0;
                         }
                         b"group" => {
-                            current_group = Some(Group {
+                            group_stack.push(Group {
+                                name: attribute(&e, b"name")?,
+                                text: None,
+                                alt_texts: Vec::new(),
                                 elements: Vec::new(),
                                 summaries: Vec::new(),
+                                groups: Vec::new(),
                             });
                             current_element = None;
                             current_element_index = 0;
@@ -187,10 +220,22 @@ impl XtabMLParser {
                             current_element = Some(Element {
                                 text: "".to_string(),
                                 index: None,
-                            })
+                                name: attribute(&e, b"name")?,
+                                alt_texts: Vec::new(),
+                                position: Some(current_leaf_position),
+                            });
+                            current_leaf_position += 1;
                         }
                         b"summary" => {
                             text_buffer.clear();
+                            current_summary = Some(Summary {
+                                text: String::new(),
+                                name: attribute(&e, b"name")?,
+                                r#type: attribute(&e, b"type")?,
+                                alt_texts: Vec::new(),
+                                position: Some(current_leaf_position),
+                            });
+                            current_leaf_position += 1;
                         }
                         // b"statistic" => {
                         //     if let Some(ref mut table) = current_table {
@@ -236,6 +281,20 @@ impl XtabMLParser {
                             //     println!("{:?}", attr);
                             // }
                         }
+                        b"h" => {
+                            // start a cell that is not intended for printing
+                            current_data_cell = Some(DataCell {
+                                is_hidden: true,
+                                ..DataCell::default()
+                            });
+                        }
+                        b"n" => {
+                            // start a cell that is not applicable but still takes its place
+                            current_data_cell = Some(DataCell {
+                                is_missing: true,
+                                ..DataCell::default()
+                            });
+                        }
                         b"x" => {
                             // Empty element indicating missing value
                             if let Some(ref mut cell) = current_data_cell {
@@ -244,23 +303,40 @@ impl XtabMLParser {
                             }
                         }
                         b"statistictype" => {
-                            for attr in e.attributes() {
-                                let attr = attr.unwrap();
-                                if attr.key.as_ref() == b"name" {
-                                    xtabml.version = String::from_utf8(attr.value.to_vec())
-                                        .map_err(|_| {
-                                            XtabMLError::InvalidStructure(
-                                                "Invalid UTF-8 in version".to_string(),
-                                            )
-                                        })?;
-                                }
-                            }
-
                             current_statistic_type = Some(StatisticType {
-                                name: "".to_string(),
+                                name: attribute(&e, b"name")?.unwrap_or_default(),
                                 text: "".to_string(),
+                                alt_texts: Vec::new(),
                             });
                         }
+                        b"date" | b"time" | b"user" | b"origin" => {
+                            text_buffer.clear();
+                        }
+                        b"controltype" => {
+                            current_control_type = Some(ControlType {
+                                name: attribute(&e, b"name")?.unwrap_or_default(),
+                                status: attribute(&e, b"status")?,
+                                text: String::new(),
+                                alt_texts: Vec::new(),
+                            });
+                        }
+                        b"language" => {
+                            text_buffer.clear();
+                            current_language = Some(Language {
+                                lang: attribute(&e, b"lang")?.unwrap_or_default(),
+                                base: attribute(&e, b"base")?,
+                                description: String::new(),
+                            });
+                        }
+                        b"t" => {
+                            alt_texts.clear();
+                        }
+                        b"a" => {
+                            current_alt = Some((
+                                attribute(&e, b"lang")?.unwrap_or_default(),
+                                text_buffer.len(),
+                            ));
+                        }
                         _ => {
                             //println!("UNMATCHED EVENT IN START: {:?}", name);
                         }
@@ -276,15 +352,24 @@ impl XtabMLParser {
                             // Text element - use the buffer
                             let text = text_buffer.clone();
                             text_buffer.clear();
+                            let alt = std::mem::take(&mut alt_texts);
                             //println!("INSIDE TEXT WITH VALUE: {}", text);
                             // Determine where to put the text based on context
-                            if let Some(ref mut table) = current_table {
+                            if let Some(ref mut control_type) = current_control_type {
+                                control_type.text = text;
+                                control_type.alt_texts = alt;
+                            } else if let Some(ref mut stattype) = current_statistic_type {
+                                stattype.text = text;
+                                stattype.alt_texts = alt;
+                            } else if let Some(ref mut table) = current_table {
                                 if table.title.is_empty() && path_stack.iter().any(|p| p == "table")
                                 {
                                     table.title = text;
+                                    table.title_alt_texts = alt;
                                 } else if current_element.is_some() {
                                     if let Some(ref mut element) = current_element {
                                         element.text = text;
+                                        element.alt_texts = alt;
                                         element.index = Some(current_element_index);
                                         current_element_index += 1;
                                     }
@@ -293,37 +378,69 @@ impl XtabMLParser {
                                     //    current_group, current_
                                     //    element
                                     //);
-                                } else if current_statistic_type.is_some() {
-                                    if let Some(ref mut stattype) = current_statistic_type {
-                                        stattype.text = text;
+                                } else if let Some(ref mut summary) = current_summary {
+                                    summary.text = text;
+                                    summary.alt_texts = alt;
+                                } else if path_stack.last().map(String::as_str) == Some("group") {
+                                    if let Some(group) = group_stack.last_mut() {
+                                        group.text = Some(text);
+                                        group.alt_texts = alt;
                                     }
                                 }
                             }
                         }
+                        b"a" => {
+                            if let Some((lang, start)) = current_alt.take() {
+                                let text = text_buffer.split_off(start.min(text_buffer.len()));
+                                alt_texts.push(AltText { lang, text });
+                            }
+                        }
+                        b"date" | b"time" | b"user" | b"origin"
+                            if path_stack.last().map(String::as_str) == Some("xtab") =>
+                        {
+                            let value = Some(std::mem::take(&mut text_buffer));
+                            match name.as_ref() {
+                                b"date" => xtabml.date = value,
+                                b"time" => xtabml.time = value,
+                                b"user" => xtabml.user = value,
+                                _ => xtabml.origin = value,
+                            }
+                        }
+                        b"controltype" => {
+                            if let Some(control_type) = current_control_type.take() {
+                                xtabml.control_types.push(control_type);
+                            }
+                        }
+                        b"language" => {
+                            if let Some(mut language) = current_language.take() {
+                                language.description = text_buffer.clone();
+                                xtabml.languages.push(language);
+                            }
+                            text_buffer.clear();
+                        }
                         b"element" => {
                             //if !text_buffer.is_empty() {
-                            if let Some(ref mut group) = current_group {
-                                if let Some(ref mut element) = current_element {
-                                    group.elements.push(element.clone());
-                                    current_element = None;
+                            if let Some(group) = group_stack.last_mut() {
+                                if let Some(element) = current_element.take() {
+                                    group.elements.push(element);
                                 }
                             }
                             //text_buffer.clear();
                             //}
                         }
                         b"summary" => {
-                            if !text_buffer.is_empty() {
-                                if let Some(ref mut group) = current_group {
-                                    group.summaries.push(Summary {
-                                        text: text_buffer.clone(),
-                                    });
+                            if let Some(summary) = current_summary.take() {
+                                if let Some(group) = group_stack.last_mut() {
+                                    group.summaries.push(summary);
                                 }
-                                text_buffer.clear();
                             }
+                            text_buffer.clear();
                         }
                         b"group" => {
-                            if let Some(group) = current_group.take() {
-                                if let Some(ref mut edge) = current_edge {
+                            if let Some(group) = group_stack.pop() {
+                                if let Some(parent) = group_stack.last_mut() {
+                                    parent.groups.push(group);
+                                } else if let Some(ref mut edge) = current_edge {
                                     edge.groups.push(group);
                                 }
                             }
@@ -344,13 +461,14 @@ impl XtabMLParser {
                         b"c" => {
                             current_data_row_series_index += 1;
                         }
-                        b"v" => {
+                        b"v" | b"h" | b"n" => {
                             // Value element
                             //println!("{:?}", text_buffer);
                             if let Some(ref mut cell) = current_data_cell.take() {
                                 if !text_buffer.is_empty() {
                                     cell.value = Some(text_buffer.clone());
-                                    cell.is_missing = false;
+                                    // A not-applicable cell stays missing whatever it holds
+                                    cell.is_missing = name.as_ref() == b"n";
                                 }
 
                                 if let Some(ref mut row) = current_data_row {
@@ -363,6 +481,11 @@ impl XtabMLParser {
                             }
                             text_buffer.clear();
                         }
+                        b"statistictype" => {
+                            if let Some(stattype) = current_statistic_type.take() {
+                                xtabml.statistic_types.push(stattype);
+                            }
+                        }
                         b"r" => {
                             if let Some(row) = current_data_row.take() {
                                 if let Some(ref mut table) = current_table {
@@ -415,14 +538,30 @@ impl XtabMLParser {
                                             })?;
                                     }
                                 }
-                                table.statistics.push(Statistic { r#type: stat_type });
+                                table.statistics.push(Statistic {
+                                    r#type: stat_type,
+                                    datatype: attribute(&e, b"datatype")?,
+                                });
                             }
                         }
-                        b"x" => {
-                            // Empty element indicating missing value
+                        b"summary" => {
+                            // Summary without a text
+                            if let Some(group) = group_stack.last_mut() {
+                                group.summaries.push(Summary {
+                                    text: String::new(),
+                                    name: attribute(&e, b"name")?,
+                                    r#type: attribute(&e, b"type")?,
+                                    alt_texts: Vec::new(),
+                                    position: Some(current_leaf_position),
+                                });
+                                current_leaf_position += 1;
+                            }
+                        }
+                        b"x" | b"n" => {
+                            // Empty element indicating a missing or not-applicable value
                             let missing_cell = DataCell {
                                 is_missing: true,
-                                value: None,
+                                ..DataCell::default()
                             };
                             if let Some(ref mut row) = current_data_row {
                                 if current_data_row_series_index < row.data_row_series.len() {
@@ -451,6 +590,18 @@ impl XtabMLParser {
     }
 }
 
+/// Read an optional attribute of an element as a string
+fn attribute(e: &BytesStart, key: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| XtabMLError::XmlParse(e.into()))?;
+        if attr.key.as_ref() == key {
+            let value = attr.unescape_value()?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
 /// Parse an XtabML file from a path
 #[allow(dead_code)]
 pub fn parse_file(path: &str) -> Result<XtabML> {
@@ -0,0 +1,501 @@
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use std::str::FromStr;
+
+/// A parsed query that selects tables and slices their rows, columns and statistics
+///
+/// The syntax is an optional `tables where <condition>` followed by `|`-separated slices:
+///
+/// ```text
+/// tables where control.base contains "Total sample" and statistic = ColumnPercent
+///   | rows "NET" | cols group "Gender"
+/// ```
+///
+/// Conditions compare a field with `=`, `!=`, `contains` (case-insensitive) or `~` (a
+/// regular expression, with the `regex` feature), and combine with `and`, `or`, `not` and
+/// parentheses. Fields are `title`, `name`, `index` (1-based), `statistic`,
+/// `control.<type>`, and `rows` or `cols`, which match any label or group heading of the
+/// edge. Multi-valued fields match when any value does, and `!=` when none equals.
+///
+/// Slices narrow each selected table: `rows [group] [op] "text"` and `cols ...` keep the
+/// rows or columns whose label (or, with `group`, an enclosing group heading) matches,
+/// `=` being the default operator; `stats A, B` keeps those statistics in that order.
+/// Tables left without rows, columns or statistics are dropped.
+///
+/// The default query selects every table whole.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    condition: Option<Condition>,
+    slices: Vec<Slice>,
+}
+
+/// The part of a table selected by a [`Query`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSlice {
+    pub table_index: usize,
+
+    /// Data row positions, in document order
+    pub rows: Vec<usize>,
+
+    /// Data column positions, in document order
+    pub columns: Vec<usize>,
+
+    /// Indices into `Table::statistics`
+    pub statistics: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    Test(Field, Matcher),
+}
+
+#[derive(Debug, Clone)]
+enum Field {
+    Title,
+    Name,
+    Index,
+    Statistic,
+    Control(String),
+    Rows,
+    Columns,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Equals(String),
+    Contains(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Matcher::Equals(value) => text == value,
+            Matcher::Contains(value) => text.to_lowercase().contains(&value.to_lowercase()),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Slice {
+    Leaves {
+        rows: bool,
+        group: bool,
+        matcher: Matcher,
+    },
+    Statistics(Vec<String>),
+}
+
+impl Query {
+    /// Parse a query, see [`Query`] for the syntax
+    pub fn parse(query: &str) -> Result<Query> {
+        let mut parser = QueryParser {
+            tokens: tokenize(query)?,
+            next: 0,
+        };
+        parser.query()
+    }
+
+    /// Whether the condition selects a table; slices are not considered
+    pub fn matches(&self, index: usize, table: &Table) -> bool {
+        self.condition
+            .as_ref()
+            .is_none_or(|condition| condition.test(index, table))
+    }
+
+    /// Apply the slices to a table, `None` if nothing is left
+    fn slice(&self, index: usize, table: &Table) -> Option<TableSlice> {
+        let mut slice = TableSlice {
+            table_index: index,
+            rows: (0..table.data.rows.len()).collect(),
+            columns: (0..table.column_count()).collect(),
+            statistics: (0..table.statistics.len()).collect(),
+        };
+        for step in &self.slices {
+            match step {
+                Slice::Leaves {
+                    rows,
+                    group,
+                    matcher,
+                } => {
+                    let (edge, positions) = if *rows {
+                        (&table.row_edge, &mut slice.rows)
+                    } else {
+                        (&table.column_edge, &mut slice.columns)
+                    };
+                    let leaves = edge.as_ref().map(Edge::leaves).unwrap_or_default();
+                    positions.retain(|&i| {
+                        leaves.get(i).is_some_and(|leaf| {
+                            if *group {
+                                leaf.headings().iter().any(|h| matcher.matches(h))
+                            } else {
+                                matcher.matches(leaf.text())
+                            }
+                        })
+                    });
+                }
+                Slice::Statistics(names) => {
+                    slice.statistics = names
+                        .iter()
+                        .filter_map(|name| table.statistics.iter().position(|s| &s.r#type == name))
+                        .filter(|i| slice.statistics.contains(i))
+                        .collect();
+                }
+            }
+        }
+        let empty =
+            slice.rows.is_empty() || slice.columns.is_empty() || slice.statistics.is_empty();
+        (!empty).then_some(slice)
+    }
+}
+
+impl FromStr for Query {
+    type Err = XtabMLError;
+
+    fn from_str(query: &str) -> Result<Query> {
+        Query::parse(query)
+    }
+}
+
+impl Condition {
+    fn test(&self, index: usize, table: &Table) -> bool {
+        match self {
+            Condition::And(a, b) => a.test(index, table) && b.test(index, table),
+            Condition::Or(a, b) => a.test(index, table) || b.test(index, table),
+            Condition::Not(a) => !a.test(index, table),
+            Condition::Test(field, matcher) => match field {
+                Field::Title => matcher.matches(&table.title),
+                Field::Name => table.name.as_deref().is_some_and(|n| matcher.matches(n)),
+                Field::Index => matcher.matches(&(index + 1).to_string()),
+                Field::Statistic => table.statistics.iter().any(|s| matcher.matches(&s.r#type)),
+                Field::Control(kind) => table
+                    .controls
+                    .iter()
+                    .any(|c| &c.r#type == kind && matcher.matches(&c.text)),
+                Field::Rows => edge_matches(table.row_edge.as_ref(), matcher),
+                Field::Columns => edge_matches(table.column_edge.as_ref(), matcher),
+            },
+        }
+    }
+}
+
+/// Whether any label or group heading of an edge matches
+fn edge_matches(edge: Option<&Edge>, matcher: &Matcher) -> bool {
+    fn group_matches(group: &Group, matcher: &Matcher) -> bool {
+        group.text.as_deref().is_some_and(|t| matcher.matches(t))
+            || group.elements.iter().any(|e| matcher.matches(&e.text))
+            || group.summaries.iter().any(|s| matcher.matches(&s.text))
+            || group.groups.iter().any(|g| group_matches(g, matcher))
+    }
+    edge.is_some_and(|edge| edge.groups.iter().any(|g| group_matches(g, matcher)))
+}
+
+impl XtabML {
+    /// Tables selected by a query, with the rows, columns and statistics of each
+    pub fn query(&self, query: &Query) -> Vec<TableSlice> {
+        self.tables
+            .iter()
+            .enumerate()
+            .filter(|(index, table)| query.matches(*index, table))
+            .filter_map(|(index, table)| query.slice(index, table))
+            .collect()
+    }
+
+    /// A copy of the document with only the tables selected by a query, sliced
+    pub fn filter(&self, query: &Query) -> XtabML {
+        let tables = self
+            .query(query)
+            .iter()
+            .map(|slice| {
+                self.tables[slice.table_index].subset(
+                    &slice.rows,
+                    &slice.columns,
+                    &slice.statistics,
+                )
+            })
+            .collect();
+        XtabML {
+            tables,
+            ..self.clone_header()
+        }
+    }
+
+    /// A copy of the document without its tables
+    fn clone_header(&self) -> XtabML {
+        XtabML {
+            version: self.version.clone(),
+            date: self.date.clone(),
+            time: self.time.clone(),
+            origin: self.origin.clone(),
+            user: self.user.clone(),
+            languages: self.languages.clone(),
+            control_types: self.control_types.clone(),
+            statistic_types: self.statistic_types.clone(),
+            controls: self.controls.clone(),
+            tables: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Symbol(&'static str),
+}
+
+/// Split a query into words, quoted strings and symbols, each with its byte offset
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => text.push(escaped),
+                        None => break,
+                    },
+                    Some((_, c)) => text.push(c),
+                    None => {
+                        return Err(XtabMLError::Query(format!(
+                            "unterminated string at offset {}",
+                            offset
+                        )))
+                    }
+                }
+            }
+            tokens.push((offset, Token::Text(text)));
+        } else if c.is_alphanumeric() || c == '_' {
+            let mut word = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                    word.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((offset, Token::Word(word)));
+        } else {
+            chars.next();
+            let symbol = match c {
+                '|' => "|",
+                '(' => "(",
+                ')' => ")",
+                ',' => ",",
+                '=' => "=",
+                '~' => "~",
+                '!' if chars.next_if(|&(_, c)| c == '=').is_some() => "!=",
+                _ => {
+                    return Err(XtabMLError::Query(format!(
+                        "unexpected {:?} at offset {}",
+                        c, offset
+                    )))
+                }
+            };
+            tokens.push((offset, Token::Symbol(symbol)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct QueryParser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    /// Whether the next token is the given keyword or symbol, consuming it if so
+    fn accept(&mut self, expected: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Word(word)) => word.eq_ignore_ascii_case(expected),
+            Some(Token::Symbol(symbol)) => *symbol == expected,
+            _ => false,
+        };
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn error(&self, expected: &str) -> XtabMLError {
+        match self.tokens.get(self.next) {
+            Some((offset, token)) => {
+                let found = match token {
+                    Token::Word(word) => word.clone(),
+                    Token::Text(text) => format!("{:?}", text),
+                    Token::Symbol(symbol) => symbol.to_string(),
+                };
+                XtabMLError::Query(format!(
+                    "expected {} at offset {}, found {}",
+                    expected, offset, found
+                ))
+            }
+            None => XtabMLError::Query(format!("expected {} at the end", expected)),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query> {
+        self.accept("tables");
+        let condition =
+            if self.accept("where") || !matches!(self.peek(), None | Some(Token::Symbol("|"))) {
+                Some(self.or()?)
+            } else {
+                None
+            };
+        let mut slices = Vec::new();
+        while self.accept("|") {
+            slices.push(self.slice()?);
+        }
+        if self.peek().is_some() {
+            return Err(self.error("`and`, `or` or `|`"));
+        }
+        Ok(Query { condition, slices })
+    }
+
+    fn or(&mut self) -> Result<Condition> {
+        let mut condition = self.and()?;
+        while self.accept("or") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition> {
+        let mut condition = self.unary()?;
+        while self.accept("and") {
+            condition = Condition::And(Box::new(condition), Box::new(self.unary()?));
+        }
+        Ok(condition)
+    }
+
+    fn unary(&mut self) -> Result<Condition> {
+        if self.accept("not") {
+            return Ok(Condition::Not(Box::new(self.unary()?)));
+        }
+        if self.accept("(") {
+            let condition = self.or()?;
+            if !self.accept(")") {
+                return Err(self.error("`)`"));
+            }
+            return Ok(condition);
+        }
+
+        let field = match self.peek() {
+            Some(Token::Word(word)) => {
+                let lower = word.to_ascii_lowercase();
+                match lower.as_str() {
+                    "title" => Field::Title,
+                    "name" => Field::Name,
+                    "index" => Field::Index,
+                    "statistic" | "stat" => Field::Statistic,
+                    "rows" => Field::Rows,
+                    "cols" | "columns" => Field::Columns,
+                    _ => match lower.strip_prefix("control.") {
+                        Some(_) => Field::Control(word["control.".len()..].to_string()),
+                        None => return Err(self.error("a field")),
+                    },
+                }
+            }
+            _ => return Err(self.error("a field")),
+        };
+        self.next += 1;
+
+        let negated = self.accept("!=");
+        let matcher = if negated {
+            Matcher::Equals(self.value()?)
+        } else {
+            self.matcher(false)?
+        };
+        let test = Condition::Test(field, matcher);
+        Ok(if negated {
+            Condition::Not(Box::new(test))
+        } else {
+            test
+        })
+    }
+
+    /// An operator and a value, the operator being optional in slices
+    fn matcher(&mut self, optional: bool) -> Result<Matcher> {
+        if self.accept("=") {
+            Ok(Matcher::Equals(self.value()?))
+        } else if self.accept("contains") {
+            Ok(Matcher::Contains(self.value()?))
+        } else if self.accept("~") {
+            let (offset, pattern) = (self.offset(), self.value()?);
+            regex_matcher(&pattern, offset)
+        } else if optional {
+            Ok(Matcher::Equals(self.value()?))
+        } else {
+            Err(self.error("`=`, `!=`, `contains` or `~`"))
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.next).map_or(0, |(offset, _)| *offset)
+    }
+
+    fn value(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Text(text) | Token::Word(text)) => {
+                let value = text.clone();
+                self.next += 1;
+                Ok(value)
+            }
+            _ => Err(self.error("a value")),
+        }
+    }
+
+    fn slice(&mut self) -> Result<Slice> {
+        let rows = if self.accept("rows") {
+            true
+        } else if self.accept("cols") || self.accept("columns") {
+            false
+        } else if self.accept("stats") || self.accept("statistics") {
+            let mut names = vec![self.value()?];
+            while self.accept(",") {
+                names.push(self.value()?);
+            }
+            return Ok(Slice::Statistics(names));
+        } else {
+            return Err(self.error("`rows`, `cols` or `stats`"));
+        };
+        let group = self.accept("group");
+        Ok(Slice::Leaves {
+            rows,
+            group,
+            matcher: self.matcher(true)?,
+        })
+    }
+}
+
+#[cfg(feature = "regex")]
+fn regex_matcher(pattern: &str, offset: usize) -> Result<Matcher> {
+    regex::Regex::new(pattern)
+        .map(Matcher::Regex)
+        .map_err(|e| XtabMLError::Query(format!("invalid regex at offset {}: {}", offset, e)))
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_matcher(_pattern: &str, offset: usize) -> Result<Matcher> {
+    Err(XtabMLError::Query(format!(
+        "`~` at offset {} needs the `regex` feature",
+        offset
+    )))
+}
@@ -0,0 +1,193 @@
+use crate::types::*;
+use serde::Serialize;
+
+/// How to match texts in [`XtabML::search`]
+#[derive(Debug, Clone)]
+pub enum SearchPattern {
+    /// Case-insensitive substring
+    Substring(String),
+
+    /// Regular expression, case-sensitive unless the pattern says otherwise with `(?i)`
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+
+    /// Case-insensitive subsequence, e.g. "nps" matches "Net Promoter Score"; hits are
+    /// scored by how closely the characters follow each other and start words
+    Fuzzy(String),
+}
+
+impl SearchPattern {
+    /// Score of a match, `None` when the text does not match
+    ///
+    /// Substring and regex matches all score 0.
+    pub fn score(&self, text: &str) -> Option<i64> {
+        match self {
+            SearchPattern::Substring(pattern) => text
+                .to_lowercase()
+                .contains(&pattern.to_lowercase())
+                .then_some(0),
+            #[cfg(feature = "regex")]
+            SearchPattern::Regex(regex) => regex.is_match(text).then_some(0),
+            SearchPattern::Fuzzy(pattern) => fuzzy_score(pattern, text),
+        }
+    }
+}
+
+/// What kind of text a search hit matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    /// Table title
+    Title,
+
+    /// Report or table control
+    Control,
+
+    /// Heading of a group in an edge
+    Group,
+
+    /// Element of an edge
+    Element,
+
+    /// Summary of an edge
+    Summary,
+}
+
+/// A text matched by [`XtabML::search`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// Index of the table, `None` for report-level controls
+    pub table_index: Option<usize>,
+    pub kind: NodeKind,
+
+    /// Axis of the edge for groups, elements and summaries: "r" or "c"
+    pub axis: Option<String>,
+
+    /// XPath of the matched `<t>` or `<control>`, e.g.
+    /// `/xtab/table[3]/edge[@axis='r']/group[1]/element[2]/t`
+    pub path: String,
+    pub text: String,
+
+    /// Match quality; only fuzzy matches have scores other than 0, higher is better
+    pub score: i64,
+}
+
+impl XtabML {
+    /// Find titles, controls, group headings, elements and summaries matching a pattern
+    ///
+    /// Hits are in document order.
+    pub fn search(&self, pattern: &SearchPattern) -> Vec<SearchHit> {
+        let mut search = Search {
+            pattern,
+            hits: Vec::new(),
+        };
+        for (i, control) in self.controls.iter().enumerate() {
+            let path = format!("/xtab/control[{}]", i + 1);
+            search.check(None, NodeKind::Control, None, path, &control.text);
+        }
+        for (index, table) in self.tables.iter().enumerate() {
+            let table_path = format!("/xtab/table[{}]", index + 1);
+            search.check(
+                Some(index),
+                NodeKind::Title,
+                None,
+                format!("{}/t", table_path),
+                &table.title,
+            );
+            for (i, control) in table.controls.iter().enumerate() {
+                let path = format!("{}/control[{}]", table_path, i + 1);
+                search.check(Some(index), NodeKind::Control, None, path, &control.text);
+            }
+            for edge in [&table.row_edge, &table.column_edge].into_iter().flatten() {
+                let edge_path = format!("{}/edge[@axis='{}']", table_path, edge.axis);
+                for (i, group) in edge.groups.iter().enumerate() {
+                    let path = format!("{}/group[{}]", edge_path, i + 1);
+                    search.group(index, &edge.axis, &path, group);
+                }
+            }
+        }
+        search.hits
+    }
+}
+
+struct Search<'a> {
+    pattern: &'a SearchPattern,
+    hits: Vec<SearchHit>,
+}
+
+impl Search<'_> {
+    fn check(
+        &mut self,
+        table_index: Option<usize>,
+        kind: NodeKind,
+        axis: Option<&str>,
+        path: String,
+        text: &str,
+    ) {
+        if let Some(score) = self.pattern.score(text) {
+            self.hits.push(SearchHit {
+                table_index,
+                kind,
+                axis: axis.map(str::to_string),
+                path,
+                text: text.to_string(),
+                score,
+            });
+        }
+    }
+
+    fn group(&mut self, table: usize, axis: &str, path: &str, group: &Group) {
+        if let Some(text) = &group.text {
+            let t = format!("{}/t", path);
+            self.check(Some(table), NodeKind::Group, Some(axis), t, text);
+        }
+        for (i, element) in group.elements.iter().enumerate() {
+            let t = format!("{}/element[{}]/t", path, i + 1);
+            self.check(Some(table), NodeKind::Element, Some(axis), t, &element.text);
+        }
+        for (i, summary) in group.summaries.iter().enumerate() {
+            let t = format!("{}/summary[{}]/t", path, i + 1);
+            self.check(Some(table), NodeKind::Summary, Some(axis), t, &summary.text);
+        }
+        for (i, nested) in group.groups.iter().enumerate() {
+            self.group(table, axis, &format!("{}/group[{}]", path, i + 1), nested);
+        }
+    }
+}
+
+/// Score `pattern` as a case-insensitive subsequence of `text`
+///
+/// Each matched character scores 1, with a bonus when it follows the previous match or
+/// starts a word, and a penalty for the characters skipped since the previous match.
+/// Characters are matched greedily, so the score is not always the best possible.
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let mut wanted = pattern.iter().peekable();
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut before = ' ';
+    for (i, c) in text.chars().flat_map(char::to_lowercase).enumerate() {
+        let Some(&&next) = wanted.peek() else {
+            break;
+        };
+        if c == next {
+            score += 1;
+            match previous {
+                Some(p) if p + 1 == i => score += 5,
+                Some(p) => score -= (i - p - 1).min(5) as i64,
+                None => {}
+            }
+            if !before.is_alphanumeric() {
+                score += 3;
+            }
+            previous = Some(i);
+            wanted.next();
+        }
+        before = c;
+    }
+    wanted.peek().is_none().then_some(score)
+}
@@ -0,0 +1,644 @@
+use crate::distribution::{chi_square_upper, normal_two_sided, student_t_two_sided};
+use crate::net::Measure;
+use crate::statistic::{Basis, StandardStatistic, StatisticRegistry};
+use crate::transform::address;
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use std::collections::HashMap;
+
+/// Effective sample sizes of means, which account for weighting, in order of preference
+///
+/// A cell's own effective n is the sample of its mean, but not the base of its
+/// percentage; proportions only take an effective column base.
+pub(crate) const EFFECTIVE_BASES: [StandardStatistic; 2] = [
+    StandardStatistic::EffectiveColumnBase,
+    StandardStatistic::EffectiveCount,
+];
+
+/// Sample sizes of means, in order of preference
+pub(crate) const MEANS_BASES: [StandardStatistic; 3] = [
+    StandardStatistic::UnweightedCount,
+    StandardStatistic::ColumnBase,
+    StandardStatistic::Count,
+];
+
+/// Adjustment of p-values for the number of comparisons in a family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Correction {
+    #[default]
+    None,
+    Bonferroni,
+    /// Holm's step-down Bonferroni
+    Holm,
+    /// Benjamini-Hochberg false discovery rate
+    FalseDiscoveryRate,
+}
+
+impl Correction {
+    /// Adjusted p-values, in the given order
+    fn adjust(&self, p: &[f64]) -> Vec<f64> {
+        let m = p.len() as f64;
+        let mut order: Vec<usize> = (0..p.len()).collect();
+        order.sort_by(|&a, &b| p[a].total_cmp(&p[b]));
+        let mut adjusted = p.to_vec();
+        match self {
+            Correction::None => {}
+            Correction::Bonferroni => adjusted.iter_mut().for_each(|q| *q = (*q * m).min(1.0)),
+            Correction::Holm => {
+                let mut running: f64 = 0.0;
+                for (rank, &i) in order.iter().enumerate() {
+                    running = running.max((p[i] * (m - rank as f64)).min(1.0));
+                    adjusted[i] = running;
+                }
+            }
+            Correction::FalseDiscoveryRate => {
+                let mut running: f64 = 1.0;
+                for (rank, &i) in order.iter().enumerate().rev() {
+                    running = running.min(p[i] * m / (rank + 1) as f64);
+                    adjusted[i] = running;
+                }
+            }
+        }
+        adjusted
+    }
+}
+
+/// Variance assumption of the t-tests between column means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TTest {
+    /// Welch's test, not assuming equal variances
+    #[default]
+    Welch,
+    /// Student's test with a pooled variance
+    Pooled,
+}
+
+/// Where the spread of a column's mean comes from
+#[derive(Clone, Copy)]
+enum Spread {
+    Deviation(usize),
+    Error(usize),
+}
+
+/// Outcome of a test between two columns of a row
+struct Comparison {
+    higher: usize,
+    lower: usize,
+    p: f64,
+    adjusted: f64,
+}
+
+/// Text about a comparison for a cell, with the other column to sort entries by
+type Entry = (usize, String);
+
+/// How significance tests between columns are run
+#[derive(Debug, Clone)]
+pub struct SignificanceOptions {
+    /// Confidence level of the two-sided tests, e.g. 0.95
+    pub confidence: f64,
+
+    /// Adjustment for the comparisons made among the columns of a group in one row
+    pub correction: Correction,
+
+    /// Use an effective base statistic where the table has one: EffectiveColumnN or
+    /// EffectiveBaseN, or for means also EffectiveN
+    pub effective_base: bool,
+
+    /// Divisor of the actual bases, used when the table has no effective base statistic
+    /// or `effective_base` is off; 1.0 leaves the bases as they are
+    pub design_effect: f64,
+
+    /// Variance assumption of [`Table::compare_column_means`]
+    pub t_test: TTest,
+
+    /// What the table's statistic types mean
+    pub statistics: StatisticRegistry,
+}
+
+impl Default for SignificanceOptions {
+    fn default() -> SignificanceOptions {
+        SignificanceOptions {
+            confidence: 0.95,
+            correction: Correction::None,
+            effective_base: true,
+            design_effect: 1.0,
+            t_test: TTest::Welch,
+            statistics: StatisticRegistry::standard(),
+        }
+    }
+}
+
+/// Chi-square test of independence between the row and column elements of a table
+#[derive(Debug, Clone)]
+pub struct ChiSquare {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+
+    /// Data rows tested: the row elements with a non-zero total
+    pub rows: Vec<usize>,
+
+    /// Data columns tested: the column elements with a non-zero total
+    pub columns: Vec<usize>,
+
+    /// Expected counts under independence, by tested row and column
+    pub expected: Vec<Vec<f64>>,
+
+    /// Standardized residuals, (observed - expected) / √expected, by tested row and column
+    pub residuals: Vec<Vec<f64>>,
+}
+
+impl ChiSquare {
+    /// A copy of the tested table with ExpectedN and Residual statistics
+    ///
+    /// Cells outside the tested rows and columns, such as totals and NETs, are missing.
+    /// Statistics of those types the table already has are replaced.
+    pub fn annotate(&self, table: &Table) -> Table {
+        let cells = |values: &[Vec<f64>]| -> Vec<Vec<DataCell>> {
+            (0..table.data.rows.len())
+                .map(|r| {
+                    let row = self.rows.iter().position(|&t| t == r);
+                    (0..table.column_count())
+                        .map(|c| {
+                            let column = self.columns.iter().position(|&t| t == c);
+                            let value = row.zip(column).map(|(i, j)| values[i][j]);
+                            string_cell(value.map(|v| format!("{:.2}", v)))
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let mut annotated = table.clone();
+        annotated.set_statistic("ExpectedN", "decimal", cells(&self.expected));
+        annotated.set_statistic("Residual", "decimal", cells(&self.residuals));
+        annotated
+    }
+}
+
+impl Table {
+    /// Chi-square test of independence of the table's counts
+    ///
+    /// ```no_run
+    /// # use libxtabml::{StatisticRegistry, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let table = &xtab.tables[0];
+    /// let test = table.chi_square(&StatisticRegistry::standard())?;
+    /// println!(
+    ///     "χ² = {:.2}, df = {}, p = {:.4}",
+    ///     test.statistic, test.degrees_of_freedom, test.p_value
+    /// );
+    /// let annotated = test.annotate(table);
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Only elements take part, so totals and NETs are left out, Q's elements labelled
+    /// "NET" included, and counts come from the first count statistic (Count, Frequency
+    /// or n) as `statistics` reads the table's statistic types. Rows and columns whose
+    /// counts add up to zero are dropped, since they carry no information about
+    /// association.
+    pub fn chi_square(&self, statistics: &StatisticRegistry) -> Result<ChiSquare> {
+        let count = Measure::all(self, statistics)
+            .iter()
+            .position(|&m| m == Measure::Count)
+            .ok_or_else(|| {
+                XtabMLError::Significance(
+                    "the table has no count statistic (Count, Frequency or n)".to_string(),
+                )
+            })?;
+        let elements = |edge: Option<&Edge>, count: usize| -> Vec<usize> {
+            match edge {
+                Some(edge) => edge
+                    .leaves()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, leaf)| {
+                        *i < count && !leaf.node.is_summary() && !leaf.node.is_net()
+                    })
+                    .map(|(i, _)| i)
+                    .collect(),
+                None => (0..count).collect(),
+            }
+        };
+        let rows = elements(self.row_edge.as_ref(), self.data.rows.len());
+        let columns = elements(self.column_edge.as_ref(), self.column_count());
+
+        let mut observed = Vec::with_capacity(rows.len());
+        for &r in &rows {
+            let values = columns
+                .iter()
+                .map(|&c| {
+                    self.number(r, count, c).ok_or_else(|| {
+                        XtabMLError::Significance(format!(
+                            "no count in data row {}, column {}",
+                            r, c
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<f64>>>()?;
+            observed.push(values);
+        }
+        let row_totals: Vec<f64> = observed.iter().map(|row| row.iter().sum()).collect();
+        let kept_rows: Vec<usize> = (0..rows.len()).filter(|&i| row_totals[i] > 0.0).collect();
+        let kept_columns: Vec<usize> = (0..columns.len())
+            .filter(|&j| observed.iter().map(|row| row[j]).sum::<f64>() > 0.0)
+            .collect();
+        if kept_rows.len() < 2 || kept_columns.len() < 2 {
+            return Err(XtabMLError::Significance(
+                "a chi-square test needs at least two rows and two columns with counts".to_string(),
+            ));
+        }
+        let observed: Vec<Vec<f64>> = kept_rows
+            .iter()
+            .map(|&i| kept_columns.iter().map(|&j| observed[i][j]).collect())
+            .collect();
+        let row_totals: Vec<f64> = observed.iter().map(|row| row.iter().sum()).collect();
+        let column_totals: Vec<f64> = (0..kept_columns.len())
+            .map(|j| observed.iter().map(|row| row[j]).sum())
+            .collect();
+        let total: f64 = row_totals.iter().sum();
+
+        let expected: Vec<Vec<f64>> = row_totals
+            .iter()
+            .map(|r| column_totals.iter().map(|c| r * c / total).collect())
+            .collect();
+        let residuals: Vec<Vec<f64>> = observed
+            .iter()
+            .zip(&expected)
+            .map(|(o, e)| o.iter().zip(e).map(|(o, e)| (o - e) / e.sqrt()).collect())
+            .collect();
+        let statistic = residuals.iter().flatten().map(|r| r * r).sum();
+        let degrees_of_freedom = (kept_rows.len() - 1) * (kept_columns.len() - 1);
+        Ok(ChiSquare {
+            statistic,
+            degrees_of_freedom,
+            p_value: chi_square_upper(statistic, degrees_of_freedom as f64),
+            rows: kept_rows.iter().map(|&i| rows[i]).collect(),
+            columns: kept_columns.iter().map(|&j| columns[j]).collect(),
+            expected,
+            residuals,
+        })
+    }
+
+    /// A copy with column letters and the results of z-tests between column proportions
+    ///
+    /// ```no_run
+    /// # use libxtabml::{Correction, SignificanceOptions, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let options = SignificanceOptions {
+    ///     correction: Correction::Holm,
+    ///     ..SignificanceOptions::default()
+    /// };
+    /// let tested = xtab.tables[0].compare_column_proportions(options)?;
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// The elements of each column group are compared pairwise, in every row, by a pooled
+    /// two-proportion z-test on the column percentages and bases. Tested columns are
+    /// lettered A, B, … across the banner in a "ColumnNames" statistic, and the
+    /// "ColumnComparisons" statistic lists, for each cell, the letters of the columns of
+    /// its group it is significantly higher than, e.g. "B C". The "p" and "pCorrected"
+    /// statistics give the p-values of each comparison before and after correction, e.g.
+    /// "B=0.0012 C=0.3104". All are string statistics replacing any the table has;
+    /// columns not tested get missing cells.
+    pub fn compare_column_proportions(&self, options: SignificanceOptions) -> Result<Table> {
+        let percent = Measure::all(self, &options.statistics)
+            .iter()
+            .position(|&m| m == Measure::Percent(Basis::Column))
+            .ok_or_else(|| {
+                XtabMLError::Significance("the table has no column percentages".to_string())
+            })?;
+        let (base, divisor) = self.sample_sizes(
+            &options,
+            &[StandardStatistic::EffectiveColumnBase],
+            &|s| Measure::of(Some(s)) == Measure::Base(Basis::Column),
+            "column base (ColumnN, BaseN or an effective base)",
+        )?;
+
+        let families = column_families(self.column_edge.as_ref());
+        let comparisons = self.compare_columns(&families, options.correction, |r, a, b| {
+            let sample = |c: usize| -> Option<(f64, f64)> {
+                Some((
+                    self.number(r, percent, c)? / 100.0,
+                    self.number(r, base, c)? / divisor,
+                ))
+            };
+            let ((p1, n1), (p2, n2)) = (sample(a)?, sample(b)?);
+            let z = proportions_z(p1, n1, p2, n2)?;
+            Some((z, normal_two_sided(z)))
+        });
+        Ok(self.with_comparisons(&families, &comparisons, &options))
+    }
+
+    /// A copy with column letters and the results of t-tests between column means
+    ///
+    /// Means come from the Average statistic, with the spread from StandardDeviation or
+    /// else StandardError, and sample sizes from an effective base or else n. Welch or
+    /// pooled-variance t-tests are run as [`SignificanceOptions::t_test`] says, between
+    /// the elements of each column group; results are written as by
+    /// [`Table::compare_column_proportions`].
+    pub fn compare_column_means(&self, options: SignificanceOptions) -> Result<Table> {
+        let find = |wanted: &[StandardStatistic]| self.find_statistic(&options.statistics, wanted);
+        let mean = find(&[StandardStatistic::Mean]).ok_or_else(|| {
+            XtabMLError::Significance("the table has no means (Average)".to_string())
+        })?;
+        let spread = match (
+            find(&[StandardStatistic::StandardDeviation]),
+            find(&[StandardStatistic::StandardError]),
+        ) {
+            (Some(deviation), _) => Spread::Deviation(deviation),
+            (None, Some(error)) => Spread::Error(error),
+            (None, None) => {
+                return Err(XtabMLError::Significance(
+                    "the table has no standard deviations or standard errors".to_string(),
+                ))
+            }
+        };
+        let (size, divisor) = self.sample_sizes(
+            &options,
+            &EFFECTIVE_BASES,
+            &|s| MEANS_BASES.contains(&s),
+            "sample sizes (n, NObservations or an effective base)",
+        )?;
+
+        let families = column_families(self.column_edge.as_ref());
+        let comparisons = self.compare_columns(&families, options.correction, |r, a, b| {
+            let sample = |c: usize| -> Option<(f64, f64, f64)> {
+                let n = self.number(r, size, c)? / divisor;
+                let deviation = match spread {
+                    Spread::Deviation(s) => self.number(r, s, c)?,
+                    Spread::Error(s) => self.number(r, s, c)? * n.sqrt(),
+                };
+                Some((self.number(r, mean, c)?, deviation, n))
+            };
+            let (t, df) = means_t(sample(a)?, sample(b)?, options.t_test)?;
+            Some((t, student_t_two_sided(t, df)))
+        });
+        Ok(self.with_comparisons(&families, &comparisons, &options))
+    }
+
+    /// Index of the sample size statistic for column tests, and the divisor of its values
+    fn sample_sizes(
+        &self,
+        options: &SignificanceOptions,
+        effective: &[StandardStatistic],
+        actual: &dyn Fn(StandardStatistic) -> bool,
+        wanted: &str,
+    ) -> Result<(usize, f64)> {
+        if options.effective_base {
+            if let Some(index) = self.find_statistic(&options.statistics, effective) {
+                return Ok((index, 1.0));
+            }
+        }
+        self.standard_statistics(&options.statistics)
+            .into_iter()
+            .position(|s| s.is_some_and(actual))
+            .map(|index| (index, options.design_effect))
+            .ok_or_else(|| XtabMLError::Significance(format!("the table has no {}", wanted)))
+    }
+
+    /// Run a test between each pair of columns of every family in every row
+    ///
+    /// `test` gives a statistic, positive when the first column is higher, and its
+    /// p-value; the p-values of a family in a row are adjusted together.
+    fn compare_columns(
+        &self,
+        families: &[Vec<usize>],
+        correction: Correction,
+        test: impl Fn(usize, usize, usize) -> Option<(f64, f64)>,
+    ) -> Vec<Vec<Comparison>> {
+        (0..self.data.rows.len())
+            .map(|r| {
+                let mut comparisons = Vec::new();
+                for family in families {
+                    let mut tested = Vec::new();
+                    for (i, &a) in family.iter().enumerate() {
+                        for &b in &family[i + 1..] {
+                            if let Some((statistic, p)) = test(r, a, b) {
+                                let (higher, lower) = if statistic > 0.0 { (a, b) } else { (b, a) };
+                                tested.push((higher, lower, p));
+                            }
+                        }
+                    }
+                    let p: Vec<f64> = tested.iter().map(|&(_, _, p)| p).collect();
+                    let adjusted = correction.adjust(&p);
+                    comparisons.extend(tested.into_iter().zip(adjusted).map(
+                        |((higher, lower, p), adjusted)| Comparison {
+                            higher,
+                            lower,
+                            p,
+                            adjusted,
+                        },
+                    ));
+                }
+                comparisons
+            })
+            .collect()
+    }
+
+    /// A copy with the letters and p-values of column comparisons as string statistics
+    fn with_comparisons(
+        &self,
+        families: &[Vec<usize>],
+        comparisons: &[Vec<Comparison>],
+        options: &SignificanceOptions,
+    ) -> Table {
+        let names = column_letters(families);
+        let alpha = 1.0 - options.confidence;
+        let columns = self.column_count();
+        // Cells listing, for each tested column, an entry per comparison sorted by the
+        // other column
+        let cells = |entry: &dyn Fn(usize, &Comparison) -> Option<Entry>| {
+            comparisons
+                .iter()
+                .map(|row| {
+                    (0..columns)
+                        .map(|c| {
+                            string_cell(names.get(&c).map(|_| {
+                                let mut entries: Vec<(usize, String)> =
+                                    row.iter().filter_map(|cmp| entry(c, cmp)).collect();
+                                entries.sort();
+                                let entries: Vec<String> =
+                                    entries.into_iter().map(|(_, text)| text).collect();
+                                entries.join(" ")
+                            }))
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let other = |c: usize, cmp: &Comparison| match c {
+            _ if cmp.higher == c => Some(cmp.lower),
+            _ if cmp.lower == c => Some(cmp.higher),
+            _ => None,
+        };
+
+        let mut table = self.clone();
+        table.set_statistic(
+            "ColumnNames",
+            "string",
+            comparisons
+                .iter()
+                .map(|_| {
+                    (0..columns)
+                        .map(|c| string_cell(names.get(&c).cloned()))
+                        .collect()
+                })
+                .collect(),
+        );
+        table.set_statistic(
+            "ColumnComparisons",
+            "string",
+            cells(&|c, cmp| {
+                (cmp.higher == c && cmp.adjusted < alpha)
+                    .then(|| (cmp.lower, names[&cmp.lower].clone()))
+            }),
+        );
+        table.set_statistic(
+            "p",
+            "string",
+            cells(&|c, cmp| {
+                let other = other(c, cmp)?;
+                Some((other, format!("{}={:.4}", names[&other], cmp.p)))
+            }),
+        );
+        table.set_statistic(
+            "pCorrected",
+            "string",
+            cells(&|c, cmp| {
+                let other = other(c, cmp)?;
+                Some((other, format!("{}={:.4}", names[&other], cmp.adjusted)))
+            }),
+        );
+        table
+    }
+
+    /// Replace the cells of a statistic, adding it if the table has none
+    pub(crate) fn set_statistic(&mut self, name: &str, datatype: &str, cells: Vec<Vec<DataCell>>) {
+        let statistic = Statistic {
+            r#type: name.to_string(),
+            datatype: Some(datatype.to_string()),
+        };
+        let index = match self.statistics.iter().position(|s| s.r#type == name) {
+            Some(index) => {
+                self.statistics[index] = statistic.clone();
+                index
+            }
+            None => {
+                self.statistics.push(statistic.clone());
+                self.statistics.len() - 1
+            }
+        };
+        for (row, cells) in self.data.rows.iter_mut().zip(cells) {
+            let series = DataRowSeries {
+                statistic: Some(statistic.clone()),
+                cells,
+            };
+            if index < row.data_row_series.len() {
+                row.data_row_series[index] = series;
+            } else {
+                row.data_row_series.push(series);
+            }
+        }
+    }
+}
+
+fn string_cell(value: Option<String>) -> DataCell {
+    match value {
+        Some(value) => DataCell {
+            value: Some(value),
+            ..DataCell::default()
+        },
+        None => DataCell {
+            is_missing: true,
+            ..DataCell::default()
+        },
+    }
+}
+
+/// t statistic and degrees of freedom of the difference between two means, given each
+/// sample's mean, standard deviation and size
+fn means_t(a: (f64, f64, f64), b: (f64, f64, f64), test: TTest) -> Option<(f64, f64)> {
+    let ((m1, s1, n1), (m2, s2, n2)) = (a, b);
+    if n1 <= 1.0 || n2 <= 1.0 {
+        return None;
+    }
+    let (v1, v2) = (s1 * s1 / n1, s2 * s2 / n2);
+    let (se, df) = match test {
+        TTest::Welch => (
+            (v1 + v2).sqrt(),
+            (v1 + v2).powi(2) / (v1 * v1 / (n1 - 1.0) + v2 * v2 / (n2 - 1.0)),
+        ),
+        TTest::Pooled => {
+            let df = n1 + n2 - 2.0;
+            let pooled = ((n1 - 1.0) * s1 * s1 + (n2 - 1.0) * s2 * s2) / df;
+            ((pooled * (1.0 / n1 + 1.0 / n2)).sqrt(), df)
+        }
+    };
+    (se > 0.0).then(|| ((m1 - m2) / se, df))
+}
+
+/// Pooled z statistic of the difference between two proportions, if defined
+fn proportions_z(p1: f64, n1: f64, p2: f64, n2: f64) -> Option<f64> {
+    if n1 <= 0.0 || n2 <= 0.0 {
+        return None;
+    }
+    let pooled = (p1 * n1 + p2 * n2) / (n1 + n2);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    (se > 0.0).then(|| (p1 - p2) / se)
+}
+
+/// Columns compared with each other: the elements of each group with more than one
+pub(crate) fn column_families(edge: Option<&Edge>) -> Vec<Vec<usize>> {
+    let Some(edge) = edge else {
+        return Vec::new();
+    };
+    let index: HashMap<*const (), usize> = edge
+        .leaves()
+        .iter()
+        .enumerate()
+        .map(|(i, leaf)| (address(leaf.node), i))
+        .collect();
+    let mut families = Vec::new();
+    collect_families(&edge.groups, &index, &mut families);
+    families
+}
+
+fn collect_families(
+    groups: &[Group],
+    index: &HashMap<*const (), usize>,
+    families: &mut Vec<Vec<usize>>,
+) {
+    for group in groups {
+        let mut family: Vec<usize> = group
+            .elements
+            .iter()
+            .filter_map(|e| index.get(&address(EdgeNode::Element(e))).copied())
+            .collect();
+        family.sort_unstable();
+        if family.len() > 1 {
+            families.push(family);
+        }
+        collect_families(&group.groups, index, families);
+    }
+}
+
+/// Letters of the tested columns in banner order: A to Z, then AA, AB and so on
+pub(crate) fn column_letters(families: &[Vec<usize>]) -> HashMap<usize, String> {
+    let mut columns: Vec<usize> = families.iter().flatten().copied().collect();
+    columns.sort_unstable();
+    columns.dedup();
+    columns
+        .into_iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let mut letters = Vec::new();
+            let mut n = i + 1;
+            while n > 0 {
+                letters.push(b'A' + ((n - 1) % 26) as u8);
+                n = (n - 1) / 26;
+            }
+            letters.reverse();
+            (column, String::from_utf8(letters).unwrap_or_default())
+        })
+        .collect()
+}
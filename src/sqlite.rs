@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, Transaction};
+
+use crate::{types::*, Result};
+
+/// Schema of the relational export
+///
+/// Every table hangs off a document, so several documents (e.g. survey waves) can share one
+/// database. Edge trees are stored in `edge_nodes` through `parent_id`; leaves carry the
+/// `position` that cells refer to.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY,
+    source TEXT,
+    version TEXT NOT NULL,
+    date TEXT,
+    time TEXT,
+    origin TEXT,
+    user TEXT
+);
+CREATE TABLE IF NOT EXISTS tables (
+    id INTEGER PRIMARY KEY,
+    document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    table_index INTEGER NOT NULL,
+    name TEXT,
+    title TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS controls (
+    id INTEGER PRIMARY KEY,
+    document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    table_id INTEGER REFERENCES tables(id) ON DELETE CASCADE,
+    type TEXT NOT NULL,
+    text TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS edges (
+    id INTEGER PRIMARY KEY,
+    table_id INTEGER NOT NULL REFERENCES tables(id) ON DELETE CASCADE,
+    axis TEXT NOT NULL,
+    name TEXT
+);
+CREATE TABLE IF NOT EXISTS edge_nodes (
+    id INTEGER PRIMARY KEY,
+    edge_id INTEGER NOT NULL REFERENCES edges(id) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES edge_nodes(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('group', 'element', 'summary')),
+    name TEXT,
+    text TEXT,
+    summary_type TEXT,
+    position INTEGER
+);
+CREATE TABLE IF NOT EXISTS statistics (
+    id INTEGER PRIMARY KEY,
+    table_id INTEGER NOT NULL REFERENCES tables(id) ON DELETE CASCADE,
+    statistic_index INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    datatype TEXT
+);
+CREATE TABLE IF NOT EXISTS cells (
+    statistic_id INTEGER NOT NULL REFERENCES statistics(id) ON DELETE CASCADE,
+    row_position INTEGER NOT NULL,
+    column_position INTEGER NOT NULL,
+    row_node_id INTEGER REFERENCES edge_nodes(id),
+    column_node_id INTEGER REFERENCES edge_nodes(id),
+    value TEXT,
+    number REAL,
+    is_missing INTEGER NOT NULL,
+    is_hidden INTEGER NOT NULL,
+    PRIMARY KEY (statistic_id, row_position, column_position)
+) WITHOUT ROWID;
+
+CREATE INDEX IF NOT EXISTS idx_tables_document ON tables(document_id);
+CREATE INDEX IF NOT EXISTS idx_controls_table ON controls(table_id);
+CREATE INDEX IF NOT EXISTS idx_controls_type ON controls(type, text);
+CREATE INDEX IF NOT EXISTS idx_edges_table ON edges(table_id, axis);
+CREATE INDEX IF NOT EXISTS idx_edge_nodes_edge ON edge_nodes(edge_id, position);
+CREATE INDEX IF NOT EXISTS idx_edge_nodes_parent ON edge_nodes(parent_id);
+CREATE INDEX IF NOT EXISTS idx_edge_nodes_text ON edge_nodes(text);
+CREATE INDEX IF NOT EXISTS idx_statistics_table ON statistics(table_id);
+CREATE INDEX IF NOT EXISTS idx_statistics_type ON statistics(type);
+CREATE INDEX IF NOT EXISTS idx_cells_row_node ON cells(row_node_id);
+CREATE INDEX IF NOT EXISTS idx_cells_column_node ON cells(column_node_id);
+";
+
+const DROP_SCHEMA: &str = "
+DROP TABLE IF EXISTS cells;
+DROP TABLE IF EXISTS statistics;
+DROP TABLE IF EXISTS edge_nodes;
+DROP TABLE IF EXISTS edges;
+DROP TABLE IF EXISTS controls;
+DROP TABLE IF EXISTS tables;
+DROP TABLE IF EXISTS documents;
+";
+
+/// Writes XtabML documents into a SQLite database with a normalised schema
+///
+/// ```no_run
+/// use libxtabml::{SqliteExporter, XtabMLParser};
+///
+/// let mut exporter = SqliteExporter::append("waves.db")?;
+/// for wave in ["wave1.xte", "wave2.xte"] {
+///     let xtab = XtabMLParser::parse_file(wave)?;
+///     exporter.export(&xtab, Some(wave))?;
+/// }
+/// # Ok::<(), libxtabml::XtabMLError>(())
+/// ```
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Open a database, replacing any data from an earlier export
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(DROP_SCHEMA)?;
+        Self::from_connection(conn)
+    }
+
+    /// Open a database, keeping documents from earlier exports
+    pub fn append<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Use an existing connection, creating the schema if it is missing
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// The underlying connection, e.g. for running queries
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    /// Write a document and return its id in the `documents` table
+    ///
+    /// `source` is a free-form label such as the file name or wave.
+    pub fn export(&mut self, xtab: &XtabML, source: Option<&str>) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO documents (source, version, date, time, origin, user)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                source,
+                xtab.version,
+                xtab.date,
+                xtab.time,
+                xtab.origin,
+                xtab.user
+            ],
+        )?;
+        let document_id = tx.last_insert_rowid();
+
+        for control in &xtab.controls {
+            insert_control(&tx, document_id, None, control)?;
+        }
+        for (table_index, table) in xtab.tables.iter().enumerate() {
+            insert_table(&tx, document_id, table_index, table)?;
+        }
+
+        tx.commit()?;
+        Ok(document_id)
+    }
+}
+
+fn insert_control(
+    tx: &Transaction,
+    document_id: i64,
+    table_id: Option<i64>,
+    control: &Control,
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO controls (document_id, table_id, type, text) VALUES (?1, ?2, ?3, ?4)",
+        params![document_id, table_id, control.r#type, control.text],
+    )?;
+    Ok(())
+}
+
+fn insert_table(
+    tx: &Transaction,
+    document_id: i64,
+    table_index: usize,
+    table: &Table,
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO tables (document_id, table_index, name, title) VALUES (?1, ?2, ?3, ?4)",
+        params![document_id, table_index as i64, table.name, table.title],
+    )?;
+    let table_id = tx.last_insert_rowid();
+
+    for control in &table.controls {
+        insert_control(tx, document_id, Some(table_id), control)?;
+    }
+
+    let row_nodes = match &table.row_edge {
+        Some(edge) => insert_edge(tx, table_id, edge)?,
+        None => Vec::new(),
+    };
+    let column_nodes = match &table.column_edge {
+        Some(edge) => insert_edge(tx, table_id, edge)?,
+        None => Vec::new(),
+    };
+
+    let mut statistic_ids = Vec::with_capacity(table.statistics.len());
+    for (statistic_index, statistic) in table.statistics.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO statistics (table_id, statistic_index, type, datatype)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                table_id,
+                statistic_index as i64,
+                statistic.r#type,
+                statistic.datatype
+            ],
+        )?;
+        statistic_ids.push(tx.last_insert_rowid());
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO cells (statistic_id, row_position, column_position, row_node_id,
+                            column_node_id, value, number, is_missing, is_hidden)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?;
+    for (row_position, row) in table.data.rows.iter().enumerate() {
+        for (series, statistic_id) in row.data_row_series.iter().zip(&statistic_ids) {
+            // Trailing runs of identical cells may be compressed to a single cell
+            let width = series.cells.len().max(column_nodes.len());
+            for column_position in 0..width {
+                let Some(cell) = series.cell(column_position) else {
+                    continue;
+                };
+                stmt.execute(params![
+                    statistic_id,
+                    row_position as i64,
+                    column_position as i64,
+                    row_nodes.get(row_position),
+                    column_nodes.get(column_position),
+                    cell.value,
+                    cell.as_f64(),
+                    cell.is_missing,
+                    cell.is_hidden,
+                ])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Insert an edge and its tree, returning the node ids of the leaves in data order
+fn insert_edge(tx: &Transaction, table_id: i64, edge: &Edge) -> Result<Vec<i64>> {
+    tx.execute(
+        "INSERT INTO edges (table_id, axis, name) VALUES (?1, ?2, ?3)",
+        params![table_id, edge.axis, edge.name],
+    )?;
+    let edge_id = tx.last_insert_rowid();
+
+    let mut leaves = Vec::new();
+    for group in &edge.groups {
+        insert_group(tx, edge_id, None, group, &mut leaves)?;
+    }
+    // Same ordering as `Edge::leaves`
+    leaves.sort_by_key(|(position, _)| position.unwrap_or(usize::MAX));
+    Ok(leaves.into_iter().map(|(_, id)| id).collect())
+}
+
+fn insert_group(
+    tx: &Transaction,
+    edge_id: i64,
+    parent_id: Option<i64>,
+    group: &Group,
+    leaves: &mut Vec<(Option<usize>, i64)>,
+) -> Result<()> {
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO edge_nodes (edge_id, parent_id, kind, name, text, summary_type, position)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    stmt.execute(params![
+        edge_id,
+        parent_id,
+        "group",
+        group.name,
+        group.text,
+        None::<String>,
+        None::<i64>
+    ])?;
+    let group_id = tx.last_insert_rowid();
+
+    for element in &group.elements {
+        stmt.execute(params![
+            edge_id,
+            group_id,
+            "element",
+            element.name,
+            element.text,
+            None::<String>,
+            element.position.map(|p| p as i64)
+        ])?;
+        leaves.push((element.position, tx.last_insert_rowid()));
+    }
+    for summary in &group.summaries {
+        stmt.execute(params![
+            edge_id,
+            group_id,
+            "summary",
+            summary.name,
+            summary.text,
+            summary.r#type,
+            summary.position.map(|p| p as i64)
+        ])?;
+        leaves.push((summary.position, tx.last_insert_rowid()));
+    }
+    for child in &group.groups {
+        insert_group(tx, edge_id, Some(group_id), child, leaves)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,480 @@
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What a percentage is taken over, or which margin a base is the base of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Basis {
+    /// The column's base
+    Column,
+    /// The row's base
+    Row,
+    /// The base of the whole table
+    Total,
+    /// The column's count over the rows of the row's group
+    RowGroup,
+    /// The row's count over the columns of the column's group
+    ColumnGroup,
+    /// The counts of all answers in the column's part of the row edge
+    ColumnResponses,
+    /// The counts of all answers in the row's part of the column edge
+    RowResponses,
+    /// The counts of all answers in both
+    TotalResponses,
+}
+
+/// What a statistic means, whatever a tabulation system calls it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StandardStatistic {
+    /// Publication count, weighted if the table is
+    Count,
+    UnweightedCount,
+    WeightedCount,
+    /// Sum of a quantity rather than of respondents
+    Volume,
+    SumOfSquaredWeights,
+    SumOfSquaredVolumes,
+    /// Effective sample size of the cell, allowing for weighting
+    EffectiveCount,
+    MissingCount,
+
+    ColumnBase,
+    RowBase,
+    TotalBase,
+    EffectiveColumnBase,
+    EffectiveRowBase,
+
+    ColumnPercent,
+    RowPercent,
+    TotalPercent,
+    RowGroupPercent,
+    ColumnGroupPercent,
+    ColumnVolumePercent,
+    RowVolumePercent,
+    TotalVolumePercent,
+    RowGroupVolumePercent,
+    ColumnGroupVolumePercent,
+    ColumnResponsesPercent,
+    RowResponsesPercent,
+    TotalResponsesPercent,
+    CumulativePercent,
+    /// Probability expressed as a percentage, such as a choice model's share of
+    /// preference, which is not taken over a base
+    ProbabilityPercent,
+
+    Population,
+    RowPopulation,
+    ColumnPopulation,
+    BasePopulation,
+
+    Mean,
+    TrimmedMean,
+    Median,
+    Mode,
+    Minimum,
+    Maximum,
+    /// The given percentile, e.g. 25 for the lower quartile
+    Percentile(u8),
+    Sum,
+    Range,
+    InterquartileRange,
+    Variance,
+    StandardDeviation,
+    StandardError,
+    ColumnStandardError,
+    ColumnStandardErrorOfMean,
+    LowerCI,
+    UpperCI,
+    /// Bounds of confidence intervals of the percentages taken over the given basis
+    LowerCIPercent(Basis),
+    UpperCIPercent(Basis),
+
+    Correlation,
+    Coefficient,
+    Index,
+    ExpectedCount,
+    ExpectedPercent,
+    ExpectedMean,
+    ExpectedCorrelation,
+    Residual,
+    ResidualPercent,
+    TStatistic,
+    ZStatistic,
+    DegreesOfFreedom,
+    PValue,
+    CorrectedPValue,
+    /// Letters naming the columns compared by significance tests
+    ColumnNames,
+    /// Letters of the columns a cell is significantly higher than
+    ColumnComparisons,
+    ColumnsCompared,
+}
+
+impl StandardStatistic {
+    /// Whether values are percentages, including bounds and expectations of percentages
+    pub fn is_percentage(&self) -> bool {
+        use StandardStatistic::*;
+        self.percentaged_on().is_some()
+            || matches!(
+                self,
+                CumulativePercent
+                    | ProbabilityPercent
+                    | LowerCIPercent(_)
+                    | UpperCIPercent(_)
+                    | ExpectedPercent
+            )
+    }
+
+    /// Whether values count respondents, so that those of disjoint rows may be summed
+    pub fn is_count(&self) -> bool {
+        use StandardStatistic::*;
+        matches!(self, Count | UnweightedCount | WeightedCount)
+    }
+
+    /// Whether values are the bases percentages and tests are taken over
+    pub fn is_base(&self) -> bool {
+        self.base_of().is_some()
+    }
+
+    /// Whether values are effective sample sizes, which allow for weighting
+    pub fn is_effective(&self) -> bool {
+        use StandardStatistic::*;
+        matches!(
+            self,
+            EffectiveCount | EffectiveColumnBase | EffectiveRowBase
+        )
+    }
+
+    /// What a percentage is taken over; `None` for other statistics
+    pub fn percentaged_on(&self) -> Option<Basis> {
+        use StandardStatistic::*;
+        match self {
+            ColumnPercent | ColumnVolumePercent => Some(Basis::Column),
+            RowPercent | RowVolumePercent => Some(Basis::Row),
+            TotalPercent | TotalVolumePercent => Some(Basis::Total),
+            RowGroupPercent | RowGroupVolumePercent => Some(Basis::RowGroup),
+            ColumnGroupPercent | ColumnGroupVolumePercent => Some(Basis::ColumnGroup),
+            ColumnResponsesPercent => Some(Basis::ColumnResponses),
+            RowResponsesPercent => Some(Basis::RowResponses),
+            TotalResponsesPercent => Some(Basis::TotalResponses),
+            _ => None,
+        }
+    }
+
+    /// Which margin a base is the base of; `None` for other statistics
+    pub fn base_of(&self) -> Option<Basis> {
+        use StandardStatistic::*;
+        match self {
+            ColumnBase | EffectiveColumnBase => Some(Basis::Column),
+            RowBase | EffectiveRowBase => Some(Basis::Row),
+            TotalBase => Some(Basis::Total),
+            _ => None,
+        }
+    }
+
+    /// Whether a percentage is of a quantity's volume rather than of respondents
+    pub fn is_volume(&self) -> bool {
+        use StandardStatistic::*;
+        matches!(
+            self,
+            Volume
+                | SumOfSquaredVolumes
+                | ColumnVolumePercent
+                | RowVolumePercent
+                | TotalVolumePercent
+                | RowGroupVolumePercent
+                | ColumnGroupVolumePercent
+        )
+    }
+
+    /// Name of the statistic among the XtabML standard statistics, e.g. "xs:cp"
+    pub fn xtabml_name(&self) -> Option<&'static str> {
+        XTABML_STATISTICS
+            .iter()
+            .find(|(_, statistic)| statistic == self)
+            .map(|(name, _)| *name)
+    }
+}
+
+/// The standard statistics of the XtabML specification
+const XTABML_STATISTICS: [(&str, StandardStatistic); 24] = [
+    ("xs:t", StandardStatistic::Count),
+    ("xs:rt", StandardStatistic::UnweightedCount),
+    ("xs:wt", StandardStatistic::WeightedCount),
+    ("xs:qt", StandardStatistic::Volume),
+    ("xs:wsqt", StandardStatistic::SumOfSquaredWeights),
+    ("xs:qsqt", StandardStatistic::SumOfSquaredVolumes),
+    ("xs:ess", StandardStatistic::EffectiveCount),
+    ("xs:mean", StandardStatistic::Mean),
+    ("xs:variance", StandardStatistic::Variance),
+    ("xs:sd", StandardStatistic::StandardDeviation),
+    ("xs:se", StandardStatistic::StandardError),
+    ("xs:sew", StandardStatistic::StandardError),
+    ("xs:cp", StandardStatistic::ColumnPercent),
+    ("xs:rp", StandardStatistic::RowPercent),
+    ("xs:pp", StandardStatistic::TotalPercent),
+    ("xs:lcp", StandardStatistic::RowGroupPercent),
+    ("xs:lrp", StandardStatistic::ColumnGroupPercent),
+    ("xs:qcp", StandardStatistic::ColumnVolumePercent),
+    ("xs:qrp", StandardStatistic::RowVolumePercent),
+    ("xs:qpp", StandardStatistic::TotalVolumePercent),
+    ("xs:lqcp", StandardStatistic::RowGroupVolumePercent),
+    // The specification declares the local quantity row percentage as "xs:lrcp" and
+    // defines it as "xs:lqrp"
+    ("xs:lqrp", StandardStatistic::ColumnGroupVolumePercent),
+    ("xs:lrcp", StandardStatistic::ColumnGroupVolumePercent),
+    ("xs:pop", StandardStatistic::Population),
+];
+
+/// The statistic types Q declares, with a few common aliases
+///
+/// Q's sums of squared weights (WW, and BaseWW, ColumnWW and RowWW of the margins) all
+/// map to `SumOfSquaredWeights`. Left out, as no statistic here means the same, are the
+/// raw data types Values, Labels, Text, TextNoBlanks, UniqueText and Observation, the
+/// NotDuplicate flag, and multipleComparisonAdjustment, which names a correction rather
+/// than holding a value.
+const Q_STATISTICS: [(&str, StandardStatistic); 83] = [
+    ("Count", StandardStatistic::Count),
+    ("Frequency", StandardStatistic::Count),
+    ("n", StandardStatistic::UnweightedCount),
+    ("NObservations", StandardStatistic::UnweightedCount),
+    ("EffectiveN", StandardStatistic::EffectiveCount),
+    ("MissingN", StandardStatistic::MissingCount),
+    ("ColumnN", StandardStatistic::ColumnBase),
+    ("ColumnBase", StandardStatistic::ColumnBase),
+    ("BaseN", StandardStatistic::ColumnBase),
+    ("Base", StandardStatistic::ColumnBase),
+    ("RowN", StandardStatistic::RowBase),
+    ("RowBase", StandardStatistic::RowBase),
+    ("TotalN", StandardStatistic::TotalBase),
+    ("TotalBase", StandardStatistic::TotalBase),
+    ("EffectiveColumnN", StandardStatistic::EffectiveColumnBase),
+    ("EffectiveBaseN", StandardStatistic::EffectiveColumnBase),
+    ("EffectiveRowN", StandardStatistic::EffectiveRowBase),
+    ("WW", StandardStatistic::SumOfSquaredWeights),
+    ("BaseWW", StandardStatistic::SumOfSquaredWeights),
+    ("ColumnWW", StandardStatistic::SumOfSquaredWeights),
+    ("RowWW", StandardStatistic::SumOfSquaredWeights),
+    ("ColumnPercent", StandardStatistic::ColumnPercent),
+    ("ColPct", StandardStatistic::ColumnPercent),
+    ("Percent", StandardStatistic::ColumnPercent),
+    ("PercentExcludingNaN", StandardStatistic::ColumnPercent),
+    ("RowPercent", StandardStatistic::RowPercent),
+    ("RowPct", StandardStatistic::RowPercent),
+    ("TotalPercent", StandardStatistic::TotalPercent),
+    ("TotPct", StandardStatistic::TotalPercent),
+    ("PercentShare", StandardStatistic::ColumnVolumePercent),
+    (
+        "PercentShareColumns",
+        StandardStatistic::ColumnVolumePercent,
+    ),
+    ("PercentShareRows", StandardStatistic::RowVolumePercent),
+    ("PercentShareTotal", StandardStatistic::TotalVolumePercent),
+    (
+        "PercentResponses",
+        StandardStatistic::ColumnResponsesPercent,
+    ),
+    (
+        "PercentColumnResponses",
+        StandardStatistic::ColumnResponsesPercent,
+    ),
+    (
+        "PercentRowResponses",
+        StandardStatistic::RowResponsesPercent,
+    ),
+    (
+        "PercentTotalResponses",
+        StandardStatistic::TotalResponsesPercent,
+    ),
+    ("CumulativePercent", StandardStatistic::CumulativePercent),
+    ("ProbabilityPercent", StandardStatistic::ProbabilityPercent),
+    ("Population", StandardStatistic::Population),
+    ("RowPopulation", StandardStatistic::RowPopulation),
+    ("ColumnPopulation", StandardStatistic::ColumnPopulation),
+    ("BasePopulation", StandardStatistic::BasePopulation),
+    ("Average", StandardStatistic::Mean),
+    ("Mean", StandardStatistic::Mean),
+    ("TrimmedAverage", StandardStatistic::TrimmedMean),
+    ("Median", StandardStatistic::Median),
+    ("Mode", StandardStatistic::Mode),
+    ("Minimum", StandardStatistic::Minimum),
+    ("Maximum", StandardStatistic::Maximum),
+    ("Percentile5", StandardStatistic::Percentile(5)),
+    ("Percentile25", StandardStatistic::Percentile(25)),
+    ("Percentile75", StandardStatistic::Percentile(75)),
+    ("Percentile95", StandardStatistic::Percentile(95)),
+    ("Sum", StandardStatistic::Sum),
+    ("Range", StandardStatistic::Range),
+    ("InterquartileRange", StandardStatistic::InterquartileRange),
+    ("StandardDeviation", StandardStatistic::StandardDeviation),
+    ("StdDev", StandardStatistic::StandardDeviation),
+    ("StandardError", StandardStatistic::StandardError),
+    (
+        "ColumnStandardError",
+        StandardStatistic::ColumnStandardError,
+    ),
+    (
+        "ColumnStandardErrorOfMean",
+        StandardStatistic::ColumnStandardErrorOfMean,
+    ),
+    ("LowerCI", StandardStatistic::LowerCI),
+    ("UpperCI", StandardStatistic::UpperCI),
+    (
+        "LowerCIPercent",
+        StandardStatistic::LowerCIPercent(Basis::Column),
+    ),
+    (
+        "UpperCIPercent",
+        StandardStatistic::UpperCIPercent(Basis::Column),
+    ),
+    ("Correlation", StandardStatistic::Correlation),
+    ("Coefficient", StandardStatistic::Coefficient),
+    ("Index", StandardStatistic::Index),
+    ("ExpectedN", StandardStatistic::ExpectedCount),
+    ("ExpectedPercent", StandardStatistic::ExpectedPercent),
+    ("ExpectedAverage", StandardStatistic::ExpectedMean),
+    (
+        "ExpectedCorrelation",
+        StandardStatistic::ExpectedCorrelation,
+    ),
+    ("Residual", StandardStatistic::Residual),
+    ("ResidualPercent", StandardStatistic::ResidualPercent),
+    ("TStatistic", StandardStatistic::TStatistic),
+    ("ZStatistic", StandardStatistic::ZStatistic),
+    ("DegreesOfFreedom", StandardStatistic::DegreesOfFreedom),
+    ("p", StandardStatistic::PValue),
+    ("pCorrected", StandardStatistic::CorrectedPValue),
+    ("ColumnNames", StandardStatistic::ColumnNames),
+    ("ColumnComparisons", StandardStatistic::ColumnComparisons),
+    ("ColumnsCompared", StandardStatistic::ColumnsCompared),
+];
+
+/// Statistics the library derives that neither XtabML nor Q has a name for
+const LIBRARY_STATISTICS: [(&str, StandardStatistic); 4] = [
+    (
+        "LowerCIRowPercent",
+        StandardStatistic::LowerCIPercent(Basis::Row),
+    ),
+    (
+        "UpperCIRowPercent",
+        StandardStatistic::UpperCIPercent(Basis::Row),
+    ),
+    (
+        "LowerCITotalPercent",
+        StandardStatistic::LowerCIPercent(Basis::Total),
+    ),
+    (
+        "UpperCITotalPercent",
+        StandardStatistic::UpperCIPercent(Basis::Total),
+    ),
+];
+
+/// Vendor statistic type names and what they mean
+///
+/// ```
+/// # use libxtabml::{Basis, StandardStatistic, StatisticRegistry};
+/// let mut registry = StatisticRegistry::standard();
+/// assert_eq!(registry.lookup("xs:cp"), Some(StandardStatistic::ColumnPercent));
+/// assert_eq!(registry.lookup("ColumnPercent"), Some(StandardStatistic::ColumnPercent));
+///
+/// registry.register("Col%", StandardStatistic::ColumnPercent);
+/// let meaning = registry.lookup("Col%").unwrap();
+/// assert_eq!(meaning.percentaged_on(), Some(Basis::Column));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticRegistry {
+    names: HashMap<String, StandardStatistic>,
+}
+
+impl Default for StatisticRegistry {
+    fn default() -> StatisticRegistry {
+        StatisticRegistry::standard()
+    }
+}
+
+impl StatisticRegistry {
+    /// A registry knowing no names
+    pub fn new() -> StatisticRegistry {
+        StatisticRegistry {
+            names: HashMap::new(),
+        }
+    }
+
+    /// The XtabML standard statistics ("xs:cp", "xs:mean" and so on) only
+    pub fn xtabml() -> StatisticRegistry {
+        let mut registry = StatisticRegistry::new();
+        registry.extend(XTABML_STATISTICS);
+        registry
+    }
+
+    /// The XtabML standard statistics, the vocabulary of Q ("ColumnPercent", "n",
+    /// "Average" and so on) and the names the library gives statistics Q lacks, such as
+    /// "LowerCIRowPercent", as used by the library's own transformations
+    pub fn standard() -> StatisticRegistry {
+        let mut registry = StatisticRegistry::xtabml();
+        registry.extend(Q_STATISTICS);
+        registry.extend(LIBRARY_STATISTICS);
+        registry
+    }
+
+    /// Give a name a meaning, returning the meaning it had
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        statistic: StandardStatistic,
+    ) -> Option<StandardStatistic> {
+        self.names.insert(name.into(), statistic)
+    }
+
+    /// Register several names
+    pub fn extend<S: Into<String>>(
+        &mut self,
+        names: impl IntoIterator<Item = (S, StandardStatistic)>,
+    ) {
+        for (name, statistic) in names {
+            self.register(name, statistic);
+        }
+    }
+
+    /// What a statistic type name means, if it is known
+    pub fn lookup(&self, name: &str) -> Option<StandardStatistic> {
+        self.names.get(name).copied()
+    }
+
+    /// The names registered for a statistic, sorted
+    pub fn names(&self, statistic: StandardStatistic) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .names
+            .iter()
+            .filter(|(_, &s)| s == statistic)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl Table {
+    /// What each of the table's statistics means, in declaration order
+    pub fn standard_statistics(
+        &self,
+        registry: &StatisticRegistry,
+    ) -> Vec<Option<StandardStatistic>> {
+        self.statistics
+            .iter()
+            .map(|s| registry.lookup(&s.r#type))
+            .collect()
+    }
+
+    /// Index of the first statistic meaning the earliest of `wanted` the table has
+    pub fn find_statistic(
+        &self,
+        registry: &StatisticRegistry,
+        wanted: &[StandardStatistic],
+    ) -> Option<usize> {
+        let meanings = self.standard_statistics(registry);
+        wanted
+            .iter()
+            .find_map(|&w| meanings.iter().position(|&m| m == Some(w)))
+    }
+}
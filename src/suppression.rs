@@ -0,0 +1,251 @@
+use crate::derive::EdgeBases;
+use crate::metadata::MetadataRules;
+use crate::net::Measure;
+use crate::statistic::{Basis, StatisticRegistry};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What [`XtabML::apply_low_base_rules`] does to cells whose base is too low
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LowBaseAction {
+    /// Leave the cells as they are and only report them
+    #[default]
+    Flag,
+    /// Mark the cells hidden, as `<h>` does, keeping their values
+    Hide,
+    /// Replace the values with a marker such as "*"
+    Replace(String),
+}
+
+/// Where the base of a low-base cell came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BaseSource {
+    /// A column base statistic such as ColumnN or BaseN
+    Statistic,
+    /// The count of the base summary of the cell's row group
+    Summary,
+    /// The smallest base stated by a base control
+    Control,
+}
+
+/// A cell [`XtabML::apply_low_base_rules`] found below its threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LowBaseCell {
+    /// Index of the table in the document
+    pub table: usize,
+    pub row: usize,
+    /// Index of the statistic in the table
+    pub statistic: usize,
+    pub statistic_type: String,
+    pub column: usize,
+    /// The cell's value before the action was applied
+    pub value: Option<String>,
+    pub base: f64,
+    pub threshold: f64,
+    pub source: BaseSource,
+}
+
+/// How [`XtabML::apply_low_base_rules`] finds and treats low bases
+pub struct LowBaseRules {
+    /// Bases below this are low
+    pub threshold: f64,
+
+    /// Thresholds for particular statistic types, overriding `threshold`. Base
+    /// statistics are only checked when they have one here.
+    pub thresholds: HashMap<String, f64>,
+
+    pub action: LowBaseAction,
+
+    /// Control type of the note added to tables with low-base cells; `None` adds none
+    pub note_type: Option<String>,
+
+    /// What the tables' statistic types mean, to find counts and column bases
+    pub statistics: StatisticRegistry,
+
+    /// How base controls are read, for tables with neither base statistics nor base
+    /// summaries
+    pub metadata: MetadataRules,
+}
+
+impl Default for LowBaseRules {
+    fn default() -> LowBaseRules {
+        LowBaseRules {
+            threshold: 30.0,
+            thresholds: HashMap::new(),
+            action: LowBaseAction::Flag,
+            note_type: Some("notes".to_string()),
+            statistics: StatisticRegistry::standard(),
+            metadata: MetadataRules::standard(),
+        }
+    }
+}
+
+impl XtabML {
+    /// A copy with the cells of every table whose base is below a threshold flagged,
+    /// hidden or replaced, and the cells concerned
+    ///
+    /// ```no_run
+    /// # use libxtabml::{LowBaseAction, LowBaseRules, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let rules = LowBaseRules {
+    ///     threshold: 50.0,
+    ///     action: LowBaseAction::Replace("*".to_string()),
+    ///     ..LowBaseRules::default()
+    /// };
+    /// let (deliverable, suppressed) = xtab.apply_low_base_rules(&rules);
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// A cell's base is, in order of preference, its value of the first column base
+    /// statistic (ColumnN, BaseN and the like), the count in its column of the base
+    /// summary of its row group (type "xs:base", "base" or "total", as for
+    /// [`Table::derive_percentages`]), or the smallest base the table's base control
+    /// states. Base statistics and base rows are left alone unless a threshold is set for
+    /// them, and missing cells are skipped. Each table with low-base cells gets a control
+    /// of type [`LowBaseRules::note_type`] saying so, declared as a secondary control type
+    /// if the document does not declare it.
+    pub fn apply_low_base_rules(&self, rules: &LowBaseRules) -> (XtabML, Vec<LowBaseCell>) {
+        let mut xtab = self.clone();
+        let mut found = Vec::new();
+        for (t, table) in self.tables.iter().enumerate() {
+            let cells = table.low_base_cells(t, self, rules);
+            if cells.is_empty() {
+                continue;
+            }
+            let target = &mut xtab.tables[t];
+            for cell in &cells {
+                let columns = table.column_count();
+                let Some(series) = target.data.rows[cell.row]
+                    .data_row_series
+                    .get_mut(cell.statistic)
+                else {
+                    continue;
+                };
+                // Undo the compression of a trailing run before changing one of its cells
+                if series.cells.len() < columns {
+                    series.cells = (0..columns)
+                        .map(|c| series.cell(c).cloned().unwrap_or_default())
+                        .collect();
+                }
+                let data = &mut series.cells[cell.column];
+                match &rules.action {
+                    LowBaseAction::Flag => {}
+                    LowBaseAction::Hide => data.is_hidden = true,
+                    LowBaseAction::Replace(marker) => {
+                        data.value = Some(marker.clone());
+                        data.is_hidden = false;
+                    }
+                }
+            }
+            if let Some(note_type) = &rules.note_type {
+                target.controls.push(Control {
+                    r#type: note_type.clone(),
+                    text: note(&cells, &rules.action),
+                    alt_texts: Vec::new(),
+                });
+            }
+            found.extend(cells);
+        }
+
+        if let Some(note_type) = &rules.note_type {
+            if !found.is_empty() && !xtab.control_types.iter().any(|d| &d.name == note_type) {
+                xtab.control_types.push(ControlType {
+                    name: note_type.clone(),
+                    status: Some("secondary".to_string()),
+                    text: "Notes".to_string(),
+                    alt_texts: Vec::new(),
+                });
+            }
+        }
+        (xtab, found)
+    }
+}
+
+impl Table {
+    /// Cells of the table below their thresholds
+    fn low_base_cells(
+        &self,
+        index: usize,
+        xtab: &XtabML,
+        rules: &LowBaseRules,
+    ) -> Vec<LowBaseCell> {
+        let measures = Measure::all(self, &rules.statistics);
+        let base_statistic = measures
+            .iter()
+            .position(|m| matches!(m, Measure::Base(Basis::Column)));
+        let count = measures.iter().position(|&m| m == Measure::Count);
+        let rows = EdgeBases::new(self.row_edge.as_ref(), self.data.rows.len());
+        let control = self
+            .metadata(xtab, &rules.metadata)
+            .facts
+            .base_n
+            .map(|n| n.min);
+        let base = |row: usize, column: usize| -> Option<(f64, BaseSource)> {
+            base_statistic
+                .and_then(|b| self.number(row, b, column))
+                .map(|n| (n, BaseSource::Statistic))
+                .or_else(|| {
+                    let n = self.number(rows.base(row)?, count?, column)?;
+                    Some((n, BaseSource::Summary))
+                })
+                .or_else(|| control.map(|n| (n, BaseSource::Control)))
+        };
+
+        let mut cells = Vec::new();
+        for (s, statistic) in self.statistics.iter().enumerate() {
+            let explicit = rules.thresholds.get(&statistic.r#type).copied();
+            let is_base = matches!(measures[s], Measure::Base(_));
+            let Some(threshold) = explicit.or((!is_base).then_some(rules.threshold)) else {
+                continue;
+            };
+            for (r, row) in self.data.rows.iter().enumerate() {
+                if rows.base(r) == Some(r) && explicit.is_none() {
+                    continue;
+                }
+                let Some(series) = row.data_row_series.get(s) else {
+                    continue;
+                };
+                for column in 0..self.column_count() {
+                    let Some(cell) = series.cell(column).filter(|c| !c.is_missing) else {
+                        continue;
+                    };
+                    if let Some((n, source)) = base(r, column).filter(|&(n, _)| n < threshold) {
+                        cells.push(LowBaseCell {
+                            table: index,
+                            row: r,
+                            statistic: s,
+                            statistic_type: statistic.r#type.clone(),
+                            column,
+                            value: cell.value.clone(),
+                            base: n,
+                            threshold,
+                            source,
+                        });
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// Text of the note on a table's low-base cells, e.g. "Low base (below 30): 4 cells hidden"
+fn note(cells: &[LowBaseCell], action: &LowBaseAction) -> String {
+    let mut thresholds: Vec<f64> = cells.iter().map(|c| c.threshold).collect();
+    thresholds.sort_by(f64::total_cmp);
+    thresholds.dedup();
+    let thresholds: Vec<String> = thresholds.iter().map(f64::to_string).collect();
+    let treatment = match action {
+        LowBaseAction::Flag => "flagged".to_string(),
+        LowBaseAction::Hide => "hidden".to_string(),
+        LowBaseAction::Replace(marker) => format!("shown as {:?}", marker),
+    };
+    format!(
+        "Low base (below {}): {} {} {}",
+        thresholds.join(" or "),
+        cells.len(),
+        if cells.len() == 1 { "cell" } else { "cells" },
+        treatment
+    )
+}
@@ -0,0 +1,549 @@
+use crate::layout::{HeaderKind, RenderOptions};
+use crate::types::*;
+
+/// Characters used for the table borders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoxStyle {
+    /// `+`, `-` and `|`, safe for any terminal or log file
+    Ascii,
+
+    /// Unicode box-drawing characters
+    #[default]
+    Unicode,
+}
+
+/// What to do with data columns that do not fit the width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Continue the remaining columns in further blocks below, repeating the row labels
+    #[default]
+    Wrap,
+
+    /// Put each block on its own page, separated by form feeds and numbered
+    Pages,
+}
+
+/// Options for the plain-text renderer
+#[derive(Debug, Clone)]
+pub struct TextOptions {
+    pub style: BoxStyle,
+
+    /// Maximum line width, usually the terminal width; unlimited when `None`
+    pub width: Option<usize>,
+
+    pub overflow: Overflow,
+
+    /// Labels longer than this are wrapped over several lines
+    pub max_label_width: usize,
+
+    /// List the controls under each table title
+    pub include_controls: bool,
+
+    pub render: RenderOptions,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            style: BoxStyle::Unicode,
+            width: None,
+            overflow: Overflow::Wrap,
+            max_label_width: 24,
+            include_controls: true,
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+/// Indentation of nested row labels, per level
+const INDENT: usize = 2;
+
+impl XtabML {
+    /// Render every table as plain text under its title
+    ///
+    /// Statistic sub-rows are labelled with the display text of their statistic type.
+    pub fn to_text(&self, options: &TextOptions) -> String {
+        let separator = match options.overflow {
+            Overflow::Wrap => "\n",
+            Overflow::Pages => "\x0c",
+        };
+        self.tables
+            .iter()
+            .map(|table| self.table_to_text(table, options))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// Render one table of this document as plain text, with its title and controls
+    pub fn table_to_text(&self, table: &Table, options: &TextOptions) -> String {
+        let mut heading = vec![table.title.clone()];
+        if options.include_controls {
+            heading.extend(
+                table
+                    .controls
+                    .iter()
+                    .map(|c| format!("{}: {}", c.r#type, c.text)),
+            );
+        }
+        render(table, options, &heading, |name| {
+            self.statistic_text(name).unwrap_or(name).to_string()
+        })
+    }
+}
+
+impl Table {
+    /// Render the table as plain text
+    ///
+    /// Nested column groups are stacked above the column labels, nested row groups are
+    /// indented, and each statistic gets its own sub-row labelled by its type. Use
+    /// [`XtabML::table_to_text`] to label statistics by their display text instead.
+    pub fn to_text(&self, options: &TextOptions) -> String {
+        render(self, options, &[], |name| name.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// A cell of a text line, spanning `span` grid columns
+#[derive(Debug)]
+struct Cell {
+    start: usize,
+    span: usize,
+    text: String,
+    indent: usize,
+    align: Align,
+}
+
+impl Cell {
+    fn new(start: usize, span: usize, text: &str, align: Align) -> Self {
+        Cell {
+            start,
+            span,
+            text: text.to_string(),
+            indent: 0,
+            align,
+        }
+    }
+
+    fn blank(start: usize) -> Self {
+        Cell::new(start, 1, "", Align::Left)
+    }
+}
+
+/// A line of cells, with the horizontal rule segments drawn above it
+#[derive(Debug)]
+struct Line {
+    rule: Vec<bool>,
+    cells: Vec<Cell>,
+}
+
+/// Stub columns before the data: row labels and statistic labels
+const STUB: usize = 2;
+
+fn render<F>(table: &Table, options: &TextOptions, heading: &[String], statistic_label: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let rows = table.row_header();
+    let columns = table.column_header();
+    let statistics = options.render.statistic_indices(table);
+    let format = &options.render.number_format;
+    let max_label = options.max_label_width.max(1);
+
+    let labels: Vec<String> = statistics
+        .iter()
+        .map(|&i| statistic_label(&table.statistics[i].r#type))
+        .collect();
+    let values: Vec<Vec<Vec<String>>> = table
+        .data
+        .rows
+        .iter()
+        .map(|row| {
+            statistics
+                .iter()
+                .map(|&index| {
+                    let series = row.data_row_series.get(index);
+                    (0..columns.leaves.len())
+                        .map(|column| format.format(series.and_then(|s| s.cell(column))))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    // Natural widths of the stub and data columns
+    let label_width = rows
+        .groups
+        .iter()
+        .chain(&rows.leaves)
+        .map(|c| (c.level * INDENT + width(&c.text)).min(max_label.max(c.level * INDENT + 1)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let statistic_width = labels
+        .iter()
+        .map(|l| width(l).min(max_label))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut widths: Vec<usize> = columns
+        .leaves
+        .iter()
+        .enumerate()
+        .map(|(column, leaf)| {
+            values
+                .iter()
+                .flatten()
+                .filter_map(|v| v.get(column))
+                .map(|v| width(v))
+                .chain(std::iter::once(width(&leaf.text).min(max_label)))
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        })
+        .collect();
+    for group in &columns.groups {
+        let range = group.start..group.start + group.span;
+        let available = widths[range.clone()].iter().sum::<usize>() + 3 * (group.span - 1);
+        let needed = width(&group.text).min(max_label.max(available));
+        if needed > available {
+            widths[range.end - 1] += needed - available;
+        }
+    }
+
+    // Split the data columns into blocks that fit the width
+    let stub_width = 1 + (label_width + 3) + (statistic_width + 3);
+    let mut blocks: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut start = 0;
+    while start < widths.len() {
+        let mut end = start + 1;
+        let mut used = stub_width + widths[start] + 3;
+        while let Some(&w) = widths.get(end) {
+            if options.width.is_some_and(|limit| used + w + 3 > limit) {
+                break;
+            }
+            used += w + 3;
+            end += 1;
+        }
+        blocks.push(start..end);
+        start = end;
+    }
+    if blocks.is_empty() {
+        blocks.push(0..0);
+    }
+
+    let pages = blocks.len();
+    let mut out = String::new();
+    for (page, block) in blocks.into_iter().enumerate() {
+        if page == 0 || options.overflow == Overflow::Pages {
+            for text in heading {
+                let lines = match options.width {
+                    Some(limit) => wrap(text, limit.max(1)),
+                    None => vec![text.clone()],
+                };
+                for line in lines {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+        }
+        if page > 0 && options.overflow == Overflow::Wrap {
+            out.push('\n');
+        }
+
+        let mut grid_widths = vec![label_width, statistic_width];
+        grid_widths.extend_from_slice(&widths[block.clone()]);
+        let lines = block_lines(&rows, &columns, &labels, &values, block);
+        out.push_str(&draw(&lines, &grid_widths, options.style));
+
+        if options.overflow == Overflow::Pages && pages > 1 {
+            out.push_str(&format!("Page {} of {}\n", page + 1, pages));
+            if page + 1 < pages {
+                out.push('\x0c');
+            }
+        }
+    }
+    out
+}
+
+/// Lay out the header and body lines for a block of data columns
+fn block_lines(
+    rows: &crate::layout::EdgeHeader,
+    columns: &crate::layout::EdgeHeader,
+    labels: &[String],
+    values: &[Vec<Vec<String>>],
+    block: std::ops::Range<usize>,
+) -> Vec<Line> {
+    let count = STUB + block.len();
+    let mut lines = Vec::new();
+
+    for level in 0..columns.levels {
+        let mut cells = vec![Cell::new(0, STUB, "", Align::Left)];
+        let mut position = block.start;
+        while position < block.end {
+            let column = STUB + position - block.start;
+            if level + 1 == columns.levels {
+                cells.push(Cell::new(
+                    column,
+                    1,
+                    &columns.leaves[position].text,
+                    Align::Right,
+                ));
+                position += 1;
+                continue;
+            }
+            match columns
+                .groups
+                .iter()
+                .find(|g| g.level == level && g.start <= position && position < g.start + g.span)
+            {
+                Some(group) => {
+                    let span = (group.start + group.span).min(block.end) - position;
+                    cells.push(Cell::new(column, span, &group.text, Align::Center));
+                    position += span;
+                }
+                None => {
+                    cells.push(Cell::blank(column));
+                    position += 1;
+                }
+            }
+        }
+
+        // Rule under the group headings of the level above
+        let rule = if level == 0 {
+            vec![true; count]
+        } else {
+            (0..count)
+                .map(|column| {
+                    column >= STUB && {
+                        let position = block.start + column - STUB;
+                        columns.groups.iter().any(|g| {
+                            g.level == level - 1
+                                && g.start <= position
+                                && position < g.start + g.span
+                        })
+                    }
+                })
+                .collect()
+        };
+        lines.push(Line { rule, cells });
+    }
+
+    for (position, row_values) in values.iter().enumerate() {
+        let mut rule = vec![true; count];
+        for header in rows.starting_at(position) {
+            if header.kind == HeaderKind::Group {
+                let mut cells = vec![Cell {
+                    indent: header.level * INDENT,
+                    ..Cell::new(0, 1, &header.text, Align::Left)
+                }];
+                cells.extend((1..count).map(Cell::blank));
+                lines.push(Line {
+                    rule: std::mem::replace(&mut rule, vec![false; count]),
+                    cells,
+                });
+            }
+        }
+
+        let leaf = &rows.leaves[position];
+        let statistic_rows = labels.len().max(1);
+        for n in 0..statistic_rows {
+            let mut cells = vec![if n == 0 {
+                Cell {
+                    indent: leaf.level * INDENT,
+                    ..Cell::new(0, 1, &leaf.text, Align::Left)
+                }
+            } else {
+                Cell::blank(0)
+            }];
+            cells.push(Cell::new(
+                1,
+                1,
+                labels.get(n).map_or("", String::as_str),
+                Align::Left,
+            ));
+            cells.extend(block.clone().map(|column| {
+                let value = row_values
+                    .get(n)
+                    .and_then(|v| v.get(column))
+                    .map_or("", String::as_str);
+                Cell::new(STUB + column - block.start, 1, value, Align::Right)
+            }));
+            lines.push(Line {
+                rule: std::mem::replace(&mut rule, vec![false; count]),
+                cells,
+            });
+        }
+    }
+    lines
+}
+
+/// Draw lines of cells with borders, closing with a full rule
+fn draw(lines: &[Line], widths: &[usize], style: BoxStyle) -> String {
+    let count = widths.len();
+    let boundaries = |line: Option<&Line>| -> Vec<bool> {
+        let mut present = vec![false; count + 1];
+        if let Some(line) = line {
+            for cell in &line.cells {
+                present[cell.start] = true;
+            }
+            present[count] = true;
+        }
+        present
+    };
+
+    let mut out = String::new();
+    let mut above = boundaries(None);
+    let closing = vec![true; count];
+    for index in 0..=lines.len() {
+        let line = lines.get(index);
+        let below = boundaries(line);
+        let rule = line.map_or(&closing, |l| &l.rule);
+        if rule.iter().any(|&r| r) {
+            for boundary in 0..=count {
+                out.push(junction(
+                    style,
+                    above[boundary],
+                    below[boundary],
+                    boundary > 0 && rule[boundary - 1],
+                    boundary < count && rule[boundary],
+                ));
+                if boundary < count {
+                    let fill = if rule[boundary] {
+                        horizontal(style)
+                    } else {
+                        ' '
+                    };
+                    out.extend(std::iter::repeat_n(fill, widths[boundary] + 2));
+                }
+            }
+            out.push('\n');
+        }
+
+        if let Some(line) = line {
+            let wrapped: Vec<(usize, Vec<String>)> = line
+                .cells
+                .iter()
+                .map(|cell| {
+                    let span_width = widths[cell.start..cell.start + cell.span]
+                        .iter()
+                        .sum::<usize>()
+                        + 3 * (cell.span - 1);
+                    let text_width = span_width.saturating_sub(cell.indent).max(1);
+                    let lines = wrap(&cell.text, text_width)
+                        .into_iter()
+                        .map(|l| format!("{}{}", " ".repeat(cell.indent), l))
+                        .collect();
+                    (span_width, lines)
+                })
+                .collect();
+            let height = wrapped.iter().map(|(_, l)| l.len()).max().unwrap_or(1);
+            for n in 0..height {
+                out.push(vertical(style));
+                for (cell, (span_width, lines)) in line.cells.iter().zip(&wrapped) {
+                    let text = lines.get(n).map_or("", String::as_str);
+                    out.push(' ');
+                    out.push_str(&pad(text, *span_width, cell.align));
+                    out.push(' ');
+                    out.push(vertical(style));
+                }
+                out.push('\n');
+            }
+        }
+        above = below;
+    }
+    out
+}
+
+fn horizontal(style: BoxStyle) -> char {
+    match style {
+        BoxStyle::Ascii => '-',
+        BoxStyle::Unicode => '─',
+    }
+}
+
+fn vertical(style: BoxStyle) -> char {
+    match style {
+        BoxStyle::Ascii => '|',
+        BoxStyle::Unicode => '│',
+    }
+}
+
+/// Border character where a rule meets the vertical lines above and below it
+fn junction(style: BoxStyle, up: bool, down: bool, left: bool, right: bool) -> char {
+    if style == BoxStyle::Ascii {
+        return match (up || down, left || right) {
+            (true, true) => '+',
+            (true, false) => '|',
+            (false, true) => '-',
+            (false, false) => ' ',
+        };
+    }
+    match (up, down, left, right) {
+        (true, true, true, true) => '┼',
+        (true, true, true, false) => '┤',
+        (true, true, false, true) => '├',
+        (true, false, true, true) => '┴',
+        (false, true, true, true) => '┬',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, false) | (true, false, false, false) | (false, true, false, false) => {
+            '│'
+        }
+        (false, false, false, false) => ' ',
+        (false, false, _, _) => '─',
+    }
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn pad(text: &str, width: usize, align: Align) -> String {
+    let gap = width.saturating_sub(self::width(text));
+    match align {
+        Align::Left => format!("{}{}", text, " ".repeat(gap)),
+        Align::Right => format!("{}{}", " ".repeat(gap), text),
+        Align::Center => format!(
+            "{}{}{}",
+            " ".repeat(gap / 2),
+            text,
+            " ".repeat(gap - gap / 2)
+        ),
+    }
+}
+
+/// Wrap text at whitespace, breaking words longer than the width
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !current.is_empty() && self::width(&current) + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        if !word.is_empty() {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.extend(word);
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
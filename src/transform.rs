@@ -0,0 +1,429 @@
+use crate::lookup::{Axis, LookupError, Selector};
+use crate::types::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Direction of [`Table::sort_rows_by`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+/// How [`Table::sort_rows_by`] orders rows
+#[derive(Debug, Clone, Default)]
+pub struct SortOptions {
+    pub order: SortOrder,
+
+    /// Label patterns of elements that stay at the end of their group, in their original
+    /// order, e.g. "Other*" or "Don't know"; `*` matches any text and case is ignored
+    pub anchors: Vec<String>,
+}
+
+impl From<SortOrder> for SortOptions {
+    fn from(order: SortOrder) -> SortOptions {
+        SortOptions {
+            order,
+            ..SortOptions::default()
+        }
+    }
+}
+
+impl Table {
+    /// A copy with the row and column edges swapped and each statistic's matrix transposed
+    ///
+    /// Compressed runs of cells are expanded first, so every new row has a cell per
+    /// column; cells past the end of a row without any cells become missing. Group trees
+    /// move unchanged, keeping their nesting and positions.
+    pub fn transpose(&self) -> Table {
+        let rows = self.data.rows.len();
+        let columns = self.column_count();
+        let missing = || DataCell {
+            is_missing: true,
+            ..DataCell::default()
+        };
+
+        let data_rows = (0..columns)
+            .map(|column| DataRow {
+                data_row_series: (0..self.statistics.len())
+                    .map(|s| DataRowSeries {
+                        statistic: Some(self.statistics[s].clone()),
+                        cells: (0..rows)
+                            .map(|row| {
+                                self.data.rows[row]
+                                    .data_row_series
+                                    .get(s)
+                                    .and_then(|series| series.cell(column))
+                                    .cloned()
+                                    .unwrap_or_else(missing)
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Table {
+            name: self.name.clone(),
+            title: self.title.clone(),
+            title_alt_texts: self.title_alt_texts.clone(),
+            controls: self.controls.clone(),
+            row_edge: self.column_edge.as_ref().map(|e| with_axis(e, "r")),
+            column_edge: self.row_edge.as_ref().map(|e| with_axis(e, "c")),
+            statistics: self.statistics.clone(),
+            data: TableData { rows: data_rows },
+        }
+    }
+
+    /// A copy with only the given data rows, columns and statistics
+    ///
+    /// Rows and columns keep their document order, since edges are trees; statistics come
+    /// in the given order. Edges are pruned to the kept leaves, dropping groups left
+    /// empty, and compressed runs of cells are expanded.
+    pub fn subset(&self, rows: &[usize], columns: &[usize], statistics: &[usize]) -> Table {
+        let rows = sorted(rows);
+        let columns = sorted(columns);
+        let statistics: Vec<usize> = statistics
+            .iter()
+            .copied()
+            .filter(|&i| i < self.statistics.len())
+            .collect();
+
+        let data_rows = rows
+            .iter()
+            .filter_map(|&r| self.data.rows.get(r))
+            .map(|row| DataRow {
+                data_row_series: statistics
+                    .iter()
+                    .filter_map(|&s| row.data_row_series.get(s))
+                    .map(|series| DataRowSeries {
+                        statistic: series.statistic.clone(),
+                        cells: columns
+                            .iter()
+                            .map(|&c| {
+                                series.cell(c).cloned().unwrap_or(DataCell {
+                                    is_missing: true,
+                                    ..DataCell::default()
+                                })
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Table {
+            name: self.name.clone(),
+            title: self.title.clone(),
+            title_alt_texts: self.title_alt_texts.clone(),
+            controls: self.controls.clone(),
+            row_edge: self.row_edge.as_ref().map(|e| prune_edge(e, &rows)),
+            column_edge: self.column_edge.as_ref().map(|e| prune_edge(e, &columns)),
+            statistics: statistics
+                .iter()
+                .map(|&i| self.statistics[i].clone())
+                .collect(),
+            data: TableData { rows: data_rows },
+        }
+    }
+
+    /// A copy with only the rows, columns and statistics given, `None` keeping all
+    ///
+    /// Rows and columns are located as by [`Table::locate`], so each selector must match
+    /// exactly one leaf, and keep their document order; statistics are named by type and
+    /// come in the given order. Groups left without leaves are dropped, the headings of
+    /// the others kept, and controls are carried over. See [`Table::subset`] for positions.
+    pub fn select(
+        &self,
+        rows: Option<&[Selector]>,
+        columns: Option<&[Selector]>,
+        statistics: Option<&[&str]>,
+    ) -> Result<Table, LookupError> {
+        let locate = |axis: Axis, selectors: Option<&[Selector]>, count: usize| match selectors {
+            Some(selectors) => selectors
+                .iter()
+                .map(|s| self.locate(axis, s))
+                .collect::<Result<Vec<_>, _>>(),
+            None => Ok((0..count).collect()),
+        };
+        let rows = locate(Axis::Rows, rows, self.data.rows.len())?;
+        let columns = locate(Axis::Columns, columns, self.column_count())?;
+        let statistics = match statistics {
+            Some(names) => names
+                .iter()
+                .map(|&name| {
+                    self.statistics
+                        .iter()
+                        .position(|s| s.r#type == name)
+                        .ok_or_else(|| LookupError::UnknownStatistic(name.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => (0..self.statistics.len()).collect(),
+        };
+        Ok(self.subset(&rows, &columns, &statistics))
+    }
+
+    /// A copy with the elements of each row group sorted by a statistic in a column
+    ///
+    /// ```no_run
+    /// # use libxtabml::{SortOrder, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("brands.xte")?;
+    /// let table = &xtab.tables[0];
+    /// let sorted = table.sort_rows_by("Total", "ColumnPercent", SortOrder::Descending)?;
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Elements only move within their own group, and summaries and NETs, including
+    /// elements labelled "NET" as Q exports them, keep their places. Rows without a numeric value go after the others, then the anchored ones;
+    /// ties keep their order. The row edge and every statistic's data move together.
+    pub fn sort_rows_by(
+        &self,
+        column: impl Into<Selector>,
+        statistic: &str,
+        options: impl Into<SortOptions>,
+    ) -> Result<Table, LookupError> {
+        let options = options.into();
+        let column = self.locate(Axis::Columns, &column.into())?;
+        let series = self
+            .statistics
+            .iter()
+            .position(|s| s.r#type == statistic)
+            .ok_or_else(|| LookupError::UnknownStatistic(statistic.to_string()))?;
+        let Some(edge) = &self.row_edge else {
+            return Ok(self.clone());
+        };
+
+        let rows = self.data.rows.len();
+        let index: HashMap<*const (), usize> = edge
+            .leaves()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i < rows)
+            .map(|(i, leaf)| (address(leaf.node), i))
+            .collect();
+        let value = |row: usize| {
+            self.data.rows[row]
+                .data_row_series
+                .get(series)
+                .and_then(|s| s.cell(column))
+                .and_then(DataCell::as_f64)
+        };
+        let anchored = |element: &Element| {
+            options
+                .anchors
+                .iter()
+                .any(|pattern| glob_matches(pattern, &element.text))
+        };
+
+        // order[new row] = old row
+        let mut order: Vec<usize> = (0..rows).collect();
+        each_group(&edge.groups, &mut |group| {
+            let mut elements: Vec<(usize, bool)> = group
+                .elements
+                .iter()
+                .filter(|e| !EdgeNode::Element(e).is_net())
+                .filter_map(|e| Some((*index.get(&address(EdgeNode::Element(e)))?, anchored(e))))
+                .collect();
+            elements.sort_by_key(|&(row, _)| row);
+            let slots: Vec<usize> = elements.iter().map(|&(row, _)| row).collect();
+            elements.sort_by(|&(a, anchored_a), &(b, anchored_b)| {
+                anchored_a.cmp(&anchored_b).then_with(|| {
+                    if anchored_a {
+                        return Ordering::Equal;
+                    }
+                    match (value(a), value(b)) {
+                        (Some(x), Some(y)) => {
+                            let ordering = x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+                            match options.order {
+                                SortOrder::Ascending => ordering,
+                                SortOrder::Descending => ordering.reverse(),
+                            }
+                        }
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }
+                })
+            });
+            for (slot, (row, _)) in slots.into_iter().zip(elements) {
+                order[slot] = row;
+            }
+        });
+
+        let mut new_row = vec![0; rows];
+        for (new, &old) in order.iter().enumerate() {
+            new_row[old] = new;
+        }
+        let position = |node: EdgeNode<'_>, old: Option<usize>| {
+            index
+                .get(&address(node))
+                .map_or(old, |&row| Some(new_row[row]))
+        };
+
+        let mut table = self.clone();
+        table.row_edge = Some(Edge {
+            axis: edge.axis.clone(),
+            name: edge.name.clone(),
+            groups: edge
+                .groups
+                .iter()
+                .map(|g| reorder_group(g, &position))
+                .collect(),
+        });
+        table.data.rows = order
+            .iter()
+            .map(|&old| self.data.rows[old].clone())
+            .collect();
+        Ok(table)
+    }
+}
+
+/// Call `f` on every group of a tree, parents first
+fn each_group<'a>(groups: &'a [Group], f: &mut impl FnMut(&'a Group)) {
+    for group in groups {
+        f(group);
+        each_group(&group.groups, f);
+    }
+}
+
+/// A copy of a group with new leaf positions, its elements in their new order
+pub(crate) fn reorder_group(
+    group: &Group,
+    position: &impl Fn(EdgeNode<'_>, Option<usize>) -> Option<usize>,
+) -> Group {
+    let mut elements: Vec<Element> = group
+        .elements
+        .iter()
+        .map(|e| Element {
+            position: position(EdgeNode::Element(e), e.position),
+            ..e.clone()
+        })
+        .collect();
+    elements.sort_by_key(|e| e.position);
+    Group {
+        elements,
+        summaries: group
+            .summaries
+            .iter()
+            .map(|s| Summary {
+                position: position(EdgeNode::Summary(s), s.position),
+                ..s.clone()
+            })
+            .collect(),
+        groups: group
+            .groups
+            .iter()
+            .map(|g| reorder_group(g, position))
+            .collect(),
+        ..group.clone()
+    }
+}
+
+/// Case-insensitive match of a pattern where `*` stands for any text
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and of the text it was tried against
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn sorted(positions: &[usize]) -> Vec<usize> {
+    let mut positions = positions.to_vec();
+    positions.sort_unstable();
+    positions.dedup();
+    positions
+}
+
+/// Address of a leaf, to find it again while walking the tree
+pub(crate) fn address(node: EdgeNode<'_>) -> *const () {
+    match node {
+        EdgeNode::Element(e) => e as *const Element as *const (),
+        EdgeNode::Summary(s) => s as *const Summary as *const (),
+    }
+}
+
+/// A copy of an edge with only the leaves at the given sorted positions, renumbered
+fn prune_edge(edge: &Edge, keep: &[usize]) -> Edge {
+    let leaves = edge.leaves();
+    let positions: HashMap<*const (), usize> = keep
+        .iter()
+        .filter_map(|&i| leaves.get(i))
+        .enumerate()
+        .map(|(position, leaf)| (address(leaf.node), position))
+        .collect();
+    Edge {
+        axis: edge.axis.clone(),
+        name: edge.name.clone(),
+        groups: edge
+            .groups
+            .iter()
+            .filter_map(|g| prune_group(g, &positions))
+            .collect(),
+    }
+}
+
+fn prune_group(group: &Group, positions: &HashMap<*const (), usize>) -> Option<Group> {
+    let elements: Vec<Element> = group
+        .elements
+        .iter()
+        .filter_map(|e| {
+            let position = *positions.get(&address(EdgeNode::Element(e)))?;
+            Some(Element {
+                position: Some(position),
+                ..e.clone()
+            })
+        })
+        .collect();
+    let summaries: Vec<Summary> = group
+        .summaries
+        .iter()
+        .filter_map(|s| {
+            let position = *positions.get(&address(EdgeNode::Summary(s)))?;
+            Some(Summary {
+                position: Some(position),
+                ..s.clone()
+            })
+        })
+        .collect();
+    let groups: Vec<Group> = group
+        .groups
+        .iter()
+        .filter_map(|g| prune_group(g, positions))
+        .collect();
+    if elements.is_empty() && summaries.is_empty() && groups.is_empty() {
+        return None;
+    }
+    Some(Group {
+        name: group.name.clone(),
+        text: group.text.clone(),
+        alt_texts: group.alt_texts.clone(),
+        elements,
+        summaries,
+        groups,
+    })
+}
+
+fn with_axis(edge: &Edge, axis: &str) -> Edge {
+    Edge {
+        axis: axis.to_string(),
+        ..edge.clone()
+    }
+}
@@ -33,12 +33,23 @@ pub struct Language {
     pub description: String,
 }
 
+/// Text in an alternative language, `<a lang="fr">Oui</a>` inside a `<t>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AltText {
+    pub lang: String,
+    pub text: String,
+}
+
 /// Control type definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlType {
     pub name: String,
     pub status: Option<String>,
     pub text: String,
+
+    /// Alternative texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
 }
 
 /// Statistic type definition
@@ -46,6 +57,10 @@ pub struct ControlType {
 pub struct StatisticType {
     pub name: String,
     pub text: String,
+
+    /// Alternative texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
 }
 
 /// Control element (metadata)
@@ -53,6 +68,10 @@ pub struct StatisticType {
 pub struct Control {
     pub r#type: String,
     pub text: String,
+
+    /// Alternative texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
 }
 
 /// A table in the XtabML document
@@ -61,6 +80,10 @@ pub struct Table {
     pub name: Option<String>,
     pub title: String,
 
+    /// Alternative titles in other languages
+    #[serde(default)]
+    pub title_alt_texts: Vec<AltText>,
+
     /// Controls specific to this table (e.g., weight, base)
     pub controls: Vec<Control>,
 
@@ -81,14 +104,27 @@ pub struct Table {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edge {
     pub axis: String, // "r" for row, "c" for column
+    pub name: Option<String>,
     pub groups: Vec<Group>,
 }
 
 /// A group within an edge
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Group {
+    pub name: Option<String>,
+
+    /// Heading text of the group, if any
+    pub text: Option<String>,
+
+    /// Alternative heading texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
+
     pub elements: Vec<Element>,
     pub summaries: Vec<Summary>,
+
+    /// Nested groups
+    pub groups: Vec<Group>,
 }
 
 /// An element (item) in a group
@@ -96,18 +132,162 @@ pub struct Group {
 pub struct Element {
     pub text: String,
     pub index: Option<i32>,
+    pub name: Option<String>,
+
+    /// Alternative texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
+
+    /// Position among the elements and summaries of the edge, i.e. the data row or column
+    pub position: Option<usize>,
 }
 
 /// A summary element
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Summary {
     pub text: String,
+    pub name: Option<String>,
+    pub r#type: Option<String>,
+
+    /// Alternative texts in other languages
+    #[serde(default)]
+    pub alt_texts: Vec<AltText>,
+
+    /// Position among the elements and summaries of the edge, i.e. the data row or column
+    pub position: Option<usize>,
+}
+
+/// An element or summary at the bottom of an edge tree
+#[derive(Debug, Clone, Copy)]
+pub enum EdgeNode<'a> {
+    Element(&'a Element),
+    Summary(&'a Summary),
+}
+
+impl<'a> EdgeNode<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            EdgeNode::Element(e) => &e.text,
+            EdgeNode::Summary(s) => &s.text,
+        }
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        match self {
+            EdgeNode::Element(e) => e.name.as_deref(),
+            EdgeNode::Summary(s) => s.name.as_deref(),
+        }
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            EdgeNode::Element(e) => e.position,
+            EdgeNode::Summary(s) => s.position,
+        }
+    }
+
+    pub fn is_summary(&self) -> bool {
+        matches!(self, EdgeNode::Summary(_))
+    }
+
+    /// Whether this is a NET, either a `type="net"` summary or an element labelled "NET"
+    pub fn is_net(&self) -> bool {
+        let r#type = match self {
+            EdgeNode::Element(_) => None,
+            EdgeNode::Summary(s) => s.r#type.as_deref(),
+        };
+        is_net(r#type, self.text())
+    }
+}
+
+/// The NET rule shared by edge nodes and header cells: Q exports NETs as elements
+/// labelled "NET", other systems as summaries of type "net"
+pub(crate) fn is_net(r#type: Option<&str>, text: &str) -> bool {
+    r#type.is_some_and(|t| t.eq_ignore_ascii_case("net")) || text.trim().eq_ignore_ascii_case("net")
+}
+
+/// A leaf of an edge together with the groups enclosing it, outermost first
+#[derive(Debug, Clone)]
+pub struct EdgeLeaf<'a> {
+    pub node: EdgeNode<'a>,
+    pub groups: Vec<&'a Group>,
+}
+
+impl<'a> EdgeLeaf<'a> {
+    pub fn text(&self) -> &'a str {
+        self.node.text()
+    }
+
+    /// Headings of the enclosing groups, outermost first, skipping groups without one
+    pub fn headings(&self) -> Vec<&'a str> {
+        self.groups
+            .iter()
+            .filter_map(|g| g.text.as_deref())
+            .collect()
+    }
+}
+
+impl Edge {
+    /// All elements and summaries of the edge in data order
+    ///
+    /// Leaves are ordered by their `position` where the parser recorded one, otherwise
+    /// in tree order.
+    pub fn leaves(&self) -> Vec<EdgeLeaf<'_>> {
+        let mut leaves = Vec::new();
+        for group in &self.groups {
+            collect_leaves(group, &mut Vec::new(), &mut leaves);
+        }
+        leaves.sort_by_key(|leaf| leaf.node.position().unwrap_or(usize::MAX));
+        leaves
+    }
+
+    /// Number of data rows or columns described by this edge
+    pub fn leaf_count(&self) -> usize {
+        self.groups.iter().map(Group::leaf_count).sum()
+    }
+}
+
+impl Group {
+    /// Number of elements and summaries in this group and its nested groups
+    pub fn leaf_count(&self) -> usize {
+        self.elements.len()
+            + self.summaries.len()
+            + self.groups.iter().map(Group::leaf_count).sum::<usize>()
+    }
+}
+
+fn collect_leaves<'a>(
+    group: &'a Group,
+    ancestors: &mut Vec<&'a Group>,
+    leaves: &mut Vec<EdgeLeaf<'a>>,
+) {
+    ancestors.push(group);
+    for element in &group.elements {
+        leaves.push(EdgeLeaf {
+            node: EdgeNode::Element(element),
+            groups: ancestors.clone(),
+        });
+    }
+    for summary in &group.summaries {
+        leaves.push(EdgeLeaf {
+            node: EdgeNode::Summary(summary),
+            groups: ancestors.clone(),
+        });
+    }
+    for child in &group.groups {
+        collect_leaves(child, ancestors, leaves);
+    }
+    ancestors.pop();
 }
 
 /// Statistic specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Statistic {
     pub r#type: String,
+
+    /// Declared `datatype`: "integer", "decimal", "string" or "percentage"
+    #[serde(default)]
+    pub datatype: Option<String>,
 }
 
 /// Table data matrix
@@ -123,6 +303,16 @@ pub struct DataRowSeries {
     pub cells: Vec<DataCell>,
 }
 
+impl DataRowSeries {
+    /// Cell at a column position
+    ///
+    /// Exporters may compress a trailing run of identical cells into a single cell, so
+    /// positions past the end resolve to the last cell.
+    pub fn cell(&self, column: usize) -> Option<&DataCell> {
+        self.cells.get(column).or(self.cells.last())
+    }
+}
+
 /// A row in the data matrix
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataRow {
@@ -130,18 +320,29 @@ pub struct DataRow {
 }
 
 /// A cell in the data matrix
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DataCell {
     pub value: Option<String>,
+
+    /// No applicable value (`<x>`, or `<n>` where the cell still takes its place)
     pub is_missing: bool,
+
+    /// Value present but not intended for printing (`<h>`)
+    #[serde(default)]
+    pub is_hidden: bool,
 }
 
-impl Default for DataCell {
-    fn default() -> Self {
-        Self {
-            value: None,
-            is_missing: false,
+impl DataCell {
+    /// Numeric value of the cell, ignoring percent signs, thousands separators and padding
+    ///
+    /// Returns `None` for missing cells and for placeholders such as `" - "`.
+    pub fn as_f64(&self) -> Option<f64> {
+        if self.is_missing {
+            return None;
         }
+        let value = self.value.as_deref()?.trim();
+        let value = value.strip_suffix('%').unwrap_or(value).replace(',', "");
+        value.trim().parse().ok()
     }
 }
 
@@ -152,6 +353,17 @@ pub struct StatisticData {
     pub values: Vec<Vec<Option<String>>>,
 }
 
+impl XtabML {
+    /// Display text of a declared statistic type, e.g. "Column %" for "ColumnPercent"
+    pub fn statistic_text(&self, name: &str) -> Option<&str> {
+        self.statistic_types
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.text.as_str())
+            .filter(|t| !t.is_empty())
+    }
+}
+
 impl Table {
     /// Get all statistic types in this table
     pub fn statistic_types(&self) -> Vec<&str> {
@@ -205,8 +417,7 @@ impl Table {
     pub fn row_labels(&self) -> Vec<String> {
         self.row_edge
             .as_ref()
-            .and_then(|e| e.groups.first())
-            .map(|g| g.elements.iter().map(|e| e.text.clone()).collect())
+            .map(|e| e.leaves().iter().map(|l| l.text().to_string()).collect())
             .unwrap_or_default()
     }
 
@@ -214,8 +425,7 @@ impl Table {
     pub fn column_labels(&self) -> Vec<String> {
         self.column_edge
             .as_ref()
-            .and_then(|e| e.groups.first())
-            .map(|g| g.elements.iter().map(|e| e.text.clone()).collect())
+            .map(|e| e.leaves().iter().map(|l| l.text().to_string()).collect())
             .unwrap_or_default()
     }
 }
@@ -0,0 +1,401 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+/// How serious a diagnostic is, ordered so that `Warning < Error`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A structural check run by [`validate_bytes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// The document is not well-formed XML
+    Xml,
+
+    /// A table has no `<edge axis="c">`
+    MissingColumnEdge,
+
+    /// The number of `<r>` in a table differs from the leaves of its row edge
+    RowCount,
+
+    /// The number of `<c>` in a row differs from the statistics of its table
+    SeriesCount,
+
+    /// A `<c>` has more cells than the column edge has leaves, or none at all
+    CellCount,
+
+    /// A `<statistic>` uses a type without a `<statistictype>` declaration
+    UndeclaredStatisticType,
+
+    /// A `<control>` uses a type without a `<controltype>` declaration
+    UndeclaredControlType,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::Xml,
+        Rule::MissingColumnEdge,
+        Rule::RowCount,
+        Rule::SeriesCount,
+        Rule::CellCount,
+        Rule::UndeclaredStatisticType,
+        Rule::UndeclaredControlType,
+    ];
+
+    /// Stable identifier, e.g. "row-count"
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::Xml => "xml",
+            Rule::MissingColumnEdge => "missing-column-edge",
+            Rule::RowCount => "row-count",
+            Rule::SeriesCount => "series-count",
+            Rule::CellCount => "cell-count",
+            Rule::UndeclaredStatisticType => "undeclared-statistic-type",
+            Rule::UndeclaredControlType => "undeclared-control-type",
+        }
+    }
+
+    /// One-line description of what the rule checks
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::Xml => "The document must be well-formed XML",
+            Rule::MissingColumnEdge => "Every table must have a column edge",
+            Rule::RowCount => "A table must have one <r> per leaf of its row edge",
+            Rule::SeriesCount => "A row must have one <c> per statistic of its table",
+            Rule::CellCount => {
+                "A <c> must have at least one and at most one cell per leaf of the column edge"
+            }
+            Rule::UndeclaredStatisticType => "Statistic types must be declared by <statistictype>",
+            Rule::UndeclaredControlType => "Control types must be declared by <controltype>",
+        }
+    }
+
+    /// Severity of the diagnostics reported by the rule
+    ///
+    /// Undeclared types are warnings, since the data can still be read.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::UndeclaredStatisticType | Rule::UndeclaredControlType => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// A problem found in a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+
+    /// 1-based line of the element at fault
+    pub line: usize,
+
+    /// Index of the table at fault, if any
+    pub table: Option<usize>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {}[{}]: {}",
+            self.line,
+            self.severity,
+            self.rule.id(),
+            self.message
+        )
+    }
+}
+
+/// Check the structure of an XtabML document
+///
+/// Unlike [`crate::XtabMLParser`], which reads what it can, this reports where the data
+/// disagrees with the edges and declarations. Diagnostics are sorted by line; a document
+/// that is not well-formed gets an [`Rule::Xml`] error and is checked no further.
+pub fn validate_bytes(bytes: &[u8]) -> Vec<Diagnostic> {
+    Validator::new(bytes).run()
+}
+
+/// Check the structure of an XtabML document held in a string
+pub fn validate_str(content: &str) -> Vec<Diagnostic> {
+    validate_bytes(content.as_bytes())
+}
+
+/// A table being read
+struct TableState {
+    index: usize,
+    line: usize,
+    statistics: usize,
+    row_leaves: Option<usize>,
+    column_leaves: Option<usize>,
+
+    /// Line of each `<r>` with the line and cell count of each of its `<c>`
+    rows: Vec<(usize, Vec<(usize, usize)>)>,
+}
+
+struct Validator<'a> {
+    bytes: &'a [u8],
+    line: usize,
+    counted: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Validator {
+            bytes,
+            line: 1,
+            counted: 0,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Line of a byte offset, which must not decrease between calls
+    fn line_at(&mut self, position: usize) -> usize {
+        let position = position.min(self.bytes.len());
+        if position > self.counted {
+            self.line += self.bytes[self.counted..position]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            self.counted = position;
+        }
+        self.line
+    }
+
+    fn report(&mut self, rule: Rule, line: usize, table: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            rule,
+            severity: rule.severity(),
+            message,
+            line,
+            table,
+        });
+    }
+
+    fn run(mut self) -> Vec<Diagnostic> {
+        let mut reader = Reader::from_reader(self.bytes);
+        reader.trim_text(true);
+        reader.check_end_names(true);
+        reader.check_comments(true);
+
+        let mut buf = Vec::new();
+        let mut open: Vec<Vec<u8>> = Vec::new();
+        let mut statistic_types = HashSet::new();
+        let mut control_types = HashSet::new();
+        let mut statistic_uses: Vec<(String, usize, usize)> = Vec::new();
+        let mut control_uses: Vec<(String, usize, Option<usize>)> = Vec::new();
+
+        let mut table_count = 0;
+        let mut table: Option<TableState> = None;
+        // Axis and leaf count of the open edge
+        let mut edge: Option<(String, usize)> = None;
+
+        loop {
+            let event = reader.read_event_into(&mut buf);
+            let line = self.line_at(reader.buffer_position());
+            let (e, empty) = match event {
+                Ok(Event::Start(e)) => (e, false),
+                Ok(Event::Empty(e)) => (e, true),
+                Ok(Event::End(_)) => {
+                    let name = open.pop().unwrap_or_default();
+                    match name.as_slice() {
+                        b"edge" => {
+                            if let (Some((axis, leaves)), Some(table)) = (edge.take(), &mut table) {
+                                match axis.as_str() {
+                                    "r" => table.row_leaves = Some(leaves),
+                                    "c" => table.column_leaves = Some(leaves),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        b"table" => {
+                            if let Some(table) = table.take() {
+                                self.check_table(table);
+                            }
+                        }
+                        _ => {}
+                    }
+                    buf.clear();
+                    continue;
+                }
+                Ok(Event::Eof) => {
+                    if let Some(name) = open.last() {
+                        let message = format!(
+                            "unexpected end of document inside <{}>",
+                            String::from_utf8_lossy(name)
+                        );
+                        self.report(Rule::Xml, line, None, message);
+                        return self.finish();
+                    }
+                    break;
+                }
+                Err(e) => {
+                    self.report(Rule::Xml, line, None, e.to_string());
+                    return self.finish();
+                }
+                _ => {
+                    buf.clear();
+                    continue;
+                }
+            };
+
+            let in_cell_series = open.last().map(Vec::as_slice) == Some(b"c");
+            if !empty {
+                open.push(e.name().as_ref().to_vec());
+            }
+            let in_table = table.as_ref().map(|t| t.index);
+            match e.name().as_ref() {
+                b"statistictype" => {
+                    statistic_types.insert(attribute(&e, b"name"));
+                }
+                b"controltype" => {
+                    control_types.insert(attribute(&e, b"name"));
+                }
+                b"control" => control_uses.push((attribute(&e, b"type"), line, in_table)),
+                b"table" => {
+                    table = Some(TableState {
+                        index: table_count,
+                        line,
+                        statistics: 0,
+                        row_leaves: None,
+                        column_leaves: None,
+                        rows: Vec::new(),
+                    });
+                    table_count += 1;
+                }
+                b"edge" => edge = Some((attribute(&e, b"axis"), 0)),
+                b"element" | b"summary" => {
+                    if let Some((_, leaves)) = &mut edge {
+                        *leaves += 1;
+                    }
+                }
+                b"statistic" => {
+                    if let Some(table) = &mut table {
+                        table.statistics += 1;
+                        statistic_uses.push((attribute(&e, b"type"), line, table.index));
+                    }
+                }
+                b"r" => {
+                    if let Some(table) = &mut table {
+                        table.rows.push((line, Vec::new()));
+                    }
+                }
+                b"c" => {
+                    if let Some((_, series)) = table.as_mut().and_then(|t| t.rows.last_mut()) {
+                        series.push((line, 0));
+                    }
+                }
+                b"v" | b"n" | b"h" | b"x" if in_cell_series => {
+                    let cells = table
+                        .as_mut()
+                        .and_then(|t| t.rows.last_mut())
+                        .and_then(|(_, series)| series.last_mut());
+                    if let Some((_, cells)) = cells {
+                        *cells += 1;
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        for (name, line, table) in statistic_uses {
+            if !statistic_types.contains(&name) {
+                let message = format!(
+                    "table {} uses statistic type {:?}, which is not declared",
+                    table + 1,
+                    name
+                );
+                self.report(Rule::UndeclaredStatisticType, line, Some(table), message);
+            }
+        }
+        for (name, line, table) in control_uses {
+            if !control_types.contains(&name) {
+                let message = format!("control type {:?} is not declared", name);
+                self.report(Rule::UndeclaredControlType, line, table, message);
+            }
+        }
+        self.finish()
+    }
+
+    fn check_table(&mut self, table: TableState) {
+        let index = Some(table.index);
+        let number = table.index + 1;
+        let Some(columns) = table.column_leaves else {
+            let message = format!("table {} has no column edge", number);
+            self.report(Rule::MissingColumnEdge, table.line, index, message);
+            return;
+        };
+        if let Some(leaves) = table.row_leaves {
+            if table.rows.len() != leaves {
+                let message = format!(
+                    "table {} has {} rows of data, but its row edge has {} leaves",
+                    number,
+                    table.rows.len(),
+                    leaves
+                );
+                self.report(Rule::RowCount, table.line, index, message);
+            }
+        }
+        for (row, (line, series)) in table.rows.iter().enumerate() {
+            if series.len() != table.statistics {
+                let message = format!(
+                    "row {} of table {} has {} statistic series, but the table has {} statistics",
+                    row + 1,
+                    number,
+                    series.len(),
+                    table.statistics
+                );
+                self.report(Rule::SeriesCount, *line, index, message);
+            }
+            for &(line, cells) in series {
+                if cells > columns || (cells == 0 && columns > 0) {
+                    let message = format!(
+                        "row {} of table {} has {} cells, but the column edge has {} leaves",
+                        row + 1,
+                        number,
+                        cells,
+                        columns
+                    );
+                    self.report(Rule::CellCount, line, index, message);
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<Diagnostic> {
+        self.diagnostics.sort_by_key(|d| d.line);
+        self.diagnostics
+    }
+}
+
+/// Value of an attribute, empty when absent
+fn attribute(e: &BytesStart, key: &[u8]) -> String {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .map(|attr| {
+            attr.unescape_value()
+                .map(|value| value.into_owned())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).into_owned())
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,183 @@
+use crate::types::*;
+use crate::{Result, XtabMLError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// Renames of statistic and control types from one system's vocabulary to another's
+///
+/// In TOML:
+///
+/// ```toml
+/// [statistics]
+/// "xs:cp" = "ColumnPercent"
+/// "xs:t" = "Count"
+///
+/// [controls]
+/// wt = "weight"
+/// ```
+///
+/// Names that are already targets of a mapping count as mapped and are kept as they are.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Vocabulary {
+    /// Statistic type names and what to rename them to
+    #[serde(default)]
+    pub statistics: BTreeMap<String, String>,
+
+    /// Control type names and what to rename them to
+    #[serde(default)]
+    pub controls: BTreeMap<String, String>,
+}
+
+/// Names [`XtabML::translate`] found no mapping for
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranslationReport {
+    /// Statistic types declared or used that the vocabulary does not map, sorted
+    pub unmapped_statistics: Vec<String>,
+
+    /// Control types declared or used that the vocabulary does not map, sorted
+    pub unmapped_controls: Vec<String>,
+}
+
+impl TranslationReport {
+    /// Whether every name was mapped
+    pub fn is_complete(&self) -> bool {
+        self.unmapped_statistics.is_empty() && self.unmapped_controls.is_empty()
+    }
+}
+
+impl Vocabulary {
+    /// Read a vocabulary from JSON, an object with `statistics` and `controls` objects
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Vocabulary> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Read a vocabulary from TOML, with `[statistics]` and `[controls]` tables
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Vocabulary> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Read a vocabulary from a `.json` or `.toml` file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vocabulary> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            #[cfg(feature = "json")]
+            Some("json") => Vocabulary::from_json(&std::fs::read_to_string(path)?),
+            #[cfg(feature = "toml")]
+            Some("toml") => Vocabulary::from_toml(&std::fs::read_to_string(path)?),
+            _ => Err(XtabMLError::Unsupported(format!(
+                "vocabulary file {}; expected .json or .toml with its feature enabled",
+                path.display()
+            ))),
+        }
+    }
+
+    /// The name a statistic type becomes, `None` if it is not mapped
+    pub fn statistic<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        rename(&self.statistics, name)
+    }
+
+    /// The name a control type becomes, `None` if it is not mapped
+    pub fn control<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        rename(&self.controls, name)
+    }
+}
+
+fn rename<'a>(map: &'a BTreeMap<String, String>, name: &'a str) -> Option<&'a str> {
+    match map.get(name) {
+        Some(target) => Some(target),
+        None => map.values().any(|t| t == name).then_some(name),
+    }
+}
+
+impl XtabML {
+    /// A copy using another vocabulary of statistic and control types
+    ///
+    /// ```no_run
+    /// # use libxtabml::{Vocabulary, XtabMLParser};
+    /// # let xtab = XtabMLParser::parse_file("agency.xte")?;
+    /// let vocabulary = Vocabulary::from_file("q.toml")?;
+    /// let (translated, report) = xtab.translate(&vocabulary)?;
+    /// for name in &report.unmapped_statistics {
+    ///     eprintln!("no mapping for statistic {}", name);
+    /// }
+    /// # Ok::<(), libxtabml::XtabMLError>(())
+    /// ```
+    ///
+    /// Renames the `statistictype` and `controltype` declarations, and the statistics and
+    /// controls of the document and its tables. Declarations that come to share a name are
+    /// merged, keeping the first. Unmapped names are kept and listed in the report. Fails
+    /// if two statistics of one table would get the same name.
+    pub fn translate(&self, vocabulary: &Vocabulary) -> Result<(XtabML, TranslationReport)> {
+        let mut unmapped_statistics = BTreeSet::new();
+        let mut unmapped_controls = BTreeSet::new();
+        let mut statistic = |name: &str| match vocabulary.statistic(name) {
+            Some(target) => target.to_string(),
+            None => {
+                unmapped_statistics.insert(name.to_string());
+                name.to_string()
+            }
+        };
+        let mut control = |name: &str| match vocabulary.control(name) {
+            Some(target) => target.to_string(),
+            None => {
+                unmapped_controls.insert(name.to_string());
+                name.to_string()
+            }
+        };
+
+        let mut xtab = self.clone();
+        let mut declared = BTreeSet::new();
+        xtab.statistic_types.retain_mut(|declaration| {
+            declaration.name = statistic(&declaration.name);
+            declared.insert(declaration.name.clone())
+        });
+        declared.clear();
+        xtab.control_types.retain_mut(|declaration| {
+            declaration.name = control(&declaration.name);
+            declared.insert(declaration.name.clone())
+        });
+        for c in xtab.controls.iter_mut() {
+            c.r#type = control(&c.r#type);
+        }
+
+        for table in xtab.tables.iter_mut() {
+            for c in table.controls.iter_mut() {
+                c.r#type = control(&c.r#type);
+            }
+            let mut sources: HashMap<String, String> = HashMap::new();
+            for s in table.statistics.iter_mut() {
+                let target = statistic(&s.r#type);
+                if let Some(other) = sources.insert(target.clone(), s.r#type.clone()) {
+                    return Err(XtabMLError::Translation(format!(
+                        "statistics {:?} and {:?} of table {:?} would both become {:?}",
+                        other,
+                        s.r#type,
+                        table.name.as_deref().unwrap_or(&table.title),
+                        target
+                    )));
+                }
+                s.r#type = target;
+            }
+            for row in table.data.rows.iter_mut() {
+                for series in row.data_row_series.iter_mut() {
+                    if let Some(s) = series.statistic.as_mut() {
+                        s.r#type = statistic(&s.r#type);
+                    }
+                }
+            }
+        }
+
+        let report = TranslationReport {
+            unmapped_statistics: unmapped_statistics.into_iter().collect(),
+            unmapped_controls: unmapped_controls.into_iter().collect(),
+        };
+        Ok((xtab, report))
+    }
+}
@@ -0,0 +1,151 @@
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use crate::csv::LONG_COLUMNS;
+use crate::flat::{FlatOptions, Layout};
+use crate::{types::*, Result};
+
+/// Longest sheet name Excel accepts
+const SHEET_NAME_LENGTH: usize = 31;
+
+impl XtabML {
+    /// Export the selected tables as an Excel workbook
+    ///
+    /// The long layout is a single "Data" sheet with one record per cell. In the wide layout
+    /// every table gets its own sheet, titled in the first row. Numeric values are written
+    /// as numbers; percentages like "8.5%" become 0.085 formatted as a percentage.
+    pub fn to_xlsx(&self, options: &FlatOptions) -> Result<Vec<u8>> {
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+
+        match options.layout {
+            Layout::Long => {
+                let sheet = workbook.add_worksheet().set_name("Data")?;
+                for (column, name) in LONG_COLUMNS.iter().enumerate() {
+                    sheet.write_string_with_format(0, column as u16, *name, &bold)?;
+                }
+                for (n, record) in self.long_records(options).into_iter().enumerate() {
+                    let row = n as u32 + 1;
+                    sheet.write_number(row, 0, record.table_index as f64)?;
+                    if let Some(name) = &record.table_name {
+                        sheet.write_string(row, 1, name)?;
+                    }
+                    sheet.write_string(row, 2, &record.table_title)?;
+                    sheet.write_number(row, 3, record.row_position as f64)?;
+                    sheet.write_string(row, 4, &record.row_label)?;
+                    sheet.write_number(row, 5, record.column_position as f64)?;
+                    sheet.write_string(row, 6, &record.column_label)?;
+                    sheet.write_string(row, 7, &record.statistic)?;
+                    if let Some(value) = &record.value {
+                        sheet.write_string(row, 8, value)?;
+                    }
+                    if let Some(number) = record.number {
+                        sheet.write_number(row, 9, number)?;
+                    }
+                }
+                sheet.set_freeze_panes(1, 0)?;
+            }
+            Layout::Wide => {
+                let mut names: Vec<String> = Vec::new();
+                for table in self.wide_tables(options) {
+                    let name = sheet_name(&table.title, table.table_index, &names);
+                    let sheet = workbook.add_worksheet().set_name(&name)?;
+                    names.push(name);
+
+                    sheet.write_string_with_format(0, 0, &table.title, &bold)?;
+                    sheet.write_string_with_format(2, 0, "Row", &bold)?;
+                    sheet.write_string_with_format(2, 1, "Statistic", &bold)?;
+                    for (n, label) in table.columns.iter().enumerate() {
+                        sheet.write_string_with_format(2, n as u16 + 2, label, &bold)?;
+                    }
+                    for (n, row) in table.rows.iter().enumerate() {
+                        let line = n as u32 + 3;
+                        sheet.write_string(line, 0, &row.row_label)?;
+                        sheet.write_string(line, 1, &row.statistic)?;
+                        for (column, cell) in row.cells.iter().enumerate() {
+                            write_cell(sheet, line, column as u16 + 2, cell)?;
+                        }
+                    }
+                    sheet.set_freeze_panes(3, 2)?;
+                }
+                if names.is_empty() {
+                    workbook.add_worksheet();
+                }
+            }
+        }
+        Ok(workbook.save_to_buffer()?)
+    }
+}
+
+fn write_cell(
+    sheet: &mut Worksheet,
+    row: u32,
+    column: u16,
+    cell: &DataCell,
+) -> std::result::Result<(), XlsxError> {
+    if cell.is_missing || cell.is_hidden {
+        return Ok(());
+    }
+    let value = cell.value.as_deref().unwrap_or("").trim();
+    match cell.as_f64() {
+        Some(number) if value.ends_with('%') => {
+            let decimals = value
+                .trim_end_matches('%')
+                .split_once('.')
+                .map_or(0, |(_, d)| d.len());
+            let format = if decimals == 0 {
+                "0%".to_string()
+            } else {
+                format!("0.{}%", "0".repeat(decimals))
+            };
+            sheet.write_number_with_format(
+                row,
+                column,
+                number / 100.0,
+                &Format::new().set_num_format(&format),
+            )?;
+        }
+        Some(number) => {
+            sheet.write_number(row, column, number)?;
+        }
+        None => {
+            sheet.write_string(row, column, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// A valid, unique sheet name derived from a table title
+fn sheet_name(title: &str, index: usize, taken: &[String]) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => ' ',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let cleaned = cleaned.trim_matches('\'').trim();
+    let base = if cleaned.is_empty() {
+        format!("Table {}", index + 1)
+    } else {
+        cleaned.to_string()
+    };
+
+    let mut suffix = 1;
+    loop {
+        let tail = if suffix == 1 {
+            String::new()
+        } else {
+            format!(" ({})", suffix)
+        };
+        let head: String = base
+            .chars()
+            .take(SHEET_NAME_LENGTH - tail.chars().count())
+            .collect();
+        let name = format!("{}{}", head.trim_end(), tail);
+        if !taken.iter().any(|t| t.eq_ignore_ascii_case(&name)) {
+            return name;
+        }
+        suffix += 1;
+    }
+}
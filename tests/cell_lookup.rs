@@ -0,0 +1,124 @@
+use libxtabml::{Axis, LookupError, Selector, XtabMLError, XtabMLParser};
+
+const TELCO_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="providers">
+    <t>Main mobile provider</t>
+    <edge axis="r">
+      <group>
+        <t>Network</t>
+        <element name="telstra"><t>Telstra</t></element>
+        <element name="optus"><t>Optus</t></element>
+        <summary name="net_networks"><t>NET</t></summary>
+      </group>
+      <group>
+        <t>Reseller</t>
+        <element name="amaysim"><t>Amaysim</t></element>
+        <summary name="net_resellers"><t>NET</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <summary><t>Total</t></summary>
+      </group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>40</v><v>38</v><v>42</v></c><c><v>400</v><v>190</v><v>210</v></c></r>
+      <r><c><v>30</v><v>33</v><v>27</v></c><c><v>300</v><v>165</v><v>135</v></c></r>
+      <r><c><v>70</v><v>71</v><v>69</v></c><c><v>700</v><v>355</v><v>345</v></c></r>
+      <r><c><v>12</v></c><c><v>120</v><v>60</v><v>60</v></c></r>
+      <r><c><v>12</v><v>11</v><x/></c><c><v>120</v><v>55</v><v>65</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_cell_by_labels_paths_names_and_indices() {
+    let xtab = XtabMLParser::parse_str(TELCO_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let cell = table.cell("Optus", "Female", "ColumnPercent").unwrap();
+    assert_eq!(cell.value.as_deref(), Some("27"));
+
+    let cell = table
+        .cell(["Network", "Optus"], ["Gender", "Female"], "Count")
+        .unwrap();
+    assert_eq!(cell.value.as_deref(), Some("135"));
+
+    let cell = table
+        .cell(Selector::Name("net_resellers".to_string()), 2, "Count")
+        .unwrap();
+    assert_eq!(cell.as_f64(), Some(65.0));
+
+    // A compressed run resolves to its last cell, and missing cells are returned as such
+    let cell = table.cell("Amaysim", "Female", "ColumnPercent").unwrap();
+    assert_eq!(cell.value.as_deref(), Some("12"));
+    let cell = table
+        .cell(["Reseller", "NET"], "Female", "ColumnPercent")
+        .unwrap();
+    assert!(cell.is_missing);
+}
+
+#[test]
+fn test_cell_lookup_errors() {
+    let xtab = XtabMLParser::parse_str(TELCO_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let error = table.cell("NET", "Total", "Count").unwrap_err();
+    assert_eq!(
+        error,
+        LookupError::Ambiguous {
+            axis: Axis::Rows,
+            selector: "\"NET\"".to_string(),
+            matches: vec![2, 4],
+            labels: vec!["Network / NET".to_string(), "Reseller / NET".to_string()],
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "\"NET\" matches 2 rows: Network / NET, Reseller / NET"
+    );
+
+    assert_eq!(
+        table
+            .cell("Vodafone", "Total", "Count")
+            .unwrap_err()
+            .to_string(),
+        "no row matches \"Vodafone\""
+    );
+    assert!(matches!(
+        table.cell("Optus", 3, "Count"),
+        Err(LookupError::NotFound {
+            axis: Axis::Columns,
+            ..
+        })
+    ));
+    assert_eq!(
+        table.cell("Optus", "Male", "RowPercent").unwrap_err(),
+        LookupError::UnknownStatistic("RowPercent".to_string())
+    );
+
+    let error: XtabMLError = table.cell(["Female"], "Male", "Count").unwrap_err().into();
+    assert_eq!(
+        error.to_string(),
+        "Lookup failed: no row matches \"Female\""
+    );
+}
+
+#[test]
+fn test_locate() {
+    let xtab = XtabMLParser::parse_str(TELCO_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    assert_eq!(table.locate(Axis::Rows, &"Amaysim".into()), Ok(3));
+    assert_eq!(table.locate(Axis::Rows, &["Reseller", "NET"].into()), Ok(4));
+    assert_eq!(table.locate(Axis::Columns, &["Total"].into()), Ok(0));
+    assert_eq!(table.locate(Axis::Columns, &Selector::Index(2)), Ok(2));
+}
@@ -0,0 +1,191 @@
+use libxtabml::{StandardStatistic, StatisticRegistry, XtabMLError, XtabMLParser};
+use std::path::Path;
+
+#[macro_use]
+mod common;
+
+use common::row;
+
+const CROSSTAB_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Own a smartwatch by age</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+        <element><t>Refused</t></element>
+        <summary type="net"><t>Total</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Age</t>
+        <element><t>18-34</t></element>
+        <element><t>35-54</t></element>
+        <element><t>55+</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="ColumnPercent"/>
+    <data>
+      <r>
+        <c><v>100</v><v>20</v><v>30</v><v>50</v></c>
+        <c><v>62.5</v><v>40</v><v>60</v><v>83.3</v></c>
+      </r>
+      <r>
+        <c><v>60</v><v>30</v><v>20</v><v>10</v></c>
+        <c><v>37.5</v><v>60</v><v>40</v><v>16.7</v></c>
+      </r>
+      <r>
+        <c><v>0</v></c>
+        <c><v>0</v></c>
+      </r>
+      <r>
+        <c><v>160</v><v>50</v><v>50</v><v>60</v></c>
+        <c><v>100</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Preferred brand by region</t>
+    <edge axis="r">
+      <group>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <element><t>Brand C</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+        <element><t>West</t></element>
+      </group>
+    </edge>
+    <statistic type="n"/>
+    <data>
+      <r><c><v>10</v><v>20</v><v>30</v></c></r>
+      <r><c><v>20</v><v>20</v><v>20</v></c></r>
+      <r><c><v>30</v><v>20</v><v>5</v></c></r>
+    </data>
+  </table>
+  <table name="q3">
+    <t>Q3. Average spend</t>
+    <edge axis="r"><group><element><t>Spend</t></element></group></edge>
+    <edge axis="c"><group><summary><t>Total</t></summary></group></edge>
+    <statistic type="Average"/>
+    <data><r><c><v>12.5</v></c></r></data>
+  </table>
+  <table name="q4">
+    <t>Q4. Would you recommend us?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Freq"/>
+    <data>
+      <r><c><v>120</v><v>80</v></c></r>
+      <r><c><v>80</v><v>120</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_chi_square() {
+    let xtab = XtabMLParser::parse_str(CROSSTAB_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let test = table.chi_square(&StatisticRegistry::standard()).unwrap();
+
+    // Summaries and the empty Refused row are left out
+    assert_eq!(test.rows, vec![0, 1]);
+    assert_eq!(test.columns, vec![1, 2, 3]);
+    assert_eq!(test.degrees_of_freedom, 2);
+    assert!((test.statistic - 22.044444).abs() < 1e-6);
+    // With two degrees of freedom, p = exp(-x / 2)
+    assert!((test.p_value - (-test.statistic / 2.0).exp()).abs() < 1e-9);
+    assert_eq!(test.expected[0], vec![31.25, 31.25, 37.5]);
+
+    let annotated = test.annotate(table);
+    assert_eq!(
+        annotated.statistic_types(),
+        vec!["Count", "ColumnPercent", "ExpectedN", "Residual"]
+    );
+    assert_eq!(
+        row(&annotated, 0, "ExpectedN"),
+        vec!["x", "31.25", "31.25", "37.50"]
+    );
+    assert_eq!(
+        row(&annotated, 1, "Residual"),
+        vec!["x", "2.60", "0.29", "-2.64"]
+    );
+    assert_eq!(row(&annotated, 2, "Residual"), vec!["x", "x", "x", "x"]);
+    assert_eq!(row(&annotated, 3, "ExpectedN"), vec!["x", "x", "x", "x"]);
+}
+
+#[test]
+fn test_chi_square_p_value_and_errors() {
+    let xtab = XtabMLParser::parse_str(CROSSTAB_XTABML).unwrap();
+    let test = xtab.tables[1]
+        .chi_square(&StatisticRegistry::standard())
+        .unwrap();
+    assert_eq!(test.degrees_of_freedom, 4);
+    // With four degrees of freedom, p = exp(-x / 2) (1 + x / 2)
+    let x = test.statistic;
+    assert!((x - 27.519513).abs() < 1e-6);
+    assert!((test.p_value - (-x / 2.0).exp() * (1.0 + x / 2.0)).abs() < 1e-9);
+
+    let message = error_message!(
+        xtab.tables[2].chi_square(&StatisticRegistry::standard()),
+        XtabMLError::Significance
+    );
+    assert_eq!(
+        message,
+        "the table has no count statistic (Count, Frequency or n)"
+    );
+}
+
+#[test]
+fn test_chi_square_with_registered_names() {
+    let xtab = XtabMLParser::parse_str(CROSSTAB_XTABML).unwrap();
+    let table = &xtab.tables[3];
+    let message = error_message!(
+        table.chi_square(&StatisticRegistry::standard()),
+        XtabMLError::Significance
+    );
+    assert_eq!(
+        message,
+        "the table has no count statistic (Count, Frequency or n)"
+    );
+
+    let mut registry = StatisticRegistry::standard();
+    registry.register("Freq", StandardStatistic::Count);
+    let test = table.chi_square(&registry).unwrap();
+    assert_eq!(test.degrees_of_freedom, 1);
+    // Expected counts are all 100, so each cell adds 20² / 100
+    assert!((test.statistic - 16.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_chi_square_leaves_out_q_nets() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/example.xte");
+    let xtab = XtabMLParser::parse_file(path.to_str().unwrap()).unwrap();
+    // Q writes NETs as elements labelled "NET", here the last row and column
+    let mut table = xtab.tables[1].clone();
+    table.statistics[0].r#type = "n".to_string();
+    let test = table.chi_square(&StatisticRegistry::standard()).unwrap();
+    assert_eq!(table.row_labels()[11], "NET");
+    assert_eq!(table.column_labels()[8], "NET");
+    assert!(!test.rows.contains(&11));
+    assert!(!test.columns.contains(&8));
+    assert_eq!(test.columns, (0..8).collect::<Vec<_>>());
+}
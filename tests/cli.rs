@@ -0,0 +1,316 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn xtabml(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xtabml"))
+        .args(args)
+        .output()
+        .expect("failed to run xtabml")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_info() {
+    let output = xtabml(&["info", &example_file_path()]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+
+    assert!(text.contains("Version:         1.1\n"));
+    assert!(text.contains("Date:            16/11/2025\n"));
+    assert!(text.contains("Tables:          4\n"));
+    assert!(text.contains("Control types:   6\n"));
+    assert!(text.contains("Statistic types: 79\n"));
+}
+
+#[test]
+fn test_list() {
+    let output = xtabml(&["list", &example_file_path()]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("#  Name"));
+    assert!(lines[2].starts_with("2  76ba6951-b0f1-472f-b86a-8a496bc7815b  Q5. Unaided Awareness"));
+    assert!(lines[2].ends_with("12x9  ColumnPercent"));
+}
+
+#[test]
+fn test_show_selectors() {
+    let file = example_file_path();
+    let by_index = stdout(&xtabml(&["show", &file, "3", "--width", "200"]));
+    let by_name = stdout(&xtabml(&[
+        "show",
+        &file,
+        "2d5c7893-ab88-4a7e-90f4-c9b5634b577e",
+        "--width",
+        "200",
+    ]));
+    let by_title = stdout(&xtabml(&["show", &file, "^Q20", "--width", "200"]));
+
+    assert!(by_index.starts_with("Q20. Image\n"));
+    assert_eq!(by_index, by_name);
+    assert_eq!(by_index, by_title);
+
+    // A pattern matching several titles shows all of them
+    let ages = stdout(&xtabml(&[
+        "show", &file, "q4: Age$", "--ascii", "--width", "100",
+    ]));
+    assert_eq!(ages.lines().filter(|l| l.ends_with("q4: Age")).count(), 2);
+    assert!(ages.contains("Time (in weeks) by q4: Age\n"));
+    assert!(ages.contains("| 15 and under | % |"));
+    assert!(ages.lines().all(|l| l.chars().count() <= 100));
+}
+
+#[test]
+fn test_show_from_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xtabml"))
+        .args(["show", "-", "1", "--no-controls", "--decimals", "1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let xml = std::fs::read(example_file_path()).unwrap();
+    child.stdin.take().unwrap().write_all(&xml).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.starts_with("q4: Age\n┌"));
+    assert!(text.contains("│ 20-24 yrs    │ % │  32.1 │"));
+}
+
+#[test]
+fn test_errors() {
+    let output = xtabml(&["show", &example_file_path(), "9"]);
+    assert!(!output.status.success());
+    let message = String::from_utf8(output.stderr).unwrap();
+    assert!(message.contains("table 9 is out of range"));
+
+    let output = xtabml(&["show", &example_file_path(), "no such table"]);
+    assert!(!output.status.success());
+
+    let output = xtabml(&["info", "does-not-exist.xte"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("xtabml: does-not-exist.xte: "));
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("libxtabml-cli-{}-{}", std::process::id(), name))
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_convert_infers_format() {
+    let csv = temp_path("tables.csv");
+    let output = xtabml(&["convert", &example_file_path(), "-o", &csv, "-t", "2"]);
+    assert!(output.status.success());
+    let text = std::fs::read_to_string(&csv).unwrap();
+    std::fs::remove_file(&csv).unwrap();
+
+    assert!(text.starts_with("table_index,table_name,table_title,"));
+    assert_eq!(text.lines().count(), 1 + 12 * 9);
+    assert!(text.lines().skip(1).all(|l| l.starts_with("1,")));
+
+    let xlsx = temp_path("tables.xlsx");
+    let output = xtabml(&[
+        "convert",
+        &example_file_path(),
+        "-o",
+        &xlsx,
+        "--layout",
+        "wide",
+    ]);
+    assert!(output.status.success());
+    assert!(std::fs::read(&xlsx).unwrap().starts_with(b"PK"));
+    std::fs::remove_file(&xlsx).unwrap();
+
+    let output = xtabml(&["convert", &example_file_path(), "-o", "tables.unknown"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot infer the format"));
+}
+
+#[test]
+fn test_convert_pipeline() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xtabml"))
+        .args([
+            "convert",
+            "-",
+            "-f",
+            "csv",
+            "-l",
+            "wide",
+            "-t",
+            "^Q5",
+            "-s",
+            "ColumnPercent",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let xml = std::fs::read(example_file_path()).unwrap();
+    child.stdin.take().unwrap().write_all(&xml).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].starts_with("Row,Statistic,Age / Under 35,Age / 35 and over,"));
+    assert_eq!(lines[1], "AAPT/Cellular One,ColumnPercent,8.545%,7.143%,9.792%,6.383%,7.447%,9.854%,4.975%,12.000%,7.994%");
+
+    // Formats without a layout convert the selected subset of the document
+    let output = xtabml(&[
+        "convert",
+        &example_file_path(),
+        "-f",
+        "md",
+        "-t",
+        "1",
+        "-t",
+        "4",
+    ]);
+    let text = stdout(&output);
+    assert_eq!(text.matches("## ").count(), 2);
+    assert!(text.starts_with("## q4: Age\n"));
+
+    let output = xtabml(&["convert", &example_file_path(), "-f", "json", "-t", "3"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["tables"].as_array().unwrap().len(), 1);
+    assert_eq!(json["tables"][0]["title"], "Q20. Image");
+
+    let output = xtabml(&["convert", &example_file_path()]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_validate() {
+    let output = xtabml(&["validate", &example_file_path()]);
+    assert!(output.status.success());
+    assert!(stdout(&output).ends_with(": valid\n"));
+
+    let path = temp_path("faulty.xte");
+    let xml = std::fs::read_to_string(example_file_path())
+        .unwrap()
+        .replacen(r#"<control type="base">"#, r#"<control type="bogus">"#, 1);
+    std::fs::write(&path, xml).unwrap();
+
+    let output = xtabml(&["validate", &path]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains(&format!(
+        "{}:270: warning[undeclared-control-type]: control type \"bogus\" is not declared\n",
+        path
+    )));
+    assert!(text.ends_with(": 0 errors, 1 warning\n"));
+
+    let output = xtabml(&[
+        "validate",
+        &path,
+        "--fail-on",
+        "warning",
+        "--format",
+        "json",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["warnings"], 1);
+    assert_eq!(report["diagnostics"][0]["rule"], "undeclared-control-type");
+    assert_eq!(report["diagnostics"][0]["line"], 270);
+
+    let output = xtabml(&["validate", &path, "--format", "sarif"]);
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "undeclared-control-type");
+    assert_eq!(result["level"], "warning");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["region"]["startLine"],
+        270
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_search() {
+    let output = xtabml(&["search", &example_file_path(), "male", "--in", "columns"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "2  column element  Male    /xtab/table[2]/edge[@axis='c']/group[1]/group[2]/element[1]/t\n\
+         2  column element  Female  /xtab/table[2]/edge[@axis='c']/group[1]/group[2]/element[2]/t\n"
+    );
+
+    let output = xtabml(&[
+        "search",
+        &example_file_path(),
+        "-E",
+        r"^Q\d",
+        "--in",
+        "titles",
+    ]);
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.starts_with("2  title  Q5. Unaided Awareness by BANNER"));
+
+    let output = xtabml(&["search", &example_file_path(), "--fuzzy", "unaw", "-n", "1"]);
+    assert!(stdout(&output).contains("/xtab/table[2]/t"));
+}
+
+#[test]
+fn test_query_option() {
+    let file = example_file_path();
+    let output = xtabml(&[
+        "list",
+        &file,
+        "--query",
+        r#"title contains "age" | rows contains "yrs""#,
+    ]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.lines().nth(1).unwrap().starts_with("1  97f48ec3"));
+    assert!(text.ends_with("7x1  Percent\n"));
+
+    let output = xtabml(&[
+        "convert",
+        &file,
+        "--format",
+        "md",
+        "--query",
+        r#"index = 2 | rows "NET" | cols group "Gender""#,
+    ]);
+    assert_eq!(
+        stdout(&output),
+        "## Q5. Unaided Awareness by BANNER\n\n\
+         |  | Gender / Male | Gender / Female |\n\
+         | :-- | --: | --: |\n\
+         | NET | 100.000% | 100.000% |\n"
+    );
+
+    let output = xtabml(&["list", &file, "--query", "colour = red"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Invalid query: expected a field at offset 0"));
+}
@@ -0,0 +1,175 @@
+use libxtabml::{XtabMLError, XtabMLParser};
+
+#[macro_use]
+mod common;
+
+use common::values;
+
+const WAVES_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Awareness</t>
+    <control type="weight"><t>Weighted</t></control>
+    <control type="base"><t>Total sample</t></control>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c" name="banner">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <data>
+      <r><c><v>60</v><v>55</v><v>65</v></c></r>
+      <r><c><v>40</v></c></r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Consideration</t>
+    <control type="weight"><t>Weighted</t></control>
+    <control type="base"><t>Aware</t></control>
+    <edge axis="r">
+      <group>
+        <t>Brands</t>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <summary><t>NET</t></summary>
+      </group>
+    </edge>
+    <edge axis="c" name="banner">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="ColumnPercent"/>
+    <data>
+      <r><c><v>30</v><v>14</v><v>16</v></c><c><v>50</v><v>47</v><v>53</v></c></r>
+      <r><c><v>20</v><v>11</v><v>9</v></c><c><v>33</v><v>37</v><v>30</v></c></r>
+      <r><c><v>45</v><v>22</v><v>23</v></c><c><v>75</v><v>73</v><v>77</v></c></r>
+    </data>
+  </table>
+  <table name="q3">
+    <t>Q3. Awareness by age</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+        <summary><t>Total</t></summary>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <data>
+      <r><c><v>55</v><v>65</v><v>60</v></c></r>
+      <r><c><v>45</v><v>35</v><v>40</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_stack_tables() {
+    let xtab = XtabMLParser::parse_str(WAVES_XTABML).unwrap();
+    let stacked = xtab.stack_tables(&[0, 1]).unwrap();
+
+    assert_eq!(stacked.title, "Q1. Awareness / Q2. Consideration");
+    assert_eq!(stacked.statistic_types(), vec!["ColumnPercent", "Count"]);
+    assert_eq!(stacked.controls.len(), 1);
+    assert_eq!(stacked.controls[0].r#type, "weight");
+    assert_eq!(stacked.column_labels(), vec!["Total", "Male", "Female"]);
+
+    let edge = stacked.row_edge.as_ref().unwrap();
+    assert_eq!(edge.groups.len(), 2);
+    assert_eq!(edge.groups[0].text.as_deref(), Some("Q1. Awareness"));
+    assert_eq!(edge.groups[1].text.as_deref(), Some("Q2. Consideration"));
+    assert_eq!(edge.groups[1].groups[0].text.as_deref(), Some("Brands"));
+    assert_eq!(edge.groups[1].groups[0].summaries[0].position, Some(4));
+    assert_eq!(
+        stacked.row_labels(),
+        vec!["Yes", "No", "Brand A", "Brand B", "NET"]
+    );
+
+    assert_eq!(
+        values(&stacked, 0),
+        vec![
+            vec!["60", "55", "65"],
+            vec!["40", "40", "40"],
+            vec!["50", "47", "53"],
+            vec!["33", "37", "30"],
+            vec!["75", "73", "77"],
+        ]
+    );
+    assert_eq!(values(&stacked, 1)[0], vec!["x", "x", "x"]);
+    assert_eq!(
+        stacked
+            .cell(["Q2. Consideration", "Brands", "NET"], "Female", "Count")
+            .unwrap()
+            .value
+            .as_deref(),
+        Some("23")
+    );
+}
+
+#[test]
+fn test_side_by_side() {
+    let xtab = XtabMLParser::parse_str(WAVES_XTABML).unwrap();
+    let combined = xtab.side_by_side(&[0, 2]).unwrap();
+
+    assert_eq!(combined.row_labels(), vec!["Yes", "No"]);
+    let edge = combined.column_edge.as_ref().unwrap();
+    assert_eq!(edge.axis, "c");
+    assert_eq!(edge.groups[0].text.as_deref(), Some("Q1. Awareness"));
+    assert_eq!(edge.groups[1].text.as_deref(), Some("Q3. Awareness by age"));
+    assert_eq!(
+        combined.column_labels(),
+        vec!["Total", "Male", "Female", "Male", "Female", "Total"]
+    );
+    assert_eq!(
+        values(&combined, 0),
+        vec![
+            vec!["60", "55", "65", "55", "65", "60"],
+            vec!["40", "40", "40", "45", "35", "40"],
+        ]
+    );
+}
+
+#[test]
+fn test_combining_requires_equal_edges() {
+    let xtab = XtabMLParser::parse_str(WAVES_XTABML).unwrap();
+
+    // Same labels, different nesting
+    let message = error_message!(xtab.stack_tables(&[0, 2]), XtabMLError::EdgeMismatch);
+    assert_eq!(
+        message,
+        "the column edge of table 2 differs from that of table 0: edge has 1 groups instead of 2"
+    );
+    let message = error_message!(xtab.side_by_side(&[0, 1]), XtabMLError::EdgeMismatch);
+    assert_eq!(
+            message,
+            "the row edge of table 1 differs from that of table 0: edge / group 1 is \"Brands\" instead of \"\""
+        );
+    assert!(matches!(
+        xtab.stack_tables(&[0, 7]),
+        Err(XtabMLError::MissingElement(_))
+    ));
+    assert!(matches!(
+        xtab.stack_tables(&[]),
+        Err(XtabMLError::MissingElement(_))
+    ));
+}
@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests
+//!
+//! Each test crate uses some of them only.
+#![allow(dead_code, unused_macros)]
+
+use libxtabml::Table;
+
+/// The message of the error a call is expected to fail with, given the error variant
+///
+/// Panics with the other error, or with "Ok", when the call does not fail that way.
+macro_rules! error_message {
+    ($result:expr, $variant:path) => {
+        match $result {
+            Err($variant(message)) => message,
+            Err(other) => panic!("expected {}, got {:?}", stringify!($variant), other),
+            Ok(_) => panic!("expected {}, got Ok", stringify!($variant)),
+        }
+    };
+}
+
+/// Index of the table's statistic of a type
+pub fn statistic(table: &Table, r#type: &str) -> usize {
+    table
+        .statistics
+        .iter()
+        .position(|s| s.r#type == r#type)
+        .unwrap_or_else(|| panic!("no statistic {:?}", r#type))
+}
+
+/// Values of a statistic across a data row, "x" for missing cells
+pub fn row(table: &Table, row: usize, statistic: &str) -> Vec<String> {
+    row_at(table, row, self::statistic(table, statistic))
+}
+
+/// Values of the statistic at an index across a data row, "x" for missing cells
+pub fn row_at(table: &Table, row: usize, statistic: usize) -> Vec<String> {
+    let series = &table.data.rows[row].data_row_series[statistic];
+    (0..table.column_count())
+        .map(|c| {
+            let cell = series.cell(c).unwrap();
+            cell.value.clone().unwrap_or_else(|| "x".to_string())
+        })
+        .collect()
+}
+
+/// Values of the statistic at an index in every data row
+pub fn values(table: &Table, statistic: usize) -> Vec<Vec<String>> {
+    (0..table.data.rows.len())
+        .map(|r| row_at(table, r, statistic))
+        .collect()
+}
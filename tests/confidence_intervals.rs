@@ -0,0 +1,208 @@
+use libxtabml::{
+    Basis, IntervalMethod, IntervalOptions, StandardStatistic, StatisticRegistry, XtabMLError,
+    XtabMLParser,
+};
+
+#[macro_use]
+mod common;
+
+use common::row;
+
+const INTERVALS_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Aware of brand</t>
+    <edge axis="r"><group><element><t>Aware</t></element></group></edge>
+    <edge axis="c">
+      <group>
+        <t>Region</t>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+        <element><t>West</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="ColumnN"/>
+    <statistic type="UpperCIPercent"/>
+    <data>
+      <r>
+        <c><v>30</v><v>2</v><v>60</v></c>
+        <c><v>100</v><v>50</v><v>200</v></c>
+        <c><v>40</v><v>11</v><v>67</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Time (in weeks)</t>
+    <edge axis="r"><group><element><t>Weeks</t></element></group></edge>
+    <edge axis="c">
+      <group>
+        <t>Region</t>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+        <element><t>West</t></element>
+      </group>
+    </edge>
+    <statistic type="Average"/>
+    <statistic type="StandardDeviation"/>
+    <statistic type="n"/>
+    <data>
+      <r>
+        <c><v>10</v><v>12.5</v><v>9.8</v></c>
+        <c><v>4</v><v>4</v><x/></c>
+        <c><v>30</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q3">
+    <t>Q3. Comments</t>
+    <edge axis="r"><group><element><t>Comment</t></element></group></edge>
+    <edge axis="c"><group><summary><t>Total</t></summary></group></edge>
+    <statistic type="Text"/>
+    <data><r><c><v>Great</v></c></r></data>
+  </table>
+  <table name="q4">
+    <t>Q4. Aware of brand by region</t>
+    <edge axis="r"><group><element><t>Aware</t></element></group></edge>
+    <edge axis="c">
+      <group>
+        <t>Region</t>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+      </group>
+    </edge>
+    <statistic type="RowPercent"/>
+    <statistic type="RowN"/>
+    <statistic type="TotalPercent"/>
+    <statistic type="TotalN"/>
+    <data>
+      <r>
+        <c><v>30</v><v>60</v></c>
+        <c><v>100</v><v>200</v></c>
+        <c><v>2</v><v>60</v></c>
+        <c><v>50</v><v>200</v></c>
+      </r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_percentage_intervals() {
+    let xtab = XtabMLParser::parse_str(INTERVALS_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let wilson = table
+        .derive_confidence_intervals(IntervalOptions::default())
+        .unwrap();
+    assert_eq!(
+        row(&wilson, 0, "LowerCIPercent"),
+        vec!["21.89", "0.35", "53.08"]
+    );
+    // Already exported, so left alone
+    assert_eq!(row(&wilson, 0, "UpperCIPercent"), vec!["40", "11", "67"]);
+    let lower = wilson
+        .statistics
+        .iter()
+        .find(|s| s.r#type == "LowerCIPercent")
+        .unwrap();
+    assert_eq!(lower.datatype.as_deref(), Some("percentage"));
+
+    // Also when exported under a name registered for it
+    let mut renamed = table.clone();
+    renamed.statistics[2].r#type = "Upper CI %".to_string();
+    let mut statistics = StatisticRegistry::standard();
+    statistics.register(
+        "Upper CI %",
+        StandardStatistic::UpperCIPercent(Basis::Column),
+    );
+    let intervals = renamed
+        .derive_confidence_intervals(IntervalOptions {
+            statistics,
+            ..IntervalOptions::default()
+        })
+        .unwrap();
+    assert_eq!(
+        intervals.statistic_types(),
+        vec!["ColumnPercent", "ColumnN", "Upper CI %", "LowerCIPercent"]
+    );
+
+    // A cell's effective n is not the base of its percentage
+    let mut cell_sizes = table.clone();
+    cell_sizes.statistics[2].r#type = "EffectiveN".to_string();
+    let intervals = cell_sizes
+        .derive_confidence_intervals(IntervalOptions::default())
+        .unwrap();
+    assert_eq!(
+        row(&intervals, 0, "LowerCIPercent"),
+        vec!["21.89", "0.35", "53.08"]
+    );
+
+    let mut without_upper = table.clone();
+    without_upper.statistics.truncate(2);
+    for row in &mut without_upper.data.rows {
+        row.data_row_series.truncate(2);
+    }
+    let normal = without_upper
+        .derive_confidence_intervals(IntervalOptions {
+            method: IntervalMethod::Normal,
+            ..IntervalOptions::default()
+        })
+        .unwrap();
+    assert_eq!(
+        row(&normal, 0, "LowerCIPercent"),
+        vec!["21.02", "0.00", "53.21"]
+    );
+    assert_eq!(
+        row(&normal, 0, "UpperCIPercent"),
+        vec!["38.98", "5.88", "66.79"]
+    );
+}
+
+#[test]
+fn test_row_and_total_percentage_intervals() {
+    let xtab = XtabMLParser::parse_str(INTERVALS_XTABML).unwrap();
+    let table = xtab.tables[3]
+        .derive_confidence_intervals(IntervalOptions::default())
+        .unwrap();
+
+    // Each percentage takes n from the base of its own margin
+    assert_eq!(row(&table, 0, "LowerCIRowPercent"), vec!["21.89", "53.08"]);
+    assert_eq!(row(&table, 0, "LowerCITotalPercent"), vec!["0.35", "53.08"]);
+    assert!(table
+        .statistics
+        .iter()
+        .all(|s| s.r#type != "LowerCIPercent"));
+    assert_eq!(
+        StatisticRegistry::standard().lookup("UpperCITotalPercent"),
+        Some(StandardStatistic::UpperCIPercent(Basis::Total))
+    );
+}
+
+#[test]
+fn test_mean_intervals() {
+    let xtab = XtabMLParser::parse_str(INTERVALS_XTABML).unwrap();
+    let table = xtab.tables[1]
+        .derive_confidence_intervals(IntervalOptions::default())
+        .unwrap();
+    // t with 29 degrees of freedom; the third column has no standard deviation
+    assert_eq!(row(&table, 0, "LowerCI"), vec!["8.51", "11.01", "x"]);
+    assert_eq!(row(&table, 0, "UpperCI"), vec!["11.49", "13.99", "x"]);
+    assert!(table
+        .statistics
+        .iter()
+        .all(|s| s.r#type != "LowerCIPercent"));
+
+    let wider = xtab.tables[1]
+        .derive_confidence_intervals(IntervalOptions {
+            level: 0.99,
+            ..IntervalOptions::default()
+        })
+        .unwrap();
+    assert!(row(&wider, 0, "LowerCI")[0].parse::<f64>().unwrap() < 8.51);
+
+    let message = error_message!(
+        xtab.tables[2].derive_confidence_intervals(IntervalOptions::default()),
+        XtabMLError::Derivation
+    );
+    assert!(message.starts_with("the table has neither"));
+}
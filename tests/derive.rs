@@ -0,0 +1,269 @@
+use libxtabml::{
+    Input, Percentage, StandardStatistic, StatisticRegistry, XtabMLError, XtabMLParser,
+};
+
+#[macro_use]
+mod common;
+
+use common::row;
+
+const COUNTS_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Preferred brand</t>
+    <edge axis="r">
+      <group>
+        <t>Brand</t>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <summary><t>Total</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="n" datatype="integer"/>
+    <statistic type="ColumnN" datatype="integer"/>
+    <data>
+      <r>
+        <c><v>30</v><v>10</v><v>20</v></c>
+        <c><v>100</v><v>40</v><v>60</v></c>
+      </r>
+      <r>
+        <c><v>50</v><v>20</v><v>30</v></c>
+        <c><v>100</v><v>40</v><v>60</v></c>
+      </r>
+      <r>
+        <c><v>80</v><v>30</v><v>50</v></c>
+        <c><v>100</v><v>40</v><v>60</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Average rating</t>
+    <edge axis="r">
+      <group><element><t>Rating</t></element></group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+    </edge>
+    <statistic type="Mean"/>
+    <data>
+      <r><c><v>3.4</v></c></r>
+    </data>
+  </table>
+  <table name="q3">
+    <t>Q3. Would you recommend us?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Freq"/>
+    <statistic type="Base"/>
+    <data>
+      <r>
+        <c><v>120</v><v>80</v></c>
+        <c><v>200</v><v>200</v></c>
+      </r>
+      <r>
+        <c><v>80</v><v>120</v></c>
+        <c><v>200</v><v>200</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q4">
+    <t>Q4. Brands bought</t>
+    <edge axis="r">
+      <group>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <element><t>Brand C</t></element>
+        <element><t>NET</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+    </edge>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>60</v></c></r>
+      <r><c><v>30</v></c></r>
+      <r><c><v>10</v></c></r>
+      <r><c><v>100</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+fn input(statistic: &str, row: usize, column: usize) -> Input {
+    Input {
+        statistic: statistic.to_string(),
+        row,
+        column,
+    }
+}
+
+#[test]
+fn test_derive_percentages() {
+    let xtab = XtabMLParser::parse_str(COUNTS_XTABML).unwrap();
+    let source = &xtab.tables[0];
+    assert_eq!(source.statistics[0].datatype.as_deref(), Some("integer"));
+
+    let (table, derivations) = source
+        .derive_percentages(&Percentage::ALL, &StatisticRegistry::standard())
+        .unwrap();
+    assert_eq!(
+        table.statistic_types(),
+        vec![
+            "n",
+            "ColumnN",
+            "ColumnPercent",
+            "RowPercent",
+            "TotalPercent",
+            "PercentColumnResponses",
+            "PercentRowResponses",
+            "PercentTotalResponses"
+        ]
+    );
+    assert!(table.statistics[2..]
+        .iter()
+        .all(|s| s.datatype.as_deref() == Some("percentage")));
+
+    // Column bases from the ColumnN statistic
+    assert_eq!(
+        row(&table, 0, "ColumnPercent"),
+        vec!["30.00", "25.00", "33.33"]
+    );
+    // Row and table bases from the Total summaries
+    assert_eq!(
+        row(&table, 0, "RowPercent"),
+        vec!["100.00", "33.33", "66.67"]
+    );
+    assert_eq!(
+        row(&table, 0, "TotalPercent"),
+        vec!["37.50", "12.50", "25.00"]
+    );
+    // Responses bases from the elements of the enclosing group
+    assert_eq!(
+        row(&table, 0, "PercentColumnResponses"),
+        vec!["37.50", "33.33", "40.00"]
+    );
+    assert_eq!(
+        row(&table, 1, "PercentRowResponses"),
+        vec!["x", "40.00", "60.00"]
+    );
+
+    let derivation = |statistic: &str, row: usize, column: usize| {
+        derivations
+            .iter()
+            .find(|d| d.statistic == statistic && d.row == row && d.column == column)
+            .unwrap()
+    };
+    let column_percent = derivation("ColumnPercent", 0, 2);
+    assert_eq!(column_percent.count, input("n", 0, 2));
+    assert_eq!(column_percent.base, vec![input("ColumnN", 0, 2)]);
+    assert!((column_percent.value - 100.0 / 3.0).abs() < 1e-9);
+    assert_eq!(derivation("RowPercent", 0, 1).base, vec![input("n", 0, 0)]);
+    assert_eq!(
+        derivation("TotalPercent", 0, 1).base,
+        vec![input("n", 2, 0)]
+    );
+    assert_eq!(
+        derivation("PercentTotalResponses", 0, 1).base,
+        vec![
+            input("n", 0, 1),
+            input("n", 0, 2),
+            input("n", 1, 1),
+            input("n", 1, 2)
+        ]
+    );
+    // Three rows and columns of six statistics, less the Total column of the row and
+    // table responses, whose group has no elements
+    assert_eq!(derivations.len(), 3 * 3 * 6 - 2 * 3);
+}
+
+#[test]
+fn test_derive_percentages_keeps_existing_statistics() {
+    let xtab = XtabMLParser::parse_str(COUNTS_XTABML).unwrap();
+    let (table, _) = xtab.tables[0]
+        .derive_percentages(&[Percentage::Column], &StatisticRegistry::standard())
+        .unwrap();
+    let (again, derivations) = table
+        .derive_percentages(
+            &[Percentage::Column, Percentage::Row],
+            &StatisticRegistry::standard(),
+        )
+        .unwrap();
+    assert_eq!(
+        again.statistic_types(),
+        vec!["n", "ColumnN", "ColumnPercent", "RowPercent"]
+    );
+    assert!(derivations.iter().all(|d| d.statistic == "RowPercent"));
+
+    // Column percentages under another name count as present too
+    let mut renamed = table.clone();
+    renamed.statistics[2].r#type = "xs:cp".to_string();
+    let (again, derivations) = renamed
+        .derive_percentages(&[Percentage::Column], &StatisticRegistry::standard())
+        .unwrap();
+    assert_eq!(again.statistic_types(), vec!["n", "ColumnN", "xs:cp"]);
+    assert!(derivations.is_empty());
+
+    let message = error_message!(
+        xtab.tables[1].derive_percentages(&Percentage::ALL, &StatisticRegistry::standard()),
+        XtabMLError::Derivation
+    );
+    assert_eq!(
+        message,
+        "the table has no count statistic (Count, Frequency or n)"
+    );
+}
+
+#[test]
+fn test_derive_percentages_with_registered_names() {
+    let xtab = XtabMLParser::parse_str(COUNTS_XTABML).unwrap();
+    let table = &xtab.tables[2];
+    let message = error_message!(
+        table.derive_percentages(&[Percentage::Column], &StatisticRegistry::standard()),
+        XtabMLError::Derivation
+    );
+    assert_eq!(
+        message,
+        "the table has no count statistic (Count, Frequency or n)"
+    );
+
+    let mut registry = StatisticRegistry::standard();
+    registry.register("Freq", StandardStatistic::Count);
+    let (derived, _) = table
+        .derive_percentages(&[Percentage::Column], &registry)
+        .unwrap();
+    assert_eq!(row(&derived, 0, "ColumnPercent"), vec!["60.00", "40.00"]);
+}
+
+#[test]
+fn test_responses_percentages_leave_out_q_nets() {
+    let xtab = XtabMLParser::parse_str(COUNTS_XTABML).unwrap();
+    let (table, _) = xtab.tables[3]
+        .derive_percentages(
+            &[Percentage::ColumnResponses],
+            &StatisticRegistry::standard(),
+        )
+        .unwrap();
+    // Q writes the NET as an element; it repeats the answers, so it is no part of the base
+    let percentages: Vec<String> = (0..4)
+        .map(|r| row(&table, r, "PercentColumnResponses").remove(0))
+        .collect();
+    assert_eq!(percentages, vec!["60.00", "30.00", "10.00", "100.00"]);
+}
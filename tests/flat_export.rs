@@ -0,0 +1,215 @@
+use libxtabml::{FlatOptions, Layout, XtabMLParser};
+use std::path::Path;
+
+const NESTED_XML: &str = r#"<xtab version="1.1">
+  <table name="t1">
+    <t>Spend, by "region"</t>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Total</t></summary>
+        <group><t>Region</t><element><t>London</t></element><element><t>South East</t></element></group>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <group><t>Gender</t><element><t>Male</t></element><element><t>Female</t></element></group>
+      </group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="ColumnPercent" />
+    <data>
+      <r><c><v>100</v><v>120</v></c><c><v>100.0%</v></c></r>
+      <r><c><v>40</v><v>50</v></c><c><v>40.0%</v><v>41.7%</v></c></r>
+      <r><c><v>60</v><x/></c><c><v>60.0%</v><v>58.3%</v></c></r>
+    </data>
+  </table>
+  <table>
+    <t>Second</t>
+    <edge axis="r"><group><element><t>Yes</t></element></group></edge>
+    <edge axis="c"><group><element><t>All</t></element></group></edge>
+    <statistic type="n" />
+    <data><r><c><v>7</v></c></r></data>
+  </table>
+</xtab>"#;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_long_records() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let records = xtab.long_records(&FlatOptions::default());
+
+    // 3 rows x 2 columns x 2 statistics, plus the single cell of the second table
+    assert_eq!(records.len(), 13);
+    let female = &records[3];
+    assert_eq!(female.row_label, "Region / London");
+    assert_eq!(female.column_label, "Gender / Female");
+    assert_eq!(female.statistic, "n");
+    assert_eq!(female.number, Some(50.0));
+    // Missing cells are kept with no value
+    assert_eq!(records[5].value, None);
+    // Compressed runs are expanded
+    assert_eq!(records[7].value.as_deref(), Some("100.0%"));
+    assert_eq!(records[12].table_index, 1);
+}
+
+#[test]
+fn test_table_and_statistic_selection() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let options = FlatOptions {
+        tables: Some(vec![0]),
+        statistics: Some(vec!["ColumnPercent".to_string()]),
+        ..FlatOptions::default()
+    };
+    let records = xtab.long_records(&options);
+    assert_eq!(records.len(), 6);
+    assert!(records.iter().all(|r| r.statistic == "ColumnPercent"));
+
+    let tables = xtab.wide_tables(&options);
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].columns, vec!["Gender / Male", "Gender / Female"]);
+    assert_eq!(tables[0].rows.len(), 3);
+}
+
+#[test]
+fn test_csv_long() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let csv = xtab.to_csv(&FlatOptions::default());
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "table_index,table_name,table_title,row_position,row_label,column_position,column_label,statistic,value,number"
+    );
+    assert_eq!(
+        lines[1],
+        r#"0,t1,"Spend, by ""region""",0,Total,0,Gender / Male,n,100,100"#
+    );
+    assert!(lines[6].ends_with(",n,,"));
+    assert!(csv.ends_with("\r\n"));
+}
+
+#[test]
+fn test_csv_wide() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let options = FlatOptions {
+        layout: Layout::Wide,
+        tables: Some(vec![0]),
+        ..FlatOptions::default()
+    };
+    let csv = xtab.to_csv(&options);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Row,Statistic,Gender / Male,Gender / Female");
+    assert_eq!(lines[1], "Total,n,100,120");
+    assert_eq!(lines[2], "Total,ColumnPercent,100.0%,100.0%");
+    assert_eq!(lines[5], "Region / South East,n,60,");
+
+    // Several tables are titled and separated by an empty line
+    let csv = xtab.to_csv(&FlatOptions {
+        layout: Layout::Wide,
+        ..FlatOptions::default()
+    });
+    assert!(csv.starts_with("\"Spend, by \"\"region\"\"\"\r\nRow,"));
+    assert!(csv.ends_with("\r\n\r\nSecond\r\nRow,Statistic,All\r\nYes,n,7\r\n"));
+}
+
+#[test]
+fn test_hidden_cells_are_blank() {
+    let xtab = XtabMLParser::parse_str(&NESTED_XML.replace("<v>50</v>", "<h>50</h>")).unwrap();
+    let records = xtab.long_records(&FlatOptions::default());
+    assert_eq!(records[3].column_label, "Gender / Female");
+    assert_eq!(records[3].value, None);
+    assert_eq!(records[3].number, None);
+
+    let csv = xtab.to_csv(&FlatOptions::default());
+    assert!(csv.lines().nth(4).unwrap().ends_with(",n,,"));
+    let csv = xtab.to_csv(&FlatOptions {
+        layout: Layout::Wide,
+        tables: Some(vec![0]),
+        ..FlatOptions::default()
+    });
+    assert_eq!(csv.lines().nth(3).unwrap(), "Region / London,n,40,");
+}
+
+#[test]
+fn test_example_document() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let expected: usize = xtab
+        .tables
+        .iter()
+        .map(|t| t.data.rows.len() * t.column_count() * t.statistics.len())
+        .sum();
+    assert_eq!(xtab.long_records(&FlatOptions::default()).len(), expected);
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_xlsx() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    for layout in [Layout::Long, Layout::Wide] {
+        let bytes = xtab
+            .to_xlsx(&FlatOptions {
+                layout,
+                ..FlatOptions::default()
+            })
+            .unwrap();
+        // An xlsx file is a zip archive
+        assert!(bytes.starts_with(b"PK\x03\x04"));
+    }
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet() {
+    use libxtabml::XtabMLError;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let read = |bytes: Vec<u8>, name: &str| {
+        let path =
+            std::env::temp_dir().join(format!("libxtabml-{}-{}.parquet", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let reader = SerializedFileReader::try_from(std::fs::File::open(&path).unwrap()).unwrap();
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        rows
+    };
+
+    let rows = read(xtab.to_parquet(&FlatOptions::default()).unwrap(), "long");
+    assert_eq!(rows.len(), 13);
+    assert_eq!(rows[3].get_string(4).unwrap(), "Region / London");
+    assert_eq!(rows[3].get_double(9).unwrap(), 50.0);
+    assert_eq!(rows[12].get_long(0).unwrap(), 1);
+
+    let wide = FlatOptions {
+        layout: Layout::Wide,
+        tables: Some(vec![0]),
+        ..FlatOptions::default()
+    };
+    let rows = read(xtab.to_parquet(&wide).unwrap(), "wide");
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[1].get_string(3).unwrap(), "100.0%");
+
+    // Hidden cells are null, like missing ones
+    let hidden = XtabMLParser::parse_str(&NESTED_XML.replace("<v>50</v>", "<h>50</h>")).unwrap();
+    let rows = read(hidden.to_parquet(&wide).unwrap(), "hidden");
+    assert!(rows[2].get_string(3).is_err());
+
+    // Wide tables have different columns, so only one fits in a file
+    let result = xtab.to_parquet(&FlatOptions {
+        layout: Layout::Wide,
+        ..FlatOptions::default()
+    });
+    assert!(matches!(result, Err(XtabMLError::Unsupported(_))));
+}
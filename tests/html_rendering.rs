@@ -0,0 +1,125 @@
+use libxtabml::{HtmlOptions, XtabMLParser};
+use std::path::Path;
+
+const NESTED_XML: &str = r#"<xtab version="1.1">
+  <statistictype name="n"><t>Count</t></statistictype>
+  <statistictype name="ColumnPercent"><t>Column %</t></statistictype>
+  <control type="project"><t>Brand &amp; Region</t></control>
+  <table name="t1">
+    <t>Region by demographics</t>
+    <control type="base"><t>All respondents</t></control>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Total</t></summary>
+        <group name="Region">
+          <t>Region</t>
+          <element><t>London</t></element>
+          <element><t>South East</t></element>
+          <summary type="net"><t>All England</t></summary>
+        </group>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <group><t>Gender</t><element><t>Male</t></element><element><t>Female</t></element></group>
+        <group><t>Age</t><element><t>Young</t></element><element><t>Old</t></element></group>
+      </group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="ColumnPercent" />
+    <data>
+      <r><c><v>100</v><v>120</v><v>90</v><v>130</v></c><c><h>100</h></c></r>
+      <r><c><v>40</v><v>50</v><v>30</v><v>60</v></c><c><v>40</v><v>41.7</v><v>33.3</v><v>46.2</v></c></r>
+      <r><c><v>60</v><v>70</v><v>60</v><v>70</v></c><c><v>60</v><v>58.3</v><v>66.7</v><v>53.8</v></c></r>
+      <r><c><v>100</v><v>120</v><v>90</v><v>130</v></c><c><x/><x/><x/><x/></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_column_header_spans() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let html = xtab.tables[0].to_html(&HtmlOptions::default());
+
+    assert!(html.contains(r#"<th scope="col" class="group" colspan="2">Gender</th>"#));
+    assert!(html.contains(r#"<th scope="col" class="group" colspan="2">Age</th>"#));
+    assert!(html.contains(r#"<th scope="col" class="label">Female</th>"#));
+    // Two header rows for the two column levels
+    let thead = html.split("</thead>").next().unwrap();
+    assert_eq!(thead.matches("<tr>").count(), 2);
+    assert!(html.contains(r#"<th class="corner" colspan="3" rowspan="2"></th>"#));
+}
+
+#[test]
+fn test_row_header_spans_and_statistics() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let html = xtab.tables[0].to_html(&HtmlOptions::default());
+
+    // The base summary has no group heading, so it spans both header columns
+    assert!(html.contains(
+        r#"<th scope="row" class="label summary summary-xs-base" rowspan="2" colspan="2">Total</th>"#
+    ));
+    // The Region heading covers three rows of two statistics each
+    assert!(html.contains(r#"<th scope="row" class="group" rowspan="6">Region</th>"#));
+    assert_eq!(
+        html.matches(r#"<th class="statistic">ColumnPercent</th>"#)
+            .count(),
+        4
+    );
+    assert_eq!(html.matches("<tr").count(), 2 + 4 * 2);
+}
+
+#[test]
+fn test_cell_classes() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let html = xtab.tables[0].to_html(&HtmlOptions::default());
+
+    // Hidden cells are expanded across the compressed run
+    assert_eq!(html.matches(r#"<td class="hidden">100</td>"#).count(), 4);
+    assert_eq!(html.matches(r#"<td class="missing"></td>"#).count(), 4);
+    assert!(html.contains(r#"<tr class="summary summary-net net">"#));
+}
+
+#[test]
+fn test_document_index_and_css() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let html = xtab.to_html(&HtmlOptions::default());
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Brand &amp; Region</title>"));
+    assert!(html.contains(r##"<li><a href="#table-1">Region by demographics</a></li>"##));
+    assert!(html.contains(r#"<section class="xtab-table" id="table-1">"#));
+    assert!(html.contains("<style>"));
+    assert!(html.contains("<dt>base</dt><dd>All respondents</dd>"));
+
+    let options = HtmlOptions {
+        inline_css: false,
+        include_controls: false,
+    };
+    let html = xtab.to_html(&options);
+    assert!(!html.contains("<style>"));
+    assert!(!html.contains("xtab-controls"));
+}
+
+#[test]
+fn test_example_document() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let html = xtab.to_html(&HtmlOptions::default());
+
+    assert_eq!(
+        html.matches("<table class=\"xtab\">").count(),
+        xtab.tables.len()
+    );
+    assert!(html.contains("<li><a href=\"#table-4\">Time (in weeks) by q4: Age</a></li>"));
+    assert!(html.contains("Don&#39;t know"));
+    // The single "%" summary of the first table
+    assert!(html.contains(r#"<th scope="col" class="label summary">%</th>"#));
+}
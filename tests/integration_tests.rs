@@ -28,10 +28,12 @@ fn test_parse_example_file_basic() {
     let xtab = result.unwrap();
 
     // Test basic document properties
-    assert_eq!(xtab.version, "Observation");
-    assert_eq!(xtab.date, None);
-    assert_eq!(xtab.time, None);
-    assert_eq!(xtab.user, None);
+    assert_eq!(xtab.version, "1.1");
+    assert_eq!(xtab.date.as_deref(), Some("16/11/2025"));
+    assert_eq!(xtab.time.as_deref(), Some("8:52 AM"));
+    assert_eq!(xtab.user.as_deref(), Some("Miles"));
+    // The origin is commented out in the example
+    assert_eq!(xtab.origin, None);
 
     // Should have multiple tables
     assert!(!xtab.tables.is_empty(), "Should have at least one table");
@@ -45,22 +47,24 @@ fn test_parse_example_file_basic() {
 fn test_parse_example_file_control_types() {
     let xtab = parse_example_file().unwrap();
 
-    // Control types are not being parsed by current parser
-    assert!(
-        xtab.control_types.is_empty(),
-        "Control types are not parsed by current implementation"
-    );
+    assert_eq!(xtab.control_types.len(), 6);
+    assert_eq!(xtab.control_types[0].name, "project");
+    assert_eq!(xtab.control_types[0].status.as_deref(), Some("primary"));
+    assert_eq!(xtab.control_types[0].text, "Project");
 }
 
 #[test]
 fn test_parse_example_file_statistic_types() {
     let xtab = parse_example_file().unwrap();
 
-    // Statistic types are not being parsed by current parser
-    assert!(
-        xtab.statistic_types.is_empty(),
-        "Statistic types are not parsed by current implementation"
-    );
+    // Every statistictype declaration is parsed with its display text
+    assert_eq!(xtab.statistic_types.len(), 79);
+    let column_percent = xtab
+        .statistic_types
+        .iter()
+        .find(|s| s.name == "ColumnPercent")
+        .expect("ColumnPercent should be declared");
+    assert_eq!(column_percent.text, "Column %");
 }
 
 #[test]
@@ -184,11 +188,12 @@ fn test_parse_example_file_table_edges() {
     assert!(row_labels.contains(&"16-19 yrs".to_string()));
     assert!(row_labels.contains(&"NET".to_string()));
 
-    // First table has neither elements nor summaries in column edge
+    // First table has a single "%" summary and no elements in the column edge
     let column_group = &column_edge.groups[0];
-    // Both elements and summaries are empty for first table
     assert!(column_group.elements.is_empty(), "Column group should have no elements");
-    assert!(column_group.summaries.is_empty(), "Column group should have no summaries");
+    assert_eq!(column_group.summaries.len(), 1, "Column group should have one summary");
+    assert_eq!(column_group.summaries[0].text, "%");
+    assert_eq!(column_group.summaries[0].position, Some(0));
 }
 
 #[test]
@@ -0,0 +1,229 @@
+#![cfg(feature = "json")]
+
+use libxtabml::{XtabML, XtabMLError, XtabMLParser, JSON_FORMAT_VERSION, JSON_SCHEMA};
+use serde_json::{json, Value};
+use std::path::Path;
+
+const LOCALIZED_XML: &str = r#"<xtab version="1.1">
+  <language lang="en" base="yes">English</language>
+  <language lang="fr">French</language>
+  <statistictype name="n"><t>Count<a lang="fr">Effectif</a></t></statistictype>
+  <table name="t1">
+    <t>Region<a lang="fr">Région</a></t>
+    <control type="base"><t>All adults<a lang="fr">Tous les adultes</a></t></control>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Total</t></summary>
+        <group name="region">
+          <t>Region</t>
+          <element name="ldn"><t>London<a lang="fr">Londres</a></t></element>
+          <element><t>South East</t></element>
+        </group>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><element><t>Yes<a lang="fr">Oui</a></t></element><element><t>No</t></element></group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="ColumnPercent" />
+    <data>
+      <r><c><v>100</v><v>120</v></c><c><h>100%</h></c></r>
+      <r><c><v>40</v><v>50</v></c><c><v>40%</v><v>41.7%</v></c></r>
+      <r><c><v>60</v><x/></c><c><v>60%</v><v>58.3%</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn assert_valid(instance: &Value) {
+    let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|e| format!("{} at {}", e, e.instance_path()))
+        .collect();
+    assert!(errors.is_empty(), "schema violations: {:#?}", errors);
+}
+
+#[test]
+fn test_localized_texts() {
+    let xtab = XtabMLParser::parse_str(LOCALIZED_XML).unwrap();
+    assert_eq!(xtab.languages.len(), 2);
+    assert_eq!(xtab.languages[1].description, "French");
+    assert_eq!(xtab.tables[0].title, "Region");
+    assert_eq!(xtab.tables[0].controls[0].text, "All adults");
+
+    let json = xtab.to_json_value();
+    assert_eq!(json["formatVersion"], JSON_FORMAT_VERSION);
+    assert_eq!(
+        json["languages"][0],
+        json!({"lang": "en", "base": true, "description": "English"})
+    );
+    let table = &json["tables"][0];
+    assert_eq!(table["titleTranslations"], json!({"fr": "Région"}));
+    assert_eq!(
+        table["controls"][0]["translations"]["fr"],
+        "Tous les adultes"
+    );
+    assert_eq!(
+        table["columns"]["groups"][0]["children"][0],
+        json!({"kind": "element", "leaf": 0, "name": null, "text": "Yes", "translations": {"fr": "Oui"}})
+    );
+}
+
+#[test]
+fn test_edge_trees_and_cube() {
+    let xtab = XtabMLParser::parse_str(LOCALIZED_XML).unwrap();
+    let json = xtab.to_json_value();
+    let table = &json["tables"][0];
+
+    let rows = &table["rows"];
+    assert_eq!(rows["leafCount"], 3);
+    let children = &rows["groups"][0]["children"];
+    assert_eq!(children[0]["kind"], "summary");
+    assert_eq!(children[0]["type"], "xs:base");
+    assert_eq!(children[1]["kind"], "group");
+    assert_eq!(children[1]["text"], "Region");
+    assert_eq!(children[1]["children"][0]["leaf"], 1);
+    assert_eq!(children[1]["children"][0]["name"], "ldn");
+
+    // cube[statistic][row leaf][column leaf], with compressed runs expanded
+    let cube = &table["cube"];
+    assert_eq!(cube[0][1][1], json!({"value": "50", "number": 50.0}));
+    assert_eq!(cube[0][2][1], json!({"value": null, "missing": true}));
+    assert_eq!(
+        cube[1][0][1],
+        json!({"value": "100%", "number": 100.0, "hidden": true})
+    );
+    assert_eq!(table["statistics"], json!(["n", "ColumnPercent"]));
+}
+
+#[test]
+fn test_statistics_dictionary() {
+    let xtab = XtabMLParser::parse_str(LOCALIZED_XML).unwrap();
+    let json = xtab.to_json_value();
+
+    assert_eq!(
+        json["statistics"]["n"],
+        json!({"text": "Count", "translations": {"fr": "Effectif"}, "declared": true})
+    );
+    // Used by the table but never declared
+    assert_eq!(
+        json["statistics"]["ColumnPercent"],
+        json!({"text": "ColumnPercent", "declared": false})
+    );
+}
+
+#[test]
+fn test_export_matches_schema() {
+    assert_valid(
+        &XtabMLParser::parse_str(LOCALIZED_XML)
+            .unwrap()
+            .to_json_value(),
+    );
+    assert_valid(
+        &XtabMLParser::parse_file(&example_file_path())
+            .unwrap()
+            .to_json_value(),
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    for xtab in [
+        XtabMLParser::parse_str(LOCALIZED_XML).unwrap(),
+        XtabMLParser::parse_file(&example_file_path()).unwrap(),
+    ] {
+        let json = xtab.to_json().unwrap();
+        let imported = XtabML::from_json(&json).unwrap();
+        assert_eq!(imported.to_json().unwrap(), json);
+
+        assert_eq!(imported.statistic_types.len(), xtab.statistic_types.len());
+        assert_eq!(imported.tables.len(), xtab.tables.len());
+        for (a, b) in imported.tables.iter().zip(&xtab.tables) {
+            assert_eq!(a.row_labels(), b.row_labels());
+            assert_eq!(a.column_labels(), b.column_labels());
+            assert_eq!(a.statistic_types(), b.statistic_types());
+        }
+    }
+
+    let imported = XtabML::from_json(
+        &XtabMLParser::parse_str(LOCALIZED_XML)
+            .unwrap()
+            .to_json()
+            .unwrap(),
+    )
+    .unwrap();
+    let leaves = imported.tables[0].row_edge.as_ref().unwrap().leaves();
+    assert_eq!(leaves[1].text(), "London");
+    assert_eq!(leaves[1].headings(), vec!["Region"]);
+    let cell = imported.tables[0].data.rows[2].data_row_series[0]
+        .cell(1)
+        .unwrap();
+    assert!(cell.is_missing);
+    let hidden = imported.tables[0].data.rows[0].data_row_series[1]
+        .cell(1)
+        .unwrap();
+    assert_eq!(hidden.value.as_deref(), Some("100%"));
+    assert!(hidden.is_hidden && !hidden.is_missing);
+
+    // Missing is its own flag, so a cell without a value is not taken for one
+    let mut xtab = XtabMLParser::parse_str(LOCALIZED_XML).unwrap();
+    xtab.tables[0].data.rows[0].data_row_series[1].cells[0].value = None;
+    let imported = XtabML::from_json(&xtab.to_json().unwrap()).unwrap();
+    let cell = &imported.tables[0].data.rows[0].data_row_series[1].cells[0];
+    assert!(cell.is_hidden && !cell.is_missing);
+}
+
+#[test]
+fn test_statistic_datatypes() {
+    let xml = LOCALIZED_XML.replace(
+        r#"<statistic type="ColumnPercent" />"#,
+        r#"<statistic type="ColumnPercent" datatype="percentage" />"#,
+    );
+    let json = XtabMLParser::parse_str(&xml).unwrap().to_json_value();
+    assert_valid(&json);
+    assert_eq!(json["tables"][0]["datatypes"], json!([null, "percentage"]));
+
+    let imported = XtabML::from_json_value(json).unwrap();
+    let datatypes: Vec<Option<&str>> = imported.tables[0]
+        .statistics
+        .iter()
+        .map(|s| s.datatype.as_deref())
+        .collect();
+    assert_eq!(datatypes, vec![None, Some("percentage")]);
+
+    // Tables without datatypes leave the member out
+    let json = XtabMLParser::parse_str(LOCALIZED_XML)
+        .unwrap()
+        .to_json_value();
+    assert!(json["tables"][0].get("datatypes").is_none());
+}
+
+#[test]
+fn test_import_rejects_other_formats() {
+    let mut json = XtabMLParser::parse_str(LOCALIZED_XML)
+        .unwrap()
+        .to_json_value();
+    json["formatVersion"] = json!(JSON_FORMAT_VERSION + 1);
+    assert!(matches!(
+        XtabML::from_json_value(json),
+        Err(XtabMLError::InvalidStructure(_))
+    ));
+
+    match XtabML::from_json(r#"{"tables": []}"#) {
+        Err(XtabMLError::InvalidStructure(message)) => assert!(message.contains("xtabml-json")),
+        other => panic!(
+            "expected an invalid structure error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+    assert!(matches!(XtabML::from_json("{"), Err(XtabMLError::Json(_))));
+}
@@ -0,0 +1,224 @@
+use libxtabml::{
+    BaseSource, FlatOptions, LowBaseAction, LowBaseRules, RenderOptions, Table, TextOptions,
+    XtabMLParser,
+};
+
+mod common;
+
+const LOW_BASE_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <controltype name="base" status="secondary"><t>Base</t></controltype>
+  <table name="q1">
+    <t>Q1. Would you recommend us?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="ColumnN"/>
+    <data>
+      <r>
+        <c><v>50</v><v>48</v><v>60</v></c>
+        <c><v>120</v><v>100</v><v>20</v></c>
+      </r>
+      <r>
+        <c><v>50</v></c>
+        <c><v>120</v><v>100</v><v>20</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Spend by brand</t>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Base</t></summary>
+        <element><t>Alpha</t></element>
+        <element><t>Beta</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Age</t>
+        <element><t>Young</t></element>
+        <element><t>Old</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="Average"/>
+    <data>
+      <r>
+        <c><v>80</v><v>45</v><v>35</v></c>
+        <c><x/></c>
+      </r>
+      <r>
+        <c><v>50</v><v>30</v><v>20</v></c>
+        <c><v>12.5</v><v>11.0</v><v>14.2</v></c>
+      </r>
+      <r>
+        <c><v>30</v><v>15</v><v>15</v></c>
+        <c><v>9.5</v><v>9.0</v><v>10.0</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q3">
+    <t>Q3. Satisfaction</t>
+    <control type="base"><t>Total sample; Unweighted; base n = 25</t></control>
+    <edge axis="r"><group><element><t>Satisfied</t></element></group></edge>
+    <statistic type="ColumnPercent"/>
+    <data><r><c><v>64</v></c></r></data>
+  </table>
+</xtab>"#;
+
+fn cell(table: &Table, row: usize, statistic: &str, column: usize) -> (Option<String>, bool) {
+    let s = common::statistic(table, statistic);
+    let cell = table.data.rows[row].data_row_series[s]
+        .cell(column)
+        .unwrap();
+    (cell.value.clone(), cell.is_hidden)
+}
+
+#[test]
+fn test_hide_low_bases() {
+    let xtab = XtabMLParser::parse_str(LOW_BASE_XTABML).unwrap();
+    let rules = LowBaseRules {
+        action: LowBaseAction::Hide,
+        ..LowBaseRules::default()
+    };
+    let (hidden, cells) = xtab.apply_low_base_rules(&rules);
+
+    let q1: Vec<(usize, &str, usize, Option<&str>)> = cells
+        .iter()
+        .filter(|c| c.table == 0)
+        .map(|c| {
+            (
+                c.row,
+                c.statistic_type.as_str(),
+                c.column,
+                c.value.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        q1,
+        vec![
+            (0, "ColumnPercent", 2, Some("60")),
+            (1, "ColumnPercent", 2, Some("50"))
+        ]
+    );
+    assert!(cells
+        .iter()
+        .filter(|c| c.table == 0)
+        .all(|c| c.statistic == 0));
+    assert!(cells
+        .iter()
+        .filter(|c| c.table == 0)
+        .all(|c| c.base == 20.0 && c.source == BaseSource::Statistic));
+
+    let table = &hidden.tables[0];
+    // Hidden cells keep their values
+    assert_eq!(
+        cell(table, 0, "ColumnPercent", 2),
+        (Some("60".into()), true)
+    );
+    assert_eq!(
+        cell(table, 0, "ColumnPercent", 1),
+        (Some("48".into()), false)
+    );
+    // A compressed trailing run is expanded so only the low-base cell is hidden
+    assert_eq!(
+        cell(table, 1, "ColumnPercent", 2),
+        (Some("50".into()), true)
+    );
+    assert_eq!(
+        cell(table, 1, "ColumnPercent", 1),
+        (Some("50".into()), false)
+    );
+    assert_eq!(cell(table, 1, "ColumnN", 2), (Some("20".into()), false));
+    let note = table.controls.last().unwrap();
+    assert_eq!(note.r#type, "notes");
+    assert_eq!(note.text, "Low base (below 30): 2 cells hidden");
+    assert!(hidden.control_types.iter().any(|d| d.name == "notes"));
+
+    // The third table's base comes from its base control
+    let q3: Vec<_> = cells.iter().filter(|c| c.table == 2).collect();
+    assert_eq!(q3.len(), 1);
+    assert_eq!((q3[0].base, q3[0].source), (25.0, BaseSource::Control));
+
+    // The second table's bases are all large enough
+    assert!(hidden.tables[1].controls.is_empty());
+}
+
+#[test]
+fn test_thresholds_per_statistic() {
+    let xtab = XtabMLParser::parse_str(LOW_BASE_XTABML).unwrap();
+    let mut rules = LowBaseRules {
+        action: LowBaseAction::Replace("*".to_string()),
+        note_type: None,
+        ..LowBaseRules::default()
+    };
+    rules.thresholds.insert("Average".to_string(), 40.0);
+    let (replaced, cells) = xtab.apply_low_base_rules(&rules);
+
+    let q2: Vec<(usize, &str, usize, BaseSource)> = cells
+        .iter()
+        .filter(|c| c.table == 1)
+        .map(|c| (c.row, c.statistic_type.as_str(), c.column, c.source))
+        .collect();
+    // The base row and its counts are left alone
+    assert_eq!(
+        q2,
+        vec![
+            (1, "Average", 2, BaseSource::Summary),
+            (2, "Average", 2, BaseSource::Summary)
+        ]
+    );
+    let table = &replaced.tables[1];
+    assert_eq!(cell(table, 1, "Average", 2), (Some("*".into()), false));
+    assert_eq!(cell(table, 1, "Average", 1), (Some("11.0".into()), false));
+    assert_eq!(cell(table, 2, "Count", 2), (Some("15".into()), false));
+    assert_eq!(replaced.tables[2].controls.len(), 1);
+    assert!(!replaced.control_types.iter().any(|d| d.name == "notes"));
+
+    // Flagging only reports
+    let (flagged, again) = xtab.apply_low_base_rules(&LowBaseRules::default());
+    assert_eq!(again.len(), 3);
+    assert_eq!(
+        cell(&flagged.tables[0], 0, "ColumnPercent", 2),
+        (Some("60".into()), false)
+    );
+    assert_eq!(
+        flagged.tables[0].controls[0].text,
+        "Low base (below 30): 2 cells flagged"
+    );
+}
+
+#[test]
+fn test_render_hidden_cells() {
+    let xtab = XtabMLParser::parse_str(LOW_BASE_XTABML).unwrap();
+    let rules = LowBaseRules {
+        action: LowBaseAction::Hide,
+        ..LowBaseRules::default()
+    };
+    let (hidden, _) = xtab.apply_low_base_rules(&rules);
+
+    // 60 is the only value hidden in the first table, and appears nowhere else
+    assert!(xtab.tables[0]
+        .to_text(&TextOptions::default())
+        .contains("60"));
+    let table = &hidden.tables[0];
+    assert!(!table.to_text(&TextOptions::default()).contains("60"));
+    assert!(!table.to_markdown(&RenderOptions::default()).contains("60"));
+    assert!(!table.to_latex(&RenderOptions::default()).contains("60"));
+    assert!(!hidden.to_csv(&FlatOptions::default()).contains("60"));
+}
@@ -0,0 +1,165 @@
+use libxtabml::{NumberFormat, RenderOptions, XtabMLParser};
+use std::path::Path;
+
+const NESTED_XML: &str = r#"<xtab version="1.1">
+  <statistictype name="n"><t>Count</t></statistictype>
+  <statistictype name="ColumnPercent"><t>Column %</t></statistictype>
+  <table>
+    <t>Spend &amp; region_1</t>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Total</t></summary>
+        <group>
+          <t>Region</t>
+          <element><t>London</t></element>
+          <element><t>South | East</t></element>
+        </group>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <group><t>Gender</t><element><t>Male</t></element><element><t>Female</t></element></group>
+        <element><t>Other</t></element>
+      </group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="ColumnPercent" />
+    <data>
+      <r><c><v>100</v><v>120</v><v>10</v></c><c><v>100.000%</v></c></r>
+      <r><c><v>40</v><v>50</v><v>4</v></c><c><v>40.000%</v><v>41.667%</v><v>40.000%</v></c></r>
+      <r><c><v>60</v><v>70</v><v>6</v></c><c><v>60.000%</v><v>58.333%</v><x/></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+const HIDDEN_XML: &str = r#"<xtab version="1.1">
+  <table>
+    <t>Spend</t>
+    <edge axis="r"><group><element><t>London</t></element></group></edge>
+    <edge axis="c">
+      <group><element><t>Male</t></element><element><t>Female</t></element></group>
+    </edge>
+    <statistic type="n" />
+    <data>
+      <r><c><v>40</v><h>7</h></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_markdown_flattened_headers() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let md = xtab.tables[0].to_markdown(&RenderOptions::default());
+    let lines: Vec<&str> = md.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "|  | Statistic | Gender / Male | Gender / Female | Other |"
+    );
+    assert_eq!(lines[1], "| :-- | :-- | --: | --: | --: |");
+    assert_eq!(lines[2], "| Total | n | 100 | 120 | 10 |");
+    // Compressed runs are expanded
+    assert_eq!(
+        lines[3],
+        "|  | ColumnPercent | 100.000% | 100.000% | 100.000% |"
+    );
+    assert_eq!(lines[6], "| Region / South \\| East | n | 60 | 70 | 6 |");
+    assert_eq!(lines.len(), 2 + 3 * 2);
+}
+
+#[test]
+fn test_markdown_statistics_and_formatting() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let options = RenderOptions {
+        statistics: Some(vec!["ColumnPercent".to_string()]),
+        number_format: NumberFormat {
+            decimals: Some(1),
+            missing: "-".to_string(),
+        },
+    };
+    let md = xtab.tables[0].to_markdown(&options);
+    let lines: Vec<&str> = md.lines().collect();
+
+    // A single statistic needs no statistic column
+    assert_eq!(lines[0], "|  | Gender / Male | Gender / Female | Other |");
+    assert_eq!(lines[3], "| Region / London | 40.0% | 41.7% | 40.0% |");
+    assert_eq!(lines[4], "| Region / South \\| East | 60.0% | 58.3% | - |");
+}
+
+#[test]
+fn test_markdown_document() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let md = xtab.to_markdown(&RenderOptions::default());
+
+    assert!(md.starts_with("## q4: Age\n\n|  | % |"));
+    assert!(md.contains("| Age / Under 35 | Age / 35 and over | Gender / Male |"));
+    assert_eq!(md.matches("## ").count(), xtab.tables.len());
+}
+
+#[test]
+fn test_latex_nested_columns() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let tex = xtab.tables[0].to_latex(&RenderOptions::default());
+    let lines: Vec<&str> = tex.lines().collect();
+
+    assert_eq!(lines[0], "\\begin{tabular}{lllrrr}");
+    assert_eq!(lines[1], "\\toprule");
+    assert_eq!(lines[2], " &  &  & \\multicolumn{2}{c}{Gender} &  \\\\");
+    assert_eq!(lines[3], "\\cmidrule(lr){4-5}");
+    assert_eq!(lines[4], " &  &  & Male & Female & Other \\\\");
+    assert_eq!(lines[5], "\\midrule");
+    assert!(tex.ends_with("\\bottomrule\n\\end{tabular}\n"));
+}
+
+#[test]
+fn test_latex_row_groups_and_escaping() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let tex = xtab.tables[0].to_latex(&RenderOptions::default());
+
+    // The base has no row group heading, so it spans both label columns
+    assert!(tex.contains("\\multicolumn{2}{l}{\\multirow{2}{*}{Total}} & n & 100 & 120 & 10 \\\\"));
+    assert!(
+        tex.contains("\\multirow{4}{*}{Region} & \\multirow{2}{*}{London} & n & 40 & 50 & 4 \\\\")
+    );
+    assert!(tex.contains(" & \\multirow{2}{*}{South \\textbar{} East} & n & 60 & 70 & 6 \\\\"));
+    assert!(tex.contains(" &  & ColumnPercent & 60.000\\% & 58.333\\% &  \\\\"));
+
+    let doc = xtab.to_latex(&RenderOptions::default());
+    assert!(doc.contains("\\caption{Spend \\& region\\_1}"));
+}
+
+#[test]
+fn test_latex_example_document() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let tex = xtab.to_latex(&RenderOptions::default());
+
+    assert_eq!(tex.matches("\\begin{tabular}").count(), xtab.tables.len());
+    assert!(tex.contains("\\multicolumn{2}{c}{Age} & \\multicolumn{2}{c}{Gender} & \\multicolumn{4}{c}{Current provider} & "));
+    assert!(tex.contains("\\cmidrule(lr){2-3} \\cmidrule(lr){4-5} \\cmidrule(lr){6-9}"));
+}
+
+#[test]
+fn test_hidden_cells_are_blank() {
+    let xtab = XtabMLParser::parse_str(HIDDEN_XML).unwrap();
+    let table = &xtab.tables[0];
+    assert_eq!(
+        table.data.rows[0].data_row_series[0].cells[1]
+            .value
+            .as_deref(),
+        Some("7")
+    );
+
+    let md = table.to_markdown(&RenderOptions::default());
+    assert_eq!(md.lines().nth(2), Some("| London | 40 |  |"));
+    let tex = table.to_latex(&RenderOptions::default());
+    assert!(tex.contains("London & 40 &  \\\\"));
+    assert!(!tex.contains('7'));
+}
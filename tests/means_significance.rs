@@ -0,0 +1,115 @@
+use libxtabml::{SignificanceOptions, TTest, XtabMLError, XtabMLParser};
+
+#[macro_use]
+mod common;
+
+use common::row;
+
+const MEANS_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q7">
+    <t>Time (in weeks) by Age</t>
+    <edge axis="r">
+      <group><element><t>Time (in weeks)</t></element></group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Age</t>
+        <element><t>18-34</t></element>
+        <element><t>35-54</t></element>
+        <element><t>55+</t></element>
+      </group>
+    </edge>
+    <statistic type="Average"/>
+    <statistic type="StandardDeviation"/>
+    <statistic type="n"/>
+    <data>
+      <r>
+        <c><v>11.9</v><v>10</v><v>12</v><v>15</v></c>
+        <c><v>5.2</v><v>4</v><v>4</v><v>8</v></c>
+        <c><v>72</v><v>30</v><v>30</v><v>12</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q8">
+    <t>Time (in weeks) by Age, with standard errors</t>
+    <edge axis="r">
+      <group><element><t>Time (in weeks)</t></element></group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <t>Age</t>
+        <element><t>18-34</t></element>
+        <element><t>35-54</t></element>
+        <element><t>55+</t></element>
+      </group>
+    </edge>
+    <statistic type="Average"/>
+    <statistic type="StandardError"/>
+    <statistic type="n"/>
+    <data>
+      <r>
+        <c><v>10</v><v>12</v><v>15</v></c>
+        <c><v>0.730297</v><v>0.730297</v><v>2.309401</v></c>
+        <c><v>30</v><v>30</v><v>12</v></c>
+      </r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_welch_and_pooled_t_tests() {
+    let xtab = XtabMLParser::parse_str(MEANS_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let welch = table
+        .compare_column_means(SignificanceOptions::default())
+        .unwrap();
+    assert_eq!(row(&welch, 0, "ColumnNames"), vec!["x", "A", "B", "C"]);
+    assert_eq!(row(&welch, 0, "ColumnComparisons"), vec!["x", "", "", ""]);
+    assert_eq!(
+        row(&welch, 0, "p"),
+        vec![
+            "x",
+            "B=0.0577 C=0.0591",
+            "A=0.0577 C=0.2370",
+            "A=0.0591 B=0.2370"
+        ]
+    );
+
+    let pooled = table
+        .compare_column_means(SignificanceOptions {
+            t_test: TTest::Pooled,
+            ..SignificanceOptions::default()
+        })
+        .unwrap();
+    assert_eq!(row(&pooled, 0, "ColumnComparisons"), vec!["x", "", "", "A"]);
+    assert_eq!(row(&pooled, 0, "p")[1], "B=0.0577 C=0.0099");
+}
+
+#[test]
+fn test_means_from_standard_errors() {
+    let xtab = XtabMLParser::parse_str(MEANS_XTABML).unwrap();
+    let table = xtab.tables[1]
+        .compare_column_means(SignificanceOptions {
+            confidence: 0.90,
+            correction: libxtabml::Correction::Bonferroni,
+            ..SignificanceOptions::default()
+        })
+        .unwrap();
+    assert_eq!(row(&table, 0, "p")[0], "B=0.0577 C=0.0591");
+    assert_eq!(row(&table, 0, "pCorrected")[0], "B=0.1731 C=0.1774");
+    assert_eq!(row(&table, 0, "ColumnComparisons"), vec!["", "", ""]);
+
+    let mut without_spread = xtab.tables[1].clone();
+    without_spread.statistics[1].r#type = "Median".to_string();
+    let message = error_message!(
+        without_spread.compare_column_means(SignificanceOptions::default()),
+        XtabMLError::Significance
+    );
+    assert_eq!(
+        message,
+        "the table has no standard deviations or standard errors"
+    );
+}
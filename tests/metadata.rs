@@ -0,0 +1,117 @@
+use libxtabml::{
+    BaseSize, ClassifiedControl, ControlFacts, ControlKind, MetadataRules, XtabMLParser,
+};
+use std::path::Path;
+
+const WEIGHTED_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <controltype name="pt" status="primary"><t>Project title</t></controltype>
+  <controltype name="gf" status="primary"><t>Global filter</t></controltype>
+  <controltype name="uw" status="primary"><t>User weight</t></controltype>
+  <controltype name="bs" status="secondary"><t>Base</t></controltype>
+  <controltype name="src" status="secondary"><t>Source</t></controltype>
+  <control type="pt"><t>Brand tracker</t></control>
+  <control type="gf"><t>Wave = 3</t></control>
+  <table name="q1">
+    <t>Q1. Awareness</t>
+    <control type="uw"><t>Weighted by age and region</t></control>
+    <control type="bs"><t>Aware of Alpha; base n = 1,204</t></control>
+    <control type="gf"><t>Region = North
+Age &gt;= 18</t></control>
+    <control type="src"><t>Panel: OMN-42</t></control>
+    <edge axis="r"><group><element><t>Yes</t></element></group></edge>
+    <statistic type="Count"/>
+    <data><r><c><v>80</v></c></r></data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_example_base_controls() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/example.xte");
+    let xtab = XtabMLParser::parse_file(&path.to_string_lossy()).unwrap();
+    let rules = MetadataRules::standard();
+
+    let metadata = xtab.tables[0].metadata(&xtab, &rules);
+    assert_eq!(metadata.controls.len(), 2);
+    assert!(metadata.controls[0].report_level);
+    assert_eq!(metadata.controls[1].kind, ControlKind::Base);
+    assert_eq!(metadata.controls[1].status, Some("secondary"));
+    let facts = metadata.facts;
+    assert_eq!(facts.project.as_deref(), Some("Phone 1"));
+    assert_eq!(facts.base_description.as_deref(), Some("Total sample"));
+    assert_eq!(facts.weighted, Some(false));
+    assert_eq!(
+        facts.base_n,
+        Some(BaseSize {
+            min: 713.0,
+            max: 713.0,
+            total: None,
+            missing: None
+        })
+    );
+
+    let ranged = xtab
+        .tables
+        .iter()
+        .map(|t| t.metadata(&xtab, &rules).facts)
+        .find_map(|f| f.base_n.filter(|n| n.min != n.max))
+        .unwrap();
+    assert_eq!(
+        ranged,
+        BaseSize {
+            min: 369.0,
+            max: 711.0,
+            total: Some(713.0),
+            missing: Some(344.0)
+        }
+    );
+}
+
+#[test]
+fn test_declared_control_types() {
+    let xtab = XtabMLParser::parse_str(WEIGHTED_XTABML).unwrap();
+    let metadata = xtab.tables[0].metadata(&xtab, &MetadataRules::default());
+
+    let kinds: Vec<ControlKind> = metadata.controls.iter().map(|c| c.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ControlKind::Project,
+            ControlKind::Filter,
+            ControlKind::Weight,
+            ControlKind::Base,
+            ControlKind::Filter,
+            ControlKind::Other
+        ]
+    );
+    assert_eq!(metadata.of_kind(ControlKind::Filter).count(), 2);
+
+    let facts = &metadata.facts;
+    assert_eq!(facts.weighted, Some(true));
+    assert_eq!(facts.weight.as_deref(), Some("Weighted by age and region"));
+    assert_eq!(facts.base_description.as_deref(), Some("Aware of Alpha"));
+    assert_eq!(facts.base_n.map(|n| n.min), Some(1204.0));
+    assert_eq!(
+        facts.filters,
+        vec!["Wave = 3", "Region = North", "Age >= 18"]
+    );
+}
+
+#[test]
+fn test_vendor_patterns() {
+    let xtab = XtabMLParser::parse_str(WEIGHTED_XTABML).unwrap();
+    let mut rules = MetadataRules::standard();
+    rules.register_kind("src", ControlKind::Notes);
+    rules.add_pattern(
+        |control: &ClassifiedControl<'_>, facts: &mut ControlFacts| {
+            if let Some(panel) = control.control.text.strip_prefix("Panel: ") {
+                facts.notes.retain(|n| !n.starts_with("Panel: "));
+                facts.project = Some(format!("Brand tracker ({})", panel));
+            }
+        },
+    );
+
+    let facts = xtab.tables[0].metadata(&xtab, &rules).facts;
+    assert_eq!(facts.project.as_deref(), Some("Brand tracker (OMN-42)"));
+    assert!(facts.notes.is_empty());
+}
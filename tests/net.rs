@@ -0,0 +1,255 @@
+use libxtabml::{NetOptions, Selector, XtabMLError, XtabMLParser};
+
+#[macro_use]
+mod common;
+
+use common::row_at;
+
+const SATISFACTION_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q5">
+    <t>Q5. Satisfaction</t>
+    <edge axis="r">
+      <group>
+        <t>Satisfaction</t>
+        <element><t>Very satisfied</t></element>
+        <element><t>Satisfied</t></element>
+        <element><t>Dissatisfied</t></element>
+        <element><t>Don't know</t></element>
+      </group>
+      <group>
+        <summary type="total"><t>Total</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="ColumnN"/>
+    <statistic type="ColumnPercent"/>
+    <statistic type="Mean"/>
+    <data>
+      <r>
+        <c><v>50</v><v>20</v><v>30</v></c>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>25.0</v><v>22.2</v><v>27.3</v></c>
+        <c><v>4.0</v></c>
+      </r>
+      <r>
+        <c><v>70</v><v>30</v><v>40</v></c>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>35.0</v><v>33.3</v><v>36.4</v></c>
+        <c><v>3.0</v></c>
+      </r>
+      <r>
+        <c><v>60</v><v>30</v><v>30</v></c>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>30.0</v><v>33.3</v><v>27.3</v></c>
+        <c><v>2.0</v></c>
+      </r>
+      <r>
+        <c><v>20</v><v>10</v><v>10</v></c>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>10.0</v><v>11.1</v><v>9.1</v></c>
+        <c><x/></c>
+      </r>
+      <r>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>200</v><v>90</v><v>110</v></c>
+        <c><v>100.0</v></c>
+        <c><v>2.9</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q6">
+    <t>Q6. Brands aware of</t>
+    <edge axis="r">
+      <group>
+        <t>Brands</t>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <element><t>Brand C</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="ColumnPercent"/>
+    <data>
+      <r><c><v>120</v></c><c><v>60%</v></c></r>
+      <r><c><v>100</v></c><c><v>50%</v></c></r>
+      <r><c><v>80</v></c><c><v>40%</v></c></r>
+    </data>
+  </table>
+  <table name="q7">
+    <t>Q7. Preferred brand</t>
+    <edge axis="r">
+      <group>
+        <t>Brands</t>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <element><t>Brand C</t></element>
+        <element><t>NET</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="ColumnN"/>
+    <data>
+      <r><c><v>60</v></c><c><v>100</v></c></r>
+      <r><c><v>30</v></c><c><v>100</v></c></r>
+      <r><c><v>10</v></c><c><v>100</v></c></r>
+      <r><c><v>100</v></c><c><v>100</v></c></r>
+    </data>
+  </table>
+  <table name="q8">
+    <t>Q8. Brands aware of</t>
+    <edge axis="r">
+      <group>
+        <t>Brands</t>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="ColumnN"/>
+    <data>
+      <r><c><v>60%</v></c><c><v>200</v></c></r>
+      <r><c><v>50%</v></c><c><v>200</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_add_net() {
+    let xtab = XtabMLParser::parse_str(SATISFACTION_XTABML).unwrap();
+    let top_two: Vec<Selector> = vec!["Very satisfied".into(), "Satisfied".into()];
+    let table = xtab.tables[0]
+        .add_net(&["Satisfaction"], &top_two, "Top 2", NetOptions::default())
+        .unwrap();
+
+    assert_eq!(
+        table.row_labels(),
+        vec![
+            "Very satisfied",
+            "Satisfied",
+            "Dissatisfied",
+            "Don't know",
+            "Top 2",
+            "Total"
+        ]
+    );
+    let edge = table.row_edge.as_ref().unwrap();
+    let net = &edge.groups[0].summaries[0];
+    assert_eq!(net.r#type.as_deref(), Some("net"));
+    assert_eq!(net.position, Some(4));
+    assert_eq!(edge.groups[1].summaries[0].position, Some(5));
+
+    assert_eq!(row_at(&table, 4, 0), vec!["120", "50", "70"]);
+    assert_eq!(row_at(&table, 4, 1), vec!["200", "90", "110"]);
+    assert_eq!(row_at(&table, 4, 2), vec!["60.0", "55.6", "63.6"]);
+    assert_eq!(row_at(&table, 4, 3), vec!["x", "x", "x"]);
+    assert!(table.data.rows[4].data_row_series[3].cells[0].is_missing);
+    assert_eq!(row_at(&table, 5, 0), vec!["200", "90", "110"]);
+}
+
+#[test]
+fn test_add_net_finds_group_and_checks_leaves() {
+    let xtab = XtabMLParser::parse_str(SATISFACTION_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let bottom: Vec<Selector> = vec![Selector::Index(2)];
+    let net = table
+        .add_net(
+            &[],
+            &bottom,
+            "Bottom box",
+            NetOptions {
+                name: Some("bottom".to_string()),
+                ..NetOptions::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(net.row_labels()[4], "Bottom box");
+    assert_eq!(row_at(&net, 4, 2), vec!["30.0", "33.3", "27.3"]);
+
+    let with_total: Vec<Selector> = vec!["Satisfied".into(), "Total".into()];
+    let message = error_message!(
+        table.add_net(&[], &with_total, "All", NetOptions::default()),
+        XtabMLError::Net
+    );
+    assert_eq!(
+        message,
+        "row \"Total\" is a summary; only elements can be combined"
+    );
+    let message = error_message!(
+        table.add_net(&["Age"], &bottom, "All", NetOptions::default()),
+        XtabMLError::Net
+    );
+    assert_eq!(message, "no row group \"Age\"");
+    assert!(matches!(
+        table.add_net(&[], &["Neutral".into()], "All", NetOptions::default()),
+        Err(XtabMLError::Lookup(_))
+    ));
+}
+
+#[test]
+fn test_add_net_refuses_multi_response() {
+    let xtab = XtabMLParser::parse_str(SATISFACTION_XTABML).unwrap();
+    let table = &xtab.tables[1];
+    let leaves: Vec<Selector> = vec!["Brand A".into(), "Brand B".into()];
+
+    let message = error_message!(
+        table.add_net(&["Brands"], &leaves, "A or B", NetOptions::default()),
+        XtabMLError::MultiResponse
+    );
+    assert_eq!(
+        message,
+        "the elements of group \"Brands\" add up to 150.0% in column \"Total\"; \
+             set allow_multi_response to sum them anyway"
+    );
+
+    let options = NetOptions {
+        allow_multi_response: true,
+        ..NetOptions::default()
+    };
+    let net = table
+        .add_net(&["Brands"], &leaves, "A or B", options)
+        .unwrap();
+    assert_eq!(row_at(&net, 3, 0), vec!["220"]);
+    // No base to percentage on
+    assert_eq!(row_at(&net, 3, 1), vec!["x"]);
+}
+
+#[test]
+fn test_add_net_checks_overlap_without_q_nets() {
+    let xtab = XtabMLParser::parse_str(SATISFACTION_XTABML).unwrap();
+    let leaves: Vec<Selector> = vec!["Brand A".into(), "Brand B".into()];
+
+    // The NET element repeats the answers, so it is left out of the sum
+    let net = xtab.tables[2]
+        .add_net(&["Brands"], &leaves, "A or B", NetOptions::default())
+        .unwrap();
+    assert_eq!(row_at(&net, 4, 0), vec!["90"]);
+
+    // Without counts, the percentages show the overlap
+    let message = error_message!(
+        xtab.tables[3].add_net(&["Brands"], &leaves, "A or B", NetOptions::default()),
+        XtabMLError::MultiResponse
+    );
+    assert_eq!(
+        message,
+        "the elements of group \"Brands\" add up to 110.0% in column \"Total\"; \
+             set allow_multi_response to sum them anyway"
+    );
+}
@@ -18,7 +18,7 @@ fn test_parse_file_method() {
     assert!(result.is_ok(), "parse_file should succeed");
     
     let xtab = result.unwrap();
-    assert_eq!(xtab.version, "Observation");
+    assert_eq!(xtab.version, "1.1");
     assert!(!xtab.tables.is_empty());
 }
 
@@ -33,14 +33,14 @@ fn test_parse_file_nonexistent() {
 
 #[test]
 fn test_parse_str_method() {
-    let file_content = fs::read_to_string(&example_file_path())
+    let file_content = fs::read_to_string(example_file_path())
         .expect("Should be able to read example file");
     
     let result = XtabMLParser::parse_str(&file_content);
     assert!(result.is_ok(), "parse_str should succeed");
     
     let xtab = result.unwrap();
-    assert_eq!(xtab.version, "Observation");
+    assert_eq!(xtab.version, "1.1");
     assert!(!xtab.tables.is_empty());
 }
 
@@ -64,14 +64,14 @@ fn test_parse_str_invalid_xml() {
 
 #[test]
 fn test_parse_bytes_method() {
-    let file_content = fs::read(&example_file_path())
+    let file_content = fs::read(example_file_path())
         .expect("Should be able to read example file as bytes");
     
     let result = XtabMLParser::parse_bytes(&file_content);
     assert!(result.is_ok(), "parse_bytes should succeed");
     
     let xtab = result.unwrap();
-    assert_eq!(xtab.version, "Observation");
+    assert_eq!(xtab.version, "1.1");
     assert!(!xtab.tables.is_empty());
 }
 
@@ -87,9 +87,9 @@ fn test_parse_bytes_empty() {
 #[test]
 fn test_parsing_methods_consistency() {
     // Read the file content once
-    let file_content_str = fs::read_to_string(&example_file_path())
+    let file_content_str = fs::read_to_string(example_file_path())
         .expect("Should be able to read example file");
-    let file_content_bytes = fs::read(&example_file_path())
+    let file_content_bytes = fs::read(example_file_path())
         .expect("Should be able to read example file as bytes");
     
     // Parse using all three methods
@@ -203,7 +203,7 @@ fn test_parse_str_with_utf8() {
 #[test]
 fn test_large_file_parsing() {
     // Test that the parser can handle the relatively large example file
-    let file_content = fs::read_to_string(&example_file_path())
+    let file_content = fs::read_to_string(example_file_path())
         .expect("Should be able to read example file");
     
     // Check that the file is reasonably large
@@ -219,3 +219,35 @@ fn test_large_file_parsing() {
     let xtab = result.unwrap();
     assert!(!xtab.tables.is_empty(), "Should parse tables from large file");
 }
+
+#[test]
+fn test_parse_not_applicable_cells() {
+    let xml = r#"<xtab version="1.1">
+  <table>
+    <t>Not applicable</t>
+    <edge axis="r"><group><element><t>Yes</t></element></group></edge>
+    <edge axis="c">
+      <group><element><t>A</t></element><element><t>B</t></element><element><t>C</t></element></group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="mean" />
+    <data>
+      <r><c><v>1</v><n/><v>3</v></c><c><n>-</n><v>5</v><v>6</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+    let xtab = XtabMLParser::parse_str(xml).unwrap();
+    let series = &xtab.tables[0].data.rows[0].data_row_series;
+
+    // <n/> takes its place, so the columns after it do not shift
+    let cell = series[0].cell(1).unwrap();
+    assert!(cell.is_missing);
+    assert_eq!(cell.value, None);
+    assert_eq!(series[0].cell(2).unwrap().value.as_deref(), Some("3"));
+
+    // <n> with content keeps it but is still not applicable
+    let cell = series[1].cell(0).unwrap();
+    assert!(cell.is_missing);
+    assert_eq!(cell.value.as_deref(), Some("-"));
+    assert_eq!(series[1].cell(1).unwrap().value.as_deref(), Some("5"));
+}
@@ -0,0 +1,154 @@
+use libxtabml::{Query, XtabMLError, XtabMLParser};
+
+const TRACKER_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="awareness">
+    <t>Brand awareness</t>
+    <control type="base"><t>Total sample; base n = 500</t></control>
+    <edge axis="r">
+      <group>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+        <summary><t>NET</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <summary><t>Total</t></summary>
+      </group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+      <group>
+        <t>Region</t>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>40</v><v>38</v><v>42</v><v>41</v><v>39</v></c><c><v>200</v><v>95</v><v>105</v><v>101</v><v>99</v></c></r>
+      <r><c><v>30</v><v>31</v><v>29</v><v>30</v></c><c><v>150</v><v>78</v><v>72</v><v>75</v><v>75</v></c></r>
+      <r><c><v>55</v><v>54</v><v>56</v><v>57</v><v>53</v></c><c><v>275</v><v>135</v><v>140</v><v>143</v><v>132</v></c></r>
+    </data>
+  </table>
+  <table name="usage">
+    <t>Brand usage</t>
+    <control type="base"><t>Aware of any brand; base n = 410</t></control>
+    <edge axis="r">
+      <group>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <summary><t>Total</t></summary>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>120</v></c></r>
+      <r><c><v>80</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_query_selects_and_slices_tables() {
+    let xtab = XtabMLParser::parse_str(TRACKER_XTABML).unwrap();
+    let query = Query::parse(
+        r#"tables where control.base contains "total sample" and statistic = ColumnPercent
+           | rows "NET" | cols group "Gender""#,
+    )
+    .unwrap();
+    let slices = xtab.query(&query);
+
+    assert_eq!(slices.len(), 1);
+    assert_eq!(slices[0].table_index, 0);
+    assert_eq!(slices[0].rows, vec![2]);
+    assert_eq!(slices[0].columns, vec![1, 2]);
+    assert_eq!(slices[0].statistics, vec![0, 1]);
+}
+
+#[test]
+fn test_query_conditions() {
+    let xtab = XtabMLParser::parse_str(TRACKER_XTABML).unwrap();
+    let indices = |query: &str| -> Vec<usize> {
+        xtab.query(&query.parse().unwrap())
+            .iter()
+            .map(|s| s.table_index)
+            .collect()
+    };
+
+    assert_eq!(indices("tables"), vec![0, 1]);
+    assert_eq!(indices(r#"cols contains "region""#), vec![0]);
+    assert_eq!(indices("statistic != ColumnPercent"), vec![1]);
+    assert_eq!(indices("not (index = 1 or name = awareness)"), vec![1]);
+    if cfg!(feature = "regex") {
+        assert_eq!(indices(r#"title ~ "^Brand (usage|share)$""#), vec![1]);
+    }
+    assert_eq!(
+        indices(r#"| rows contains "brand" | stats Count"#),
+        vec![0, 1]
+    );
+    assert_eq!(indices(r#"tables | rows "NET""#), vec![0]);
+}
+
+#[test]
+fn test_filter_builds_subset_tables() {
+    let xtab = XtabMLParser::parse_str(TRACKER_XTABML).unwrap();
+    let query = Query::parse("index = 1 | cols group Region | stats Count").unwrap();
+    let filtered = xtab.filter(&query);
+
+    assert_eq!(filtered.tables.len(), 1);
+    let table = &filtered.tables[0];
+    assert_eq!(table.statistic_types(), vec!["Count"]);
+    assert_eq!(table.row_labels(), vec!["Brand A", "Brand B", "NET"]);
+    assert_eq!(table.column_labels(), vec!["North", "South"]);
+    let edge = table.column_edge.as_ref().unwrap();
+    assert_eq!(edge.groups.len(), 1);
+    assert_eq!(edge.groups[0].text.as_deref(), Some("Region"));
+    let values: Vec<&str> = table.data.rows[1].data_row_series[0]
+        .cells
+        .iter()
+        .map(|c| c.value.as_deref().unwrap())
+        .collect();
+    assert_eq!(values, vec!["75", "75"]);
+
+    // The compressed run at the end of the second row is expanded
+    let query = Query::parse("index = 1 | cols group Region | stats ColumnPercent").unwrap();
+    let filtered = xtab.filter(&query);
+    let values: Vec<&str> = filtered.tables[0].data.rows[1].data_row_series[0]
+        .cells
+        .iter()
+        .map(|c| c.value.as_deref().unwrap())
+        .collect();
+    assert_eq!(values, vec!["30", "30"]);
+}
+
+#[test]
+fn test_query_errors() {
+    for (query, message) in [
+        ("title contains", "expected a value at the end"),
+        ("colour = red", "expected a field at offset 0, found colour"),
+        (
+            r#"title = "x" | pages 2"#,
+            "expected `rows`, `cols` or `stats` at offset 14, found pages",
+        ),
+        (r#"title = "open"#, "unterminated string at offset 8"),
+        ("(title = x", "expected `)` at the end"),
+        (
+            r#"index = 1 | rows != "x""#,
+            "expected a value at offset 17, found !=",
+        ),
+    ] {
+        match Query::parse(query) {
+            Err(XtabMLError::Query(m)) => assert_eq!(m, message, "{}", query),
+            other => panic!("{}: {:?}", query, other.map(|_| ())),
+        }
+    }
+}
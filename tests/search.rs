@@ -0,0 +1,98 @@
+use libxtabml::{NodeKind, SearchPattern, XtabMLParser};
+
+const NPS_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <control type="project"><t>Brand tracker, wave 3</t></control>
+  <table name="nps">
+    <t>Recommendation by region</t>
+    <control type="base"><t>All respondents</t></control>
+    <edge axis="r">
+      <group>
+        <t>Net Promoter Score</t>
+        <element><t>Promoters</t></element>
+        <element><t>Detractors</t></element>
+        <summary type="net"><t>NPS</t></summary>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <summary><t>Total</t></summary>
+      </group>
+      <group>
+        <t>Region</t>
+        <element><t>North</t></element>
+        <element><t>South</t></element>
+      </group>
+    </edge>
+    <statistic type="Percent"/>
+    <data>
+      <r><c><v>40</v><v>38</v><v>42</v></c></r>
+      <r><c><v>20</v><v>21</v><v>19</v></c></r>
+      <r><c><v>20</v><v>17</v><v>23</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_substring_search() {
+    let xtab = XtabMLParser::parse_str(NPS_XTABML).unwrap();
+    let hits = xtab.search(&SearchPattern::Substring("region".to_string()));
+
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].kind, NodeKind::Title);
+    assert_eq!(hits[0].table_index, Some(0));
+    assert_eq!(hits[0].path, "/xtab/table[1]/t");
+    assert_eq!(hits[1].kind, NodeKind::Group);
+    assert_eq!(hits[1].axis.as_deref(), Some("c"));
+    assert_eq!(hits[1].path, "/xtab/table[1]/edge[@axis='c']/group[2]/t");
+    assert_eq!(hits[1].text, "Region");
+}
+
+#[test]
+fn test_search_controls_and_summaries() {
+    let xtab = XtabMLParser::parse_str(NPS_XTABML).unwrap();
+
+    let hits = xtab.search(&SearchPattern::Substring("WAVE".to_string()));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].kind, NodeKind::Control);
+    assert_eq!(hits[0].table_index, None);
+    assert_eq!(hits[0].path, "/xtab/control[1]");
+
+    let hits = xtab.search(&SearchPattern::Substring("nps".to_string()));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].kind, NodeKind::Summary);
+    assert_eq!(
+        hits[0].path,
+        "/xtab/table[1]/edge[@axis='r']/group[1]/summary[1]/t"
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_regex_search() {
+    let xtab = XtabMLParser::parse_str(NPS_XTABML).unwrap();
+    let pattern = SearchPattern::Regex(regex::Regex::new("^(North|South)$").unwrap());
+    let paths: Vec<String> = xtab.search(&pattern).into_iter().map(|h| h.path).collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            "/xtab/table[1]/edge[@axis='c']/group[2]/element[1]/t",
+            "/xtab/table[1]/edge[@axis='c']/group[2]/element[2]/t",
+        ]
+    );
+}
+
+#[test]
+fn test_fuzzy_search() {
+    let xtab = XtabMLParser::parse_str(NPS_XTABML).unwrap();
+    let hits = xtab.search(&SearchPattern::Fuzzy("net prom".to_string()));
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].text, "Net Promoter Score");
+
+    let hits = xtab.search(&SearchPattern::Fuzzy("nt".to_string()));
+    let best = hits.iter().max_by_key(|h| h.score).unwrap();
+    assert_eq!(best.text, "Net Promoter Score");
+    assert!(hits.iter().any(|h| h.text == "Recommendation by region"));
+}
@@ -0,0 +1,185 @@
+use libxtabml::{Correction, SignificanceOptions, XtabMLError, XtabMLParser};
+
+#[macro_use]
+mod common;
+
+use common::row;
+
+const BANNER_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Would you recommend us?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group><summary><t>Total</t></summary></group>
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+      <group>
+        <t>Age</t>
+        <element><t>18-34</t></element>
+        <element><t>35-54</t></element>
+        <element><t>55+</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="ColumnN"/>
+    <statistic type="EffectiveColumnN"/>
+    <data>
+      <r>
+        <c><v>50</v><v>60</v><v>40</v><v>70</v><v>50</v><v>30</v></c>
+        <c><v>400</v><v>200</v><v>200</v><v>100</v></c>
+        <c><v>40</v><v>20</v><v>20</v><v>100</v></c>
+      </r>
+      <r>
+        <c><v>50</v><v>40</v><v>60</v><v>30</v><v>50</v><x/></c>
+        <c><v>400</v><v>200</v><v>200</v><v>100</v></c>
+        <c><v>40</v><v>20</v><v>20</v><v>100</v></c>
+      </r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Spend</t>
+    <edge axis="r"><group><element><t>Spend</t></element></group></edge>
+    <edge axis="c"><group><summary><t>Total</t></summary></group></edge>
+    <statistic type="Average"/>
+    <data><r><c><v>12.5</v></c></r></data>
+  </table>
+</xtab>"#;
+
+fn actual_bases(confidence: f64, correction: Correction) -> SignificanceOptions {
+    SignificanceOptions {
+        confidence,
+        correction,
+        effective_base: false,
+        ..SignificanceOptions::default()
+    }
+}
+
+#[test]
+fn test_compare_column_proportions() {
+    let xtab = XtabMLParser::parse_str(BANNER_XTABML).unwrap();
+    let table = xtab.tables[0]
+        .compare_column_proportions(actual_bases(0.95, Correction::None))
+        .unwrap();
+
+    let names = table
+        .statistics
+        .iter()
+        .find(|s| s.r#type == "ColumnComparisons")
+        .unwrap();
+    assert_eq!(names.datatype.as_deref(), Some("string"));
+    assert_eq!(
+        row(&table, 0, "ColumnNames"),
+        vec!["x", "A", "B", "C", "D", "E"]
+    );
+    assert_eq!(
+        row(&table, 0, "ColumnComparisons"),
+        vec!["x", "B", "", "D E", "E", ""]
+    );
+    // Missing percentages are left out of the comparisons
+    assert_eq!(
+        row(&table, 1, "ColumnComparisons"),
+        vec!["x", "", "A", "", "C", ""]
+    );
+
+    // Testing again replaces the statistics
+    let again = table
+        .compare_column_proportions(actual_bases(0.95, Correction::None))
+        .unwrap();
+    assert_eq!(again.statistics.len(), table.statistics.len());
+}
+
+#[test]
+fn test_corrections() {
+    let xtab = XtabMLParser::parse_str(BANNER_XTABML).unwrap();
+    let comparisons = |correction| {
+        let table = xtab.tables[0]
+            .compare_column_proportions(actual_bases(0.99, correction))
+            .unwrap();
+        row(&table, 0, "ColumnComparisons")[3..].to_vec()
+    };
+    // The two age differences of 20 points have p = 0.0039
+    assert_eq!(comparisons(Correction::None), vec!["D E", "E", ""]);
+    assert_eq!(comparisons(Correction::Bonferroni), vec!["E", "", ""]);
+    assert_eq!(comparisons(Correction::Holm), vec!["D E", "E", ""]);
+    assert_eq!(
+        comparisons(Correction::FalseDiscoveryRate),
+        vec!["D E", "E", ""]
+    );
+
+    // Age differences with distinct p-values, which the corrections treat differently
+    let xml = BANNER_XTABML.replace("<v>70</v><v>50</v><v>30</v>", "<v>60</v><v>50</v><v>35</v>");
+    let xtab = XtabMLParser::parse_str(&xml).unwrap();
+    let tested = |correction| {
+        let table = xtab.tables[0]
+            .compare_column_proportions(actual_bases(0.95, correction))
+            .unwrap();
+        (
+            row(&table, 0, "ColumnComparisons")[3..].to_vec(),
+            row(&table, 0, "pCorrected")[3..].to_vec(),
+        )
+    };
+    // p = 0.1552, 0.0319 and 0.0004 for 18-34/35-54, 35-54/55+ and 18-34/55+
+    let (letters, p) = tested(Correction::None);
+    assert_eq!(letters, vec!["E", "E", ""]);
+    assert_eq!(p[1], "C=0.1552 E=0.0319");
+    // Holm multiplies the second smallest by 2, losing 35-54 over 55+
+    let (letters, p) = tested(Correction::Holm);
+    assert_eq!(letters, vec!["E", "", ""]);
+    assert_eq!(p[1], "C=0.1552 E=0.0638");
+    // Benjamini-Hochberg multiplies it by 3/2, keeping it
+    let (letters, p) = tested(Correction::FalseDiscoveryRate);
+    assert_eq!(letters, vec!["E", "E", ""]);
+    assert_eq!(p[1], "C=0.1552 E=0.0479");
+    assert_eq!(p[2], "C=0.0012 D=0.0479");
+}
+
+#[test]
+fn test_effective_bases() {
+    let xtab = XtabMLParser::parse_str(BANNER_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let effective = table
+        .compare_column_proportions(SignificanceOptions::default())
+        .unwrap();
+    assert_eq!(row(&effective, 0, "ColumnComparisons")[1], "");
+    assert_eq!(row(&effective, 0, "ColumnComparisons")[3], "D E");
+
+    let deflated = table
+        .compare_column_proportions(SignificanceOptions {
+            effective_base: false,
+            design_effect: 10.0,
+            ..SignificanceOptions::default()
+        })
+        .unwrap();
+    assert_eq!(row(&deflated, 0, "ColumnComparisons")[1], "");
+
+    // A cell's effective n is not the base of its percentage, so the column base is used
+    let mut cell_sizes = table.clone();
+    cell_sizes.statistics[2].r#type = "EffectiveN".to_string();
+    let tested = cell_sizes
+        .compare_column_proportions(SignificanceOptions::default())
+        .unwrap();
+    let actual = table
+        .compare_column_proportions(SignificanceOptions {
+            effective_base: false,
+            ..SignificanceOptions::default()
+        })
+        .unwrap();
+    assert_eq!(row(&tested, 0, "p"), row(&actual, 0, "p"));
+    assert_ne!(row(&tested, 0, "p"), row(&effective, 0, "p"));
+
+    let message = error_message!(
+        xtab.tables[1].compare_column_proportions(SignificanceOptions::default()),
+        XtabMLError::Significance
+    );
+    assert_eq!(message, "the table has no column percentages");
+}
@@ -0,0 +1,171 @@
+#![cfg(feature = "sqlite")]
+
+use libxtabml::{SqliteExporter, XtabMLParser};
+use rusqlite::Connection;
+use std::path::Path;
+
+const HIDDEN_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Would you recommend us?</t>
+    <edge axis="r"><group><element><t>Yes</t></element></group></edge>
+    <edge axis="c">
+      <group><element><t>Male</t></element><element><t>Female</t></element></group>
+    </edge>
+    <statistic type="Count" datatype="integer"/>
+    <data><r><c><v>12</v><h>3</h></c></r></data>
+  </table>
+</xtab>"#;
+
+fn parse_example_file() -> libxtabml::XtabML {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte");
+    XtabMLParser::parse_file(path.to_str().unwrap()).expect("Failed to parse example file")
+}
+
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn test_export_counts() {
+    let xtab = parse_example_file();
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.export(&xtab, Some("example.xte")).unwrap();
+    let conn = exporter.connection();
+
+    assert_eq!(count(conn, "SELECT COUNT(*) FROM documents"), 1);
+    assert_eq!(
+        count(conn, "SELECT COUNT(*) FROM tables"),
+        xtab.tables.len() as i64
+    );
+    assert_eq!(
+        count(conn, "SELECT COUNT(*) FROM controls WHERE table_id IS NULL"),
+        1
+    );
+    assert_eq!(
+        count(conn, "SELECT COUNT(*) FROM edges"),
+        2 * xtab.tables.len() as i64
+    );
+
+    let expected_cells: usize = xtab
+        .tables
+        .iter()
+        .map(|t| t.data.rows.len() * t.column_edge.as_ref().unwrap().leaf_count())
+        .sum();
+    assert_eq!(
+        count(conn, "SELECT COUNT(*) FROM cells"),
+        expected_cells as i64
+    );
+}
+
+#[test]
+fn test_export_group_tree() {
+    let xtab = parse_example_file();
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.export(&xtab, None).unwrap();
+    let conn = exporter.connection();
+
+    // "Female" sits under the "Gender" group of the second table's banner
+    let heading: String = conn
+        .query_row(
+            "SELECT parent.text FROM edge_nodes node
+             JOIN edge_nodes parent ON parent.id = node.parent_id
+             WHERE node.text = 'Female'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(heading, "Gender");
+}
+
+#[test]
+fn test_export_labelled_cell_query() {
+    let xtab = parse_example_file();
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.export(&xtab, None).unwrap();
+    let conn = exporter.connection();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT row_node.text, cells.value, cells.number FROM cells
+             JOIN statistics ON statistics.id = cells.statistic_id
+             JOIN edge_nodes column_node ON column_node.id = cells.column_node_id
+             JOIN edge_nodes row_node ON row_node.id = cells.row_node_id
+             WHERE statistics.type = 'ColumnPercent' AND column_node.text = 'Female'
+             ORDER BY cells.row_position",
+        )
+        .unwrap();
+    let rows: Vec<(String, String, f64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(rows.len(), 12);
+    assert_eq!(rows[0].0, "AAPT/Cellular One");
+    assert_eq!(rows[0].1, "6.383%");
+    assert!((rows[0].2 - 6.383).abs() < 1e-9);
+}
+
+#[test]
+fn test_append_and_create_modes() {
+    let xtab = parse_example_file();
+    let path = std::env::temp_dir().join(format!("libxtabml-sqlite-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    SqliteExporter::create(&path)
+        .unwrap()
+        .export(&xtab, Some("wave 1"))
+        .unwrap();
+    SqliteExporter::append(&path)
+        .unwrap()
+        .export(&xtab, Some("wave 2"))
+        .unwrap();
+    let conn = SqliteExporter::append(&path).unwrap().into_connection();
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM documents"), 2);
+    assert_eq!(
+        count(&conn, "SELECT COUNT(DISTINCT document_id) FROM tables"),
+        2
+    );
+    drop(conn);
+
+    // create starts again from an empty database
+    SqliteExporter::create(&path)
+        .unwrap()
+        .export(&xtab, Some("wave 3"))
+        .unwrap();
+    let conn = Connection::open(&path).unwrap();
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM documents"), 1);
+    drop(conn);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_export_hidden_cells() {
+    let xtab = XtabMLParser::parse_str(HIDDEN_XTABML).unwrap();
+    let mut exporter =
+        SqliteExporter::from_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.export(&xtab, None).unwrap();
+    let conn = exporter.connection();
+
+    let mut stmt = conn
+        .prepare("SELECT value, is_hidden FROM cells ORDER BY column_position")
+        .unwrap();
+    let cells: Vec<(String, bool)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(cells, vec![("12".into(), false), ("3".into(), true)]);
+
+    let datatype: String = conn
+        .query_row("SELECT datatype FROM statistics", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(datatype, "integer");
+}
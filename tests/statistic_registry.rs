@@ -0,0 +1,173 @@
+use libxtabml::{
+    Basis, SignificanceOptions, StandardStatistic, StatisticRegistry, XtabMLError, XtabMLParser,
+};
+use std::path::Path;
+
+#[macro_use]
+mod common;
+
+const VENDOR_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="q1">
+    <t>Q1. Would you recommend us?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <t>Gender</t>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Col%"/>
+    <statistic type="Base"/>
+    <statistic type="xs:t"/>
+    <data>
+      <r>
+        <c><v>60</v><v>40</v></c>
+        <c><v>200</v><v>200</v></c>
+        <c><v>120</v><v>80</v></c>
+      </r>
+      <r>
+        <c><v>40</v><v>60</v></c>
+        <c><v>200</v><v>200</v></c>
+        <c><v>80</v><v>120</v></c>
+      </r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_standard_vocabularies() {
+    let registry = StatisticRegistry::standard();
+    for name in ["xs:cp", "ColumnPercent", "Percent"] {
+        assert_eq!(
+            registry.lookup(name),
+            Some(StandardStatistic::ColumnPercent)
+        );
+    }
+    assert_eq!(registry.lookup("xs:mean"), Some(StandardStatistic::Mean));
+    assert_eq!(registry.lookup("Average"), Some(StandardStatistic::Mean));
+    assert_eq!(
+        registry.lookup("xs:rt"),
+        Some(StandardStatistic::UnweightedCount)
+    );
+    assert_eq!(
+        registry.lookup("n"),
+        Some(StandardStatistic::UnweightedCount)
+    );
+    assert_eq!(
+        registry.lookup("Percentile25"),
+        Some(StandardStatistic::Percentile(25))
+    );
+    assert_eq!(registry.lookup("Labels"), None);
+    assert_eq!(StatisticRegistry::xtabml().lookup("ColumnPercent"), None);
+
+    let percent = StandardStatistic::RowGroupPercent;
+    assert!(percent.is_percentage() && !percent.is_count() && !percent.is_base());
+    assert_eq!(percent.percentaged_on(), Some(Basis::RowGroup));
+    assert_eq!(percent.xtabml_name(), Some("xs:lcp"));
+
+    let base = registry.lookup("EffectiveColumnN").unwrap();
+    assert!(base.is_base() && base.is_effective());
+    assert_eq!(base.base_of(), Some(Basis::Column));
+    assert_eq!(base.percentaged_on(), None);
+    assert!(registry.lookup("xs:t").unwrap().is_count());
+    assert_eq!(
+        registry.names(StandardStatistic::RowPercent),
+        vec!["RowPct", "RowPercent", "xs:rp"]
+    );
+}
+
+#[test]
+fn test_registered_names() {
+    let xtab = XtabMLParser::parse_str(VENDOR_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    let standard = StatisticRegistry::standard();
+    assert_eq!(
+        table.standard_statistics(&standard),
+        vec![
+            None,
+            Some(StandardStatistic::ColumnBase),
+            Some(StandardStatistic::Count)
+        ]
+    );
+    let message = error_message!(
+        table.compare_column_proportions(SignificanceOptions::default()),
+        XtabMLError::Significance
+    );
+    assert_eq!(message, "the table has no column percentages");
+
+    let mut registry = StatisticRegistry::standard();
+    assert_eq!(
+        registry.register("Col%", StandardStatistic::ColumnPercent),
+        None
+    );
+    assert_eq!(
+        table.find_statistic(
+            &registry,
+            &[StandardStatistic::Mean, StandardStatistic::ColumnPercent]
+        ),
+        Some(0)
+    );
+    let tested = table
+        .compare_column_proportions(SignificanceOptions {
+            statistics: registry,
+            ..SignificanceOptions::default()
+        })
+        .unwrap();
+    let comparisons = tested
+        .statistics
+        .iter()
+        .position(|s| s.r#type == "ColumnComparisons")
+        .unwrap();
+    let cell = |row: usize, column: usize| {
+        tested.data.rows[row].data_row_series[comparisons]
+            .cell(column)
+            .and_then(|c| c.value.clone())
+    };
+    // 60% against 40% of 200 each gives z = 4
+    assert_eq!(cell(0, 0).as_deref(), Some("B"));
+    assert_eq!(cell(1, 1).as_deref(), Some("A"));
+}
+
+#[test]
+fn test_q_vocabulary_coverage() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/example.xte");
+    let xtab = XtabMLParser::parse_file(path.to_str().unwrap()).unwrap();
+    let registry = StatisticRegistry::standard();
+    let unregistered: Vec<&str> = xtab
+        .statistic_types
+        .iter()
+        .map(|t| t.name.as_str())
+        .filter(|name| registry.lookup(name).is_none())
+        .collect();
+    assert_eq!(
+        unregistered,
+        vec![
+            "Values",
+            "Labels",
+            "Text",
+            "TextNoBlanks",
+            "UniqueText",
+            "multipleComparisonAdjustment",
+            "NotDuplicate",
+            "Observation"
+        ]
+    );
+
+    let probability = registry.lookup("ProbabilityPercent").unwrap();
+    assert!(probability.is_percentage());
+    assert_eq!(probability.percentaged_on(), None);
+    for name in ["WW", "BaseWW", "ColumnWW", "RowWW"] {
+        assert_eq!(
+            registry.lookup(name),
+            Some(StandardStatistic::SumOfSquaredWeights)
+        );
+    }
+}
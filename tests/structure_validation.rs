@@ -0,0 +1,117 @@
+use libxtabml::{validate_bytes, validate_str, Rule, Severity};
+use std::path::Path;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+const FAULTY_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <controltype name="base"><t>Base</t></controltype>
+  <statistictype name="Count"><t>Count</t></statistictype>
+  <table name="short">
+    <t>Too few rows</t>
+    <control type="weight"><t>Unweighted</t></control>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <statistic type="Percent"/>
+    <data>
+      <r><c><v>1</v><v>2</v><v>3</v></c><c><v>50</v></c></r>
+    </data>
+  </table>
+  <table name="flat">
+    <t>No columns</t>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>1</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_example_file_is_valid() {
+    let bytes = std::fs::read(example_file_path()).unwrap();
+    assert_eq!(validate_bytes(&bytes), Vec::new());
+}
+
+#[test]
+fn test_structural_diagnostics() {
+    let diagnostics = validate_str(FAULTY_XTABML);
+    let found: Vec<(Rule, usize, Option<usize>)> = diagnostics
+        .iter()
+        .map(|d| (d.rule, d.line, d.table))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (Rule::RowCount, 5, Some(0)),
+            (Rule::UndeclaredControlType, 7, Some(0)),
+            (Rule::UndeclaredStatisticType, 21, Some(0)),
+            (Rule::CellCount, 23, Some(0)),
+            (Rule::MissingColumnEdge, 26, Some(1)),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        "table 1 has 1 rows of data, but its row edge has 2 leaves"
+    );
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+    assert_eq!(diagnostics[3].severity, Severity::Error);
+    assert_eq!(
+        diagnostics[3].to_string(),
+        "line 23: error[cell-count]: row 1 of table 1 has 3 cells, but the column edge has 2 leaves"
+    );
+}
+
+#[test]
+fn test_series_count_and_malformed_xml() {
+    let xml = FAULTY_XTABML.replace("<c><v>50</v></c>", "");
+    let diagnostics = validate_str(&xml);
+    assert!(diagnostics
+        .iter()
+        .any(|d| d.rule == Rule::SeriesCount && d.line == 23));
+
+    let diagnostics = validate_str("<xtab>\n  <table>\n    <t>x</t>\n</xtab>\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].rule, Rule::Xml);
+    assert_eq!(diagnostics[0].line, 4);
+}
+
+#[test]
+fn test_not_applicable_cells() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <statistictype name="Count"><t>Count</t></statistictype>
+  <table name="q1">
+    <t>Not applicable</t>
+    <edge axis="r"><group><element><t>Yes</t></element></group></edge>
+    <edge axis="c">
+      <group>
+        <element><t>Male</t></element>
+        <element><t>Female</t></element>
+      </group>
+    </edge>
+    <statistic type="Count"/>
+    <data>
+      <r><c><n/></c></r>
+    </data>
+  </table>
+</xtab>"#;
+    assert_eq!(validate_str(xml), Vec::new());
+}
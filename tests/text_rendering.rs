@@ -0,0 +1,151 @@
+use libxtabml::{BoxStyle, Overflow, RenderOptions, TextOptions, XtabMLParser};
+use std::path::Path;
+
+const NESTED_XML: &str = r#"<xtab version="1.1">
+  <statistictype name="n"><t>Count</t></statistictype>
+  <statistictype name="ColumnPercent"><t>Column %</t></statistictype>
+  <table>
+    <t>Spend by region</t>
+    <control type="base"><t>All adults</t></control>
+    <edge axis="r">
+      <group>
+        <summary type="xs:base"><t>Total</t></summary>
+        <group>
+          <t>Region</t>
+          <element><t>London</t></element>
+          <element><t>South East</t></element>
+        </group>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <group><t>Gender</t><element><t>Male</t></element><element><t>Female</t></element></group>
+        <element><t>Other</t></element>
+      </group>
+    </edge>
+    <statistic type="n" />
+    <statistic type="ColumnPercent" />
+    <data>
+      <r><c><v>100</v><v>120</v><v>10</v></c><c><v>100.0%</v></c></r>
+      <r><c><v>40</v><v>50</v><v>4</v></c><c><v>40.0%</v><v>41.7%</v><v>40.0%</v></c></r>
+      <r><c><v>60</v><v>70</v><v>6</v></c><c><v>60.0%</v><v>58.3%</v><x/></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_stacked_column_headers() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let text = xtab.tables[0].to_text(&TextOptions::default());
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "┌──────────────────────────────┬─────────────────┬────────┐"
+    );
+    assert_eq!(
+        lines[1],
+        "│                              │     Gender      │        │"
+    );
+    assert_eq!(
+        lines[2],
+        "│                              ├────────┬────────┤        │"
+    );
+    assert_eq!(
+        lines[3],
+        "│                              │   Male │ Female │  Other │"
+    );
+    assert_eq!(
+        lines[4],
+        "├──────────────┬───────────────┼────────┼────────┼────────┤"
+    );
+    assert!(text.ends_with("└──────────────┴───────────────┴────────┴────────┴────────┘\n"));
+}
+
+#[test]
+fn test_statistic_sub_rows_and_row_groups() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let text = xtab.table_to_text(&xtab.tables[0], &TextOptions::default());
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "Spend by region");
+    assert_eq!(lines[1], "base: All adults");
+    // Statistics are labelled by their display text and compressed runs are expanded
+    assert_eq!(
+        lines[7],
+        "│ Total        │ Count    │    100 │    120 │     10 │"
+    );
+    assert_eq!(
+        lines[8],
+        "│              │ Column % │ 100.0% │ 100.0% │ 100.0% │"
+    );
+    // Row group headings get a line of their own, with the leaves indented under them
+    assert_eq!(
+        lines[10],
+        "│ Region       │          │        │        │        │"
+    );
+    assert_eq!(
+        lines[11],
+        "│   London     │ Count    │     40 │     50 │      4 │"
+    );
+    assert_eq!(
+        lines[15],
+        "│              │ Column % │  60.0% │  58.3% │        │"
+    );
+}
+
+#[test]
+fn test_ascii_pages() {
+    let xtab = XtabMLParser::parse_str(NESTED_XML).unwrap();
+    let options = TextOptions {
+        style: BoxStyle::Ascii,
+        width: Some(44),
+        overflow: Overflow::Pages,
+        ..TextOptions::default()
+    };
+    let text = xtab.tables[0].to_text(&options);
+    let pages: Vec<&str> = text.split('\x0c').collect();
+
+    assert_eq!(pages.len(), 3);
+    assert!(pages[0].starts_with("+------------------------------+--------+\n"));
+    assert!(pages[0].contains("|                              | Gender |\n"));
+    assert!(pages[1].contains("|                              | Female |\n"));
+    assert!(pages[2].ends_with("Page 3 of 3\n"));
+    assert!(text.lines().all(|l| l.chars().count() <= 44));
+}
+
+#[test]
+fn test_wrap_to_width() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let options = TextOptions {
+        width: Some(80),
+        render: RenderOptions::default(),
+        ..TextOptions::default()
+    };
+    let text = xtab.to_text(&options);
+
+    assert!(text.lines().all(|l| l.chars().count() <= 80));
+    // Every table title is printed once, continuation blocks repeat the row labels
+    assert_eq!(text.matches("Q20. Image\n").count(), 1);
+    assert!(text.matches("│ Vodafone             │ %").count() > 1);
+    // A long group heading is wrapped inside its span
+    assert!(text.contains("│ Current  │"));
+}
+
+#[test]
+fn test_hidden_cells_are_blank() {
+    let xml = NESTED_XML.replace("<v>6</v>", "<h>6</h>");
+    let xtab = XtabMLParser::parse_str(&xml).unwrap();
+    let text = xtab.tables[0].to_text(&TextOptions::default());
+
+    assert!(text.contains("│   South East │ n             │     60 │     70 │        │"));
+    assert!(!text.contains(" 6 "));
+}
@@ -0,0 +1,321 @@
+use libxtabml::{LookupError, Selector, SortOptions, SortOrder, XtabMLParser};
+use std::path::Path;
+
+mod common;
+
+use common::values;
+
+fn example_file_path() -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources")
+        .join("example.xte")
+        .to_string_lossy()
+        .to_string()
+}
+
+const NESTED_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="nested">
+    <t>Brand by region</t>
+    <edge axis="r">
+      <group>
+        <element><t>Brand A</t></element>
+        <element><t>Brand B</t></element>
+      </group>
+    </edge>
+    <edge axis="c" name="banner">
+      <group>
+        <summary><t>Total</t></summary>
+      </group>
+      <group>
+        <t>Region</t>
+        <group>
+          <t>East</t>
+          <element><t>North-east</t></element>
+          <element><t>South-east</t></element>
+        </group>
+        <element><t>West</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>40</v><v>38</v><v>42</v><v>41</v></c><c><v>200</v><v>95</v><v>105</v><v>101</v></c></r>
+      <r><c><v>30</v><v>31</v><x/></c><c><v>150</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_transpose_swaps_edges_and_data() {
+    let xtab = XtabMLParser::parse_str(NESTED_XTABML).unwrap();
+    let transposed = xtab.tables[0].transpose();
+
+    let rows = transposed.row_edge.as_ref().unwrap();
+    assert_eq!(rows.axis, "r");
+    assert_eq!(rows.name.as_deref(), Some("banner"));
+    assert_eq!(rows.groups[1].groups[0].text.as_deref(), Some("East"));
+    assert_eq!(
+        transposed.row_labels(),
+        vec!["Total", "North-east", "South-east", "West"]
+    );
+    assert_eq!(transposed.column_edge.as_ref().unwrap().axis, "c");
+    assert_eq!(transposed.column_labels(), vec!["Brand A", "Brand B"]);
+
+    // The trailing missing cell of Brand B repeats, as does its single count
+    assert_eq!(
+        values(&transposed, 0),
+        vec![
+            vec!["40", "30"],
+            vec!["38", "31"],
+            vec!["42", "x"],
+            vec!["41", "x"]
+        ]
+    );
+    assert_eq!(
+        values(&transposed, 1),
+        vec![
+            vec!["200", "150"],
+            vec!["95", "150"],
+            vec!["105", "150"],
+            vec!["101", "150"]
+        ]
+    );
+    assert_eq!(
+        transposed
+            .cell("South-east", "Brand A", "Count")
+            .unwrap()
+            .value
+            .as_deref(),
+        Some("105")
+    );
+}
+
+#[test]
+fn test_transpose_twice_expands_runs() {
+    let xtab = XtabMLParser::parse_str(NESTED_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let back = table.transpose().transpose();
+
+    assert_eq!(back.row_labels(), table.row_labels());
+    assert_eq!(back.column_labels(), table.column_labels());
+    assert_eq!(values(&back, 0)[0], values(table, 0)[0]);
+    assert_eq!(values(&back, 1)[1], vec!["150", "150", "150", "150"]);
+}
+
+#[test]
+fn test_transpose_example_frequency_table() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let table = &xtab.tables[0];
+    let transposed = table.transpose();
+
+    assert_eq!(transposed.data.rows.len(), 1);
+    assert_eq!(transposed.row_labels(), vec!["%"]);
+    assert_eq!(transposed.column_labels(), table.row_labels());
+    assert_eq!(transposed.column_count(), table.data.rows.len());
+    let cell = transposed.cell("%", "20-24 yrs", "Percent").unwrap();
+    let original = table.cell("20-24 yrs", "%", "Percent").unwrap();
+    assert_eq!(cell.value, original.value);
+}
+
+#[test]
+fn test_select_prunes_edges_and_statistics() {
+    let xtab = XtabMLParser::parse_str(NESTED_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let columns = [
+        Selector::from("Total"),
+        Selector::from(["East", "South-east"]),
+    ];
+    let selected = table
+        .select(None, Some(&columns), Some(&["Count"]))
+        .unwrap();
+
+    assert_eq!(selected.statistic_types(), vec!["Count"]);
+    assert_eq!(selected.row_labels(), vec!["Brand A", "Brand B"]);
+    assert_eq!(selected.column_labels(), vec!["Total", "South-east"]);
+
+    let edge = selected.column_edge.as_ref().unwrap();
+    assert_eq!(edge.groups.len(), 2);
+    let region = &edge.groups[1];
+    assert_eq!(region.text.as_deref(), Some("Region"));
+    assert!(region.elements.is_empty());
+    assert_eq!(region.groups[0].text.as_deref(), Some("East"));
+    assert_eq!(region.groups[0].elements[0].position, Some(1));
+
+    assert_eq!(
+        values(&selected, 0),
+        vec![vec!["200", "105"], vec!["150", "150"]]
+    );
+    assert!(selected
+        .data
+        .rows
+        .iter()
+        .all(|r| r.data_row_series.len() == 1));
+}
+
+#[test]
+fn test_select_errors() {
+    let xtab = XtabMLParser::parse_str(NESTED_XTABML).unwrap();
+    let table = &xtab.tables[0];
+
+    assert_eq!(
+        table.select(None, None, Some(&["RowPercent"])).unwrap_err(),
+        LookupError::UnknownStatistic("RowPercent".to_string())
+    );
+    assert!(matches!(
+        table.select(Some(&["Brand C".into()]), None, None),
+        Err(LookupError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_select_client_ready_example() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let table = &xtab.tables[0];
+    let rows: Vec<Selector> = table
+        .row_labels()
+        .into_iter()
+        .filter(|label| label != "NET" && label != "Don't know")
+        .map(Selector::from)
+        .collect();
+    let selected = table.select(Some(&rows), None, None).unwrap();
+
+    assert_eq!(selected.data.rows.len(), table.data.rows.len() - 2);
+    assert_eq!(selected.row_labels().last().unwrap(), "65 and over");
+    assert_eq!(selected.controls.len(), table.controls.len());
+    assert_eq!(selected.title, table.title);
+}
+
+const BRANDS_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <table name="brands">
+    <t>Preferred brand</t>
+    <edge axis="r">
+      <group>
+        <t>Supermarkets</t>
+        <element><t>Brand A</t></element>
+        <element><t>Other supermarket</t></element>
+        <element><t>Brand B</t></element>
+        <summary><t>NET</t></summary>
+        <element><t>Brand C</t></element>
+      </group>
+      <group>
+        <t>Online</t>
+        <element><t>Shop X</t></element>
+        <element><t>Shop Y</t></element>
+      </group>
+      <group>
+        <element><t>Don't know</t></element>
+        <element><t>None</t></element>
+      </group>
+    </edge>
+    <edge axis="c">
+      <group>
+        <summary><t>Total</t></summary>
+        <element><t>Male</t></element>
+      </group>
+    </edge>
+    <statistic type="ColumnPercent"/>
+    <statistic type="Count"/>
+    <data>
+      <r><c><v>20</v><v>1</v></c><c><v>200</v></c></r>
+      <r><c><v>40</v><v>2</v></c><c><v>400</v></c></r>
+      <r><c><v>35</v><v>3</v></c><c><v>350</v></c></r>
+      <r><c><v>95</v><v>4</v></c><c><v>950</v></c></r>
+      <r><c><x/><v>5</v></c><c><v>0</v></c></r>
+      <r><c><v>2</v><v>6</v></c><c><v>20</v></c></r>
+      <r><c><v>3</v><v>7</v></c><c><v>30</v></c></r>
+      <r><c><v>4</v><v>8</v></c><c><v>40</v></c></r>
+      <r><c><v>1</v><v>9</v></c><c><v>10</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+#[test]
+fn test_sort_rows_by_keeps_summaries_and_groups() {
+    let xtab = XtabMLParser::parse_str(BRANDS_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let options = SortOptions {
+        order: SortOrder::Descending,
+        anchors: vec!["other*".to_string(), "Don't know".to_string()],
+    };
+    let sorted = table
+        .sort_rows_by("Total", "ColumnPercent", options)
+        .unwrap();
+
+    assert_eq!(
+        sorted.row_labels(),
+        vec![
+            "Brand B",
+            "Brand A",
+            "Brand C",
+            "NET",
+            "Other supermarket",
+            "Shop Y",
+            "Shop X",
+            "None",
+            "Don't know",
+        ]
+    );
+    // Brand C has no percentage so it goes after the other brands, before anchored rows;
+    // the counts moved with their rows
+    let counts: Vec<&str> = sorted
+        .data
+        .rows
+        .iter()
+        .map(|r| r.data_row_series[1].cells[0].value.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        counts,
+        vec!["350", "200", "0", "950", "400", "30", "20", "10", "40"]
+    );
+    assert_eq!(
+        sorted
+            .cell("Shop Y", "Male", "ColumnPercent")
+            .unwrap()
+            .value
+            .as_deref(),
+        Some("7")
+    );
+
+    let supermarkets = &sorted.row_edge.as_ref().unwrap().groups[0];
+    assert_eq!(supermarkets.summaries[0].position, Some(3));
+    let positions: Vec<Option<usize>> = supermarkets.elements.iter().map(|e| e.position).collect();
+    assert_eq!(positions, vec![Some(0), Some(1), Some(2), Some(4)]);
+}
+
+#[test]
+fn test_sort_rows_ascending_by_other_column() {
+    let xtab = XtabMLParser::parse_str(BRANDS_XTABML).unwrap();
+    let table = &xtab.tables[0];
+    let sorted = table
+        .sort_rows_by(["Male"], "ColumnPercent", SortOrder::Ascending)
+        .unwrap();
+    assert_eq!(sorted.row_labels(), table.row_labels());
+
+    let sorted = table
+        .sort_rows_by(0, "Count", SortOrder::Ascending)
+        .unwrap();
+    assert_eq!(
+        sorted.row_labels()[..5],
+        ["Brand C", "Brand A", "Brand B", "NET", "Other supermarket"]
+    );
+    assert!(matches!(
+        table.sort_rows_by("Female", "Count", SortOrder::Ascending),
+        Err(LookupError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_sort_rows_by_keeps_q_nets() {
+    let xtab = XtabMLParser::parse_file(&example_file_path()).unwrap();
+    let table = &xtab.tables[1];
+    let labels = table.row_labels();
+    let net = labels.iter().position(|l| l == "NET").unwrap();
+    let sorted = table
+        .sort_rows_by("Male", "ColumnPercent", SortOrder::Descending)
+        .unwrap();
+    // Q writes NETs as elements; they keep their places like summaries
+    assert_eq!(sorted.row_labels()[net], "NET");
+    assert_ne!(sorted.row_labels(), labels);
+}
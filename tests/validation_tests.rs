@@ -22,17 +22,17 @@ fn test_document_structure_validation() {
     
     // Validate root document structure
     assert!(!xtab.version.is_empty(), "Version should not be empty");
-    assert_eq!(xtab.version, "Observation", "Should match expected version");
+    assert_eq!(xtab.version, "1.1", "Should match expected version");
     
     // Validate that required fields are present (parser doesn't parse these currently)
-    assert!(xtab.date.is_none(), "Date is not parsed by current implementation");
-    assert!(xtab.time.is_none(), "Time is not parsed by current implementation");
-    assert!(xtab.user.is_none(), "User is not parsed by current implementation");
+    assert!(xtab.date.is_some(), "Date should be parsed");
+    assert!(xtab.time.is_some(), "Time should be parsed");
+    assert!(xtab.user.is_some(), "User should be parsed");
     
     // Validate that collections are initialized (even if empty)
-    assert!(!xtab.languages.is_empty() || true, "Languages collection should exist");
-    assert!(xtab.control_types.is_empty(), "Control types are not parsed by current implementation");
-    assert!(xtab.statistic_types.is_empty(), "Statistic types are not parsed by current implementation");
+    assert!(xtab.languages.iter().all(|l| !l.lang.is_empty()), "Languages collection should exist");
+    assert!(!xtab.control_types.is_empty(), "Control types should be parsed");
+    assert!(!xtab.statistic_types.is_empty(), "Statistic types should be parsed");
     assert!(!xtab.controls.is_empty(), "Should have controls");
     assert!(!xtab.tables.is_empty(), "Should have tables");
 }
@@ -41,9 +41,8 @@ fn test_document_structure_validation() {
 fn test_control_types_validation() {
     let xtab = parse_example_file();
     
-    // Control types are not being parsed by current parser
     let control_types = &xtab.control_types;
-    assert!(control_types.is_empty(), "Control types are not parsed by current implementation");
+    assert!(!control_types.is_empty(), "Control types should be parsed");
     
     // Validate each control type has required fields
     for control_type in control_types {
@@ -56,18 +55,17 @@ fn test_control_types_validation() {
                    "Status should be 'primary' or 'secondary', got: {}", status);
         }
     }
-    
-    // Control types are not being parsed by current parser
-    assert!(control_types.is_empty(), "Control types are not parsed by current implementation");
+
+    // The example declares both primary and secondary control types
+    assert!(control_types.iter().any(|c| c.status.as_deref() == Some("secondary")));
 }
 
 #[test]
 fn test_statistic_types_validation() {
     let xtab = parse_example_file();
     
-    // Statistic types are not being parsed by current parser
     let statistic_types = &xtab.statistic_types;
-    assert!(statistic_types.is_empty(), "Statistic types are not parsed by current implementation");
+    assert!(!statistic_types.is_empty(), "Statistic types should be parsed");
     
     // Validate each statistic type has required fields
     for stat_type in statistic_types {
@@ -75,8 +73,9 @@ fn test_statistic_types_validation() {
         assert!(!stat_type.text.is_empty(), "Statistic type text should not be empty");
     }
     
-    // Statistic types are not being parsed by current parser
-    assert!(statistic_types.is_empty(), "Statistic types are not parsed by current implementation");
+    // The first declaration in the example file
+    assert_eq!(statistic_types[0].name, "Values");
+    assert_eq!(statistic_types[0].text, "Values");
 }
 
 #[test]
@@ -230,12 +229,13 @@ fn test_first_table_specific_validation() {
         .sum();
     assert_eq!(row_elements_count, 11);
     
-    // Column edge should have 0 summaries (first table has neither elements nor summaries)
+    // Column edge should have 1 summary ("%") and no elements
     let col_edge = first_table.column_edge.as_ref().unwrap();
     let col_summaries_count: usize = col_edge.groups.iter()
         .map(|g| g.summaries.len())
         .sum();
-    assert_eq!(col_summaries_count, 0);
+    assert_eq!(col_summaries_count, 1);
+    assert_eq!(col_edge.leaf_count(), 1);
     
     // Data should have 11 rows
     assert_eq!(first_table.data.rows.len(), 11);
@@ -257,17 +257,40 @@ fn test_first_table_specific_validation() {
 fn test_table_with_summary_validation() {
     let xtab = parse_example_file();
     
-    // Current parser doesn't parse summaries properly
-    // All tables have empty summaries in the parser output
-    for table in &xtab.tables {
+    // Only the first table has a summary in its column edge
+    for (table_idx, table) in xtab.tables.iter().enumerate() {
         if let Some(col_edge) = &table.column_edge {
-            let has_summary = col_edge.groups.iter()
-                .any(|g| !g.summaries.is_empty());
-            assert!(!has_summary, "Current parser doesn't parse summaries correctly");
+            let has_summary = col_edge.leaves().iter().any(|l| l.node.is_summary());
+            assert_eq!(has_summary, table_idx == 0,
+                      "Table {} column edge summary presence", table_idx);
         }
     }
 }
 
+#[test]
+fn test_nested_group_validation() {
+    let xtab = parse_example_file();
+    let table = &xtab.tables[1];
+    let col_edge = table.column_edge.as_ref().unwrap();
+
+    // The banner is a single top-level group with one nested group per variable
+    assert_eq!(col_edge.groups.len(), 1);
+    let headings: Vec<Option<&str>> = col_edge.groups[0].groups.iter()
+        .map(|g| g.text.as_deref())
+        .collect();
+    assert_eq!(headings, vec![Some("Age"), Some("Gender"), Some("Current provider"), None]);
+
+    // Leaves are in data order and match the number of cells per row
+    let leaves = col_edge.leaves();
+    assert_eq!(leaves.len(), table.shape().1);
+    assert_eq!(leaves[2].text(), "Male");
+    assert_eq!(leaves[2].headings(), vec!["Gender"]);
+    for (idx, leaf) in leaves.iter().enumerate() {
+        assert_eq!(leaf.node.position(), Some(idx));
+    }
+    assert_eq!(table.column_labels().len(), 9);
+}
+
 #[test]
 fn test_data_consistency_validation() {
     let xtab = parse_example_file();
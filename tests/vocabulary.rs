@@ -0,0 +1,139 @@
+#![cfg(all(feature = "json", feature = "toml"))]
+
+use libxtabml::{Vocabulary, XtabML, XtabMLError, XtabMLParser};
+
+#[macro_use]
+mod common;
+
+const AGENCY_XTABML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xtab version="1.1">
+  <controltype name="wt"><t>Weight</t></controltype>
+  <controltype name="pt"><t>Project</t></controltype>
+  <statistictype name="xs:t"><t>Count</t></statistictype>
+  <statistictype name="xs:cp"><t>Column %</t></statistictype>
+  <statistictype name="Percent"><t>%</t></statistictype>
+  <statistictype name="xs:mean"><t>Mean</t></statistictype>
+  <control type="pt"><t>Brand tracker</t></control>
+  <table name="q1">
+    <t>Q1. Would you recommend us?</t>
+    <control type="wt"><t>Weighted by age and gender</t></control>
+    <edge axis="r">
+      <group>
+        <element><t>Yes</t></element>
+        <element><t>No</t></element>
+      </group>
+    </edge>
+    <statistic type="xs:t"/>
+    <statistic type="xs:cp"/>
+    <data>
+      <r><c><v>120</v></c><c><v>60</v></c></r>
+      <r><c><v>80</v></c><c><v>40</v></c></r>
+    </data>
+  </table>
+  <table name="q2">
+    <t>Q2. Which brands do you know?</t>
+    <edge axis="r">
+      <group>
+        <element><t>Alpha</t></element>
+      </group>
+    </edge>
+    <statistic type="Percent"/>
+    <statistic type="xs:cp"/>
+    <data>
+      <r><c><v>55</v></c><c><v>55</v></c></r>
+    </data>
+  </table>
+</xtab>"#;
+
+const Q_VOCABULARY: &str = r#"
+[statistics]
+"xs:t" = "Count"
+"xs:cp" = "ColumnPercent"
+
+[controls]
+wt = "weight"
+"#;
+
+fn statistic_names(xtab: &XtabML, table: usize) -> Vec<&str> {
+    xtab.tables[table].statistic_types()
+}
+
+#[test]
+fn test_translate() {
+    let mut xtab = XtabMLParser::parse_str(AGENCY_XTABML).unwrap();
+    xtab.tables.truncate(1);
+    let vocabulary = Vocabulary::from_toml(Q_VOCABULARY).unwrap();
+    let (translated, report) = xtab.translate(&vocabulary).unwrap();
+
+    assert_eq!(
+        statistic_names(&translated, 0),
+        vec!["Count", "ColumnPercent"]
+    );
+    let series = &translated.tables[0].data.rows[1].data_row_series[1];
+    assert_eq!(series.statistic.as_ref().unwrap().r#type, "ColumnPercent");
+    assert_eq!(translated.tables[0].controls[0].r#type, "weight");
+    let declared: Vec<&str> = translated
+        .statistic_types
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(
+        declared,
+        vec!["Count", "ColumnPercent", "Percent", "xs:mean"]
+    );
+    assert_eq!(translated.control_types[0].name, "weight");
+
+    assert!(!report.is_complete());
+    assert_eq!(report.unmapped_statistics, vec!["Percent", "xs:mean"]);
+    assert_eq!(report.unmapped_controls, vec!["pt"]);
+
+    // Translating again changes nothing more
+    let (again, _) = translated.translate(&vocabulary).unwrap();
+    assert_eq!(statistic_names(&again, 0), vec!["Count", "ColumnPercent"]);
+}
+
+#[test]
+fn test_merged_names() {
+    let xtab = XtabMLParser::parse_str(AGENCY_XTABML).unwrap();
+    let vocabulary = Vocabulary::from_json(
+        r#"{"statistics": {"Percent": "ColumnPercent", "xs:cp": "ColumnPercent"}}"#,
+    )
+    .unwrap();
+
+    // Table q2 has both, so they cannot share a name
+    let message = error_message!(xtab.translate(&vocabulary), XtabMLError::Translation);
+    assert_eq!(
+        message,
+        "statistics \"Percent\" and \"xs:cp\" of table \"q2\" would both become \"ColumnPercent\""
+    );
+
+    let mut xtab = xtab;
+    xtab.tables.truncate(1);
+    let (translated, report) = xtab.translate(&vocabulary).unwrap();
+    let declared: Vec<&str> = translated
+        .statistic_types
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(declared, vec!["xs:t", "ColumnPercent", "xs:mean"]);
+    assert_eq!(translated.statistic_types[1].text, "Column %");
+    assert_eq!(report.unmapped_controls, vec!["pt", "wt"]);
+}
+
+#[test]
+fn test_vocabulary_files() {
+    let path =
+        std::env::temp_dir().join(format!("libxtabml-vocabulary-{}.toml", std::process::id()));
+    std::fs::write(&path, Q_VOCABULARY).unwrap();
+    let vocabulary = Vocabulary::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(vocabulary.statistic("xs:cp"), Some("ColumnPercent"));
+    assert_eq!(vocabulary.statistic("ColumnPercent"), Some("ColumnPercent"));
+    assert_eq!(vocabulary.statistic("Average"), None);
+    assert_eq!(vocabulary.control("wt"), Some("weight"));
+
+    error_message!(
+        Vocabulary::from_file("vocabulary.yaml"),
+        XtabMLError::Unsupported
+    );
+}